@@ -0,0 +1,73 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use big_num::{encode, parse, Base, BigNum};
+
+//pow multiplies by a clone of the base on every step and encode clones and compares on every
+//digit, so both lean on cheap clones
+fn pow(c: &mut Criterion) {
+    let base = BigNum::from(u64::MAX - 58) * BigNum::from(u64::MAX - 82);
+    c.bench_function("pow 3-limb base ^ 200", |b| {
+        b.iter(|| black_box(base.clone()).pow(BigNum::from(200)))
+    });
+    let small = BigNum::from(3);
+    c.bench_function("pow 3 ^ 1000", |b| {
+        b.iter(|| black_box(small.clone()).pow(BigNum::from(1000)))
+    });
+}
+
+fn encode_digits(c: &mut Criterion) {
+    let value = BigNum::from(7).pow(BigNum::from(700));
+    c.bench_function("encode 592 decimal digits", |b| {
+        b.iter(|| encode(black_box(value.clone()), Base::Decimal))
+    });
+    let value = BigNum::from(u64::MAX - 1).pow(BigNum::from(40));
+    c.bench_function("encode 40 limbs as hexadecimal", |b| {
+        b.iter(|| encode(black_box(value.clone()), Base::Hexadecimal))
+    });
+}
+
+//parse multiplies by the base and adds a digit for every character, which is where single
+//limbs staying inline pay off, and the calculator parses every literal it reads
+fn parse_digits(c: &mut Criterion) {
+    let literals: Vec<String> = (0..100u64)
+        .map(|i| (i * 7919 + 12345).to_string())
+        .collect();
+    c.bench_function("parse 100 small integers", |b| {
+        b.iter(|| {
+            for literal in literals.iter() {
+                black_box(parse(black_box(literal), Base::Decimal));
+            }
+        })
+    });
+    c.bench_function("parse 20 digit decimal fraction", |b| {
+        b.iter(|| parse(black_box("3.14159265358979323846"), Base::Decimal))
+    });
+    let digits = BigNum::from(7).pow(BigNum::from(700)).to_string();
+    c.bench_function("parse 592 decimal digits", |b| {
+        b.iter(|| parse(black_box(&digits), Base::Decimal))
+    });
+}
+
+//the same products through Mul and through mul_into with one output reused for all of them
+fn into_buffers(c: &mut Criterion) {
+    let a = BigNum::from(u64::MAX - 58).pow(BigNum::from(4));
+    let b = BigNum::from(u64::MAX - 82).pow(BigNum::from(4));
+    c.bench_function("1000 products with Mul", |bench| {
+        bench.iter(|| {
+            for _ in 0..1000 {
+                black_box(black_box(&a) * black_box(&b));
+            }
+        })
+    });
+    let mut out = BigNum::with_capacity(8);
+    c.bench_function("1000 products with mul_into", |bench| {
+        bench.iter(|| {
+            for _ in 0..1000 {
+                BigNum::mul_into(black_box(&a), black_box(&b), &mut out);
+            }
+        })
+    });
+}
+
+criterion_group!(benches, pow, encode_digits, parse_digits, into_buffers);
+criterion_main!(benches);
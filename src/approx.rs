@@ -0,0 +1,429 @@
+use std::fmt::{self, Display};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::big_num::BigNum;
+use crate::codec::{encode, Base};
+use crate::decimal::{div_round, RoundingMode};
+use crate::functions::{limbs_for, DomainError};
+use crate::interval::BigInterval;
+use crate::limbs;
+use crate::rational::BigRational;
+
+//a value together with a bound on how far the true value can be from it, so the number of
+//correct bits is known after every operation instead of just how many limbs are stored
+//add, sub and mul carry the bound exactly, division and the roots and powers also add the
+//rounding they do themselves, and encode stops at the last digit the bound still supports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigApprox {
+    value: BigNum,
+    //never negative, zero for exact values
+    error: BigNum,
+}
+
+fn abs(x: &BigNum) -> BigNum {
+    if x < &BigNum::new() {
+        -x
+    } else {
+        x.clone()
+    }
+}
+
+//an upper bound for e kept to two limbs so error bounds stay cheap to carry around
+fn round_up(e: BigNum) -> BigNum {
+    match e.log2_floor() {
+        Some(top) if e.get_precision() > 2 => {
+            e.round_to_limbs(2) + BigNum::from(1).mul_pow2(top - 127)
+        }
+        _ => e,
+    }
+}
+
+//the most the true value of q can differ from q after rounding toward zero to n limbs
+fn truncation_error(q: &BigNum, n: usize) -> BigNum {
+    match q.log2_floor() {
+        None => BigNum::new(),
+        Some(top) => BigNum::from(1).mul_pow2(top - 64 * (n as i64 - 1)),
+    }
+}
+
+//an upper bound for a / b with a, b >= 0
+fn div_up(a: &BigNum, b: &BigNum) -> BigNum {
+    let q = a.div_to_limbs(b, 2);
+    round_up(&q + truncation_error(&q, 2))
+}
+
+impl BigApprox {
+    //value with |true value - value| <= error
+    //limbs of value below the error are dropped (and the bound grows to cover them), so inexact
+    //values stay about as long as they are accurate
+    pub fn new(value: BigNum, error: BigNum) -> BigApprox {
+        let mut error = abs(&error);
+        let mut value = value;
+        if let (Some(top), Some(bottom)) = (value.log2_floor(), error.log2_floor()) {
+            let keep = limbs_for((top - bottom).max(0) as usize + 64);
+            if value.get_precision() > keep {
+                value = value.round_to_limbs(keep);
+                error = error + truncation_error(&value, keep);
+            }
+        }
+        BigApprox {
+            value,
+            error: round_up(error),
+        }
+    }
+
+    pub fn exact(value: BigNum) -> BigApprox {
+        BigApprox::new(value, BigNum::new())
+    }
+
+    //value known to about `bits` significant bits, an error of |value| 2^-bits
+    pub fn with_significant_bits(value: BigNum, bits: usize) -> BigApprox {
+        let error = abs(&value).mul_pow2(-(bits as i64));
+        BigApprox::new(value, error)
+    }
+
+    pub fn value(&self) -> &BigNum {
+        &self.value
+    }
+
+    pub fn error(&self) -> &BigNum {
+        &self.error
+    }
+
+    pub fn is_exact(&self) -> bool {
+        self.error.is_zero()
+    }
+
+    //every value the true one could be
+    pub fn to_interval(&self) -> BigInterval {
+        BigInterval::new(&self.value - &self.error, &self.value + &self.error)
+    }
+
+    //roughly log2(|value| / error), how many leading bits are right
+    //None for exact values, and negative when the error is larger than the value
+    pub fn significant_bits(&self) -> Option<i64> {
+        let error = self.error.log2_floor()?;
+        Some(self.value.log2_floor().map_or(i64::MIN, |top| top - error))
+    }
+
+    //bits to work at for something that only needs to be as good as self
+    fn working_bits(&self) -> usize {
+        match self.significant_bits() {
+            Some(bits) => bits.max(0) as usize + 16,
+            None => 64 * self.value.get_precision().max(1) + 64,
+        }
+    }
+
+    //self / rhs, None when rhs could be zero
+    pub fn checked_div(&self, rhs: &BigApprox) -> Option<BigApprox> {
+        let b = abs(&rhs.value);
+        if b <= rhs.error {
+            return None;
+        }
+        //|a / b - A / B| <= (|b| ea + |a| eb) / (|b| (|b| - eb))
+        let propagated = div_up(
+            &(&b * &self.error + abs(&self.value) * &rhs.error),
+            &(&b * (&b - &rhs.error)),
+        );
+        let n = if propagated.is_zero() {
+            //exact operands get the limbs Div would give them
+            self.value.get_precision() + rhs.value.get_precision()
+        } else {
+            let top = self.value.log2_floor().unwrap_or(0) - b.log2_floor().unwrap() + 1;
+            let bits = top - propagated.log2_floor().unwrap() + 8;
+            limbs_for(bits.max(0) as usize)
+        };
+        let q = self.value.div_to_limbs(&rhs.value, n);
+        let error = propagated + truncation_error(&q, q.get_precision());
+        Some(BigApprox::new(q, error))
+    }
+
+    //f(self) for f increasing or decreasing on [value - error, value + error]: the spread of f
+    //over that range plus 2^-bits relative error from computing f itself
+    fn monotonic(&self, bits: usize, f: impl Fn(&BigNum, usize) -> BigNum) -> BigApprox {
+        let lo = &self.value - &self.error;
+        let hi = &self.value + &self.error;
+        let value = f(&self.value, bits + 8);
+        let (f_lo, f_hi) = if self.is_exact() {
+            (value.clone(), value.clone())
+        } else {
+            (f(&lo, bits + 8), f(&hi, bits + 8))
+        };
+        let spread = abs(&(&f_lo - &value)).max(abs(&(&f_hi - &value)));
+        let largest = abs(&f_lo).max(abs(&f_hi)).max(abs(&value));
+        let slack = largest.mul_pow2(2 - bits as i64);
+        BigApprox::new(value, spread + slack)
+    }
+
+    pub fn sqrt(&self) -> Result<BigApprox, DomainError> {
+        if &self.value + &self.error < BigNum::new() {
+            return Err(DomainError::new("square root of a negative number"));
+        }
+        let clamped = BigApprox {
+            value: self.value.clone().max(BigNum::new()),
+            error: self.error.clone(),
+        };
+        let bits = self.working_bits();
+        Ok(clamped.monotonic(bits, |x, bits| {
+            x.clone().max(BigNum::new()).sqrt_to_limbs(limbs_for(bits))
+        }))
+    }
+
+    //the n-th root of a non negative value
+    pub fn root(&self, n: u64) -> Result<BigApprox, DomainError> {
+        assert!(n > 0, "zeroth root");
+        if &self.value - &self.error <= BigNum::new() {
+            return Err(DomainError::new("root of a value that may not be positive"));
+        }
+        let bits = self.working_bits();
+        let n = BigNum::from(n);
+        Ok(self.monotonic(bits, |x, bits| {
+            let log = x.ln(bits + 64).unwrap();
+            log.div_to_limbs(&n, limbs_for(bits + 64)).exp(bits)
+        }))
+    }
+
+    //self^exp for an exact exponent
+    //integer exponents multiply (and divide for negative ones) so the bound comes from those,
+    //other exponents need a positive value and go through e^(exp ln self)
+    pub fn pow(&self, exp: &BigNum) -> Result<BigApprox, DomainError> {
+        if exp.is_integer() {
+            let n = exp.magnitude();
+            let mut result = BigApprox::exact(BigNum::from(1));
+            for i in (0..limbs::bit_len(&n)).rev() {
+                result = &result * &result;
+                if limbs::bit(&n, i) {
+                    result = &result * self;
+                }
+            }
+            if exp < &BigNum::new() {
+                return BigApprox::exact(BigNum::from(1))
+                    .checked_div(&result)
+                    .ok_or_else(|| DomainError::new("negative power of a value that may be zero"));
+            }
+            return Ok(result);
+        }
+        if &self.value - &self.error <= BigNum::new() {
+            return Err(DomainError::new(
+                "fractional power of a value that may not be positive",
+            ));
+        }
+        //the relative error grows with |exp ln x|, so work with that many more bits
+        let extra = (exp * self.value.ln(64).unwrap())
+            .log2_floor()
+            .unwrap_or(0)
+            .max(0) as usize;
+        let bits = self.working_bits() + extra;
+        Ok(self.monotonic(bits, |x, bits| {
+            (exp * x.ln(bits + extra + 64).unwrap()).exp(bits)
+        }))
+    }
+
+    //only the digits the error bound supports: the value is rounded at the last place whose
+    //half unit still covers the error, so the true value is within one unit of the last digit
+    //when that place is left of the point the missing places are written as a power of the
+    //base, like 12*10^3
+    pub fn encode(&self, base: Base) -> String {
+        if self.is_exact() {
+            return encode(self.value.clone(), base);
+        }
+        let b = BigNum::from(base as u64);
+        let twice_error = self.error.clone().mul_pow2(1);
+        let power = |places: i64| b.clone().pow(BigNum::from(places.unsigned_abs()));
+        //base^-places >= 2 error
+        let fits = |places: i64| {
+            if places >= 0 {
+                &twice_error * power(places) <= BigNum::from(1)
+            } else {
+                power(places) >= twice_error
+            }
+        };
+        //start a little to the left of the right place and step right while it still fits
+        let log2_base = (base as u64 as f64).log2();
+        let estimate = (-(twice_error.log2_floor().unwrap() + 1)) as f64 / log2_base;
+        let mut places = estimate.floor() as i64 - 1;
+        while !fits(places) {
+            places -= 1;
+        }
+        while fits(places + 1) {
+            places += 1;
+        }
+
+        let exact = BigRational::from(self.value.clone());
+        let digits = if places >= 0 {
+            div_round(
+                &(exact.numer() * power(places)),
+                exact.denom(),
+                RoundingMode::HalfEven,
+            )
+        } else {
+            div_round(
+                exact.numer(),
+                &(exact.denom() * power(places)),
+                RoundingMode::HalfEven,
+            )
+        };
+
+        let negative = digits < BigNum::new();
+        let mut text = encode(abs(&digits), base);
+        if places < 0 {
+            text = format!("{}*{}^{}", text, base as u64, -places);
+        } else if places > 0 {
+            let places = places as usize;
+            if text.len() <= places {
+                text.insert_str(0, &"0".repeat(places + 1 - text.len()));
+            }
+            text.insert(text.len() - places, '.');
+        }
+        if negative {
+            text.insert(0, '-');
+        }
+        text
+    }
+}
+
+impl From<BigNum> for BigApprox {
+    fn from(value: BigNum) -> Self {
+        BigApprox::exact(value)
+    }
+}
+
+impl Add for BigApprox {
+    type Output = BigApprox;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl Add for &BigApprox {
+    type Output = BigApprox;
+    fn add(self, rhs: Self) -> Self::Output {
+        BigApprox::new(&self.value + &rhs.value, &self.error + &rhs.error)
+    }
+}
+
+impl Add<&BigApprox> for BigApprox {
+    type Output = BigApprox;
+    fn add(self, rhs: &BigApprox) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<BigApprox> for &BigApprox {
+    type Output = BigApprox;
+    fn add(self, rhs: BigApprox) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for BigApprox {
+    type Output = BigApprox;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl Sub for &BigApprox {
+    type Output = BigApprox;
+    fn sub(self, rhs: Self) -> Self::Output {
+        BigApprox::new(&self.value - &rhs.value, &self.error + &rhs.error)
+    }
+}
+
+impl Sub<&BigApprox> for BigApprox {
+    type Output = BigApprox;
+    fn sub(self, rhs: &BigApprox) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<BigApprox> for &BigApprox {
+    type Output = BigApprox;
+    fn sub(self, rhs: BigApprox) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl Mul for BigApprox {
+    type Output = BigApprox;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+//|ab - AB| <= |a| eb + |b| ea + ea eb
+impl Mul for &BigApprox {
+    type Output = BigApprox;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let error = abs(&self.value) * &rhs.error
+            + abs(&rhs.value) * &self.error
+            + &self.error * &rhs.error;
+        BigApprox::new(&self.value * &rhs.value, error)
+    }
+}
+
+impl Mul<&BigApprox> for BigApprox {
+    type Output = BigApprox;
+    fn mul(self, rhs: &BigApprox) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<BigApprox> for &BigApprox {
+    type Output = BigApprox;
+    fn mul(self, rhs: BigApprox) -> Self::Output {
+        self * &rhs
+    }
+}
+
+impl Div for &BigApprox {
+    type Output = BigApprox;
+    fn div(self, rhs: Self) -> Self::Output {
+        self.checked_div(rhs)
+            .expect("Divide by a value that may be zero")
+    }
+}
+
+impl Div for BigApprox {
+    type Output = BigApprox;
+    fn div(self, rhs: Self) -> Self::Output {
+        &self / &rhs
+    }
+}
+
+impl Div<&BigApprox> for BigApprox {
+    type Output = BigApprox;
+    fn div(self, rhs: &BigApprox) -> Self::Output {
+        &self / rhs
+    }
+}
+
+impl Div<BigApprox> for &BigApprox {
+    type Output = BigApprox;
+    fn div(self, rhs: BigApprox) -> Self::Output {
+        self / &rhs
+    }
+}
+
+impl Neg for BigApprox {
+    type Output = BigApprox;
+    fn neg(self) -> Self::Output {
+        BigApprox {
+            value: -self.value,
+            error: self.error,
+        }
+    }
+}
+
+impl Neg for &BigApprox {
+    type Output = BigApprox;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+impl Display for BigApprox {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.encode(Base::Decimal))
+    }
+}
@@ -1,17 +1,53 @@
 use std::cmp::{Eq, Ord, Ordering, PartialEq, PartialOrd};
+use std::error::Error;
+use std::fmt::{self, Display};
 use std::ops::{Add, Div, Mul, Neg, Rem, Sub};
 
+use crate::limbs;
+use crate::parts::Parts;
+
 #[derive(Debug, Clone, Default)]
 pub struct BigNum {
-    parts: Vec<u64>,
-    exp: i32,
+    parts: Parts,
+    //in limbs, kept between MIN_EXP and MAX_EXP for anything that isn't zero
+    exp: i64,
     neg: bool,
 }
 
+//a result whose exponent would leave MIN_EXP..=MAX_EXP
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExponentError {
+    Overflow,
+    Underflow,
+    //a sum or difference of operands so far apart that its limbs would span more than MAX_SPAN
+    TooWide,
+}
+
+impl Display for ExponentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ExponentError::Overflow => write!(f, "Exponent overflow"),
+            ExponentError::Underflow => write!(f, "Exponent underflow"),
+            ExponentError::TooWide => write!(f, "Operands too far apart"),
+        }
+    }
+}
+
+impl Error for ExponentError {}
+
 impl BigNum {
+    //the range of the limb exponent, small enough that a bit position 64 * exp plus the length
+    //of parts always fits in an i64 and the sum of two exponents can't wrap
+    pub const MAX_EXP: i64 = i64::MAX / 128;
+    pub const MIN_EXP: i64 = -BigNum::MAX_EXP;
+    //the most limbs add and sub lay out from the lowest limb of either operand to the highest,
+    //512 MiB, since every limb between them is stored, operands further apart give TooWide
+    //from checked_add and checked_sub and panic in the operators
+    pub const MAX_SPAN: i64 = 1 << 26;
+
     pub fn new() -> BigNum {
         BigNum {
-            parts: Vec::new(),
+            parts: Parts::new(),
             exp: 0,
             neg: false,
         }
@@ -48,7 +84,7 @@ impl BigNum {
 
 impl From<u64> for BigNum {
     fn from(num: u64) -> Self {
-        let parts = vec![num];
+        let parts = Parts::from_slice(&[num]);
         BigNum {
             parts,
             exp: 0,
@@ -59,7 +95,7 @@ impl From<u64> for BigNum {
 
 impl From<i64> for BigNum {
     fn from(num: i64) -> Self {
-        let parts = vec![num.abs_diff(0)];
+        let parts = Parts::from_slice(&[num.abs_diff(0)]);
         BigNum {
             parts,
             exp: 0,
@@ -70,7 +106,7 @@ impl From<i64> for BigNum {
 
 impl From<i32> for BigNum {
     fn from(num: i32) -> Self {
-        let parts = vec![(num as i64).unsigned_abs()];
+        let parts = Parts::from_slice(&[(num as i64).unsigned_abs()]);
         BigNum {
             parts,
             exp: 0,
@@ -81,7 +117,7 @@ impl From<i32> for BigNum {
 
 impl From<u32> for BigNum {
     fn from(num: u32) -> Self {
-        let parts = vec![num as u64];
+        let parts = Parts::from_slice(&[num as u64]);
         BigNum {
             parts,
             exp: 0,
@@ -150,22 +186,23 @@ impl BigNum {
     }
 }
 
-impl Add for BigNum {
-    type Output = BigNum;
-    fn add(self, rhs: Self) -> Self::Output {
+//add and sub without the exponent check, which the operators and checked_add/checked_sub
+//apply to the result
+impl BigNum {
+    fn sum(self, rhs: BigNum) -> BigNum {
         // -a + b = b - a
         if self.neg && !rhs.neg {
             //copy self and make it positive
             let mut pos_self = self.clone();
             pos_self.neg = false;
-            return rhs - pos_self;
+            return rhs.difference(pos_self);
         }
         // a + -b = a - b
         if !self.neg && rhs.neg {
             //copy rhs and make it positive
             let mut pos_rhs = rhs.clone();
             pos_rhs.neg = false;
-            return self - pos_rhs;
+            return self.difference(pos_rhs);
         }
         // -a + -b
         //-(a + b)
@@ -174,12 +211,25 @@ impl Add for BigNum {
             let mut b = rhs.clone();
             a.neg = false;
             b.neg = false;
-            let mut result = a + b;
+            let mut result = a.sum(b);
             result.neg = true;
             return result;
         }
 
         // a + b
+        //single limbs at the same exponent, like most small integers, skip aligning
+        if let (&[x], &[y]) = (&*self.parts, &*rhs.parts) {
+            if self.exp == rhs.exp {
+                let (sum, carry) = x.overflowing_add(y);
+                let mut result = BigNum {
+                    parts: Parts::from_slice(&[sum, carry as u64]),
+                    exp: self.exp,
+                    neg: false,
+                };
+                result.compact();
+                return result;
+            }
+        }
         let mut a = self;
         let mut b = rhs;
         align(&mut a, &mut b);
@@ -187,12 +237,14 @@ impl Add for BigNum {
         //set the size of results parts to be the same as a + 1
         result.parts.resize(a.parts.len(), 0);
         let mut carry = false;
+        //borrow the limbs once rather than on every write
+        let parts = &mut *result.parts;
         for i in 0..a.parts.len() {
             let sum;
             let tmp_carry1;
             let tmp_carry2;
             (sum, tmp_carry1) = a.parts[i].overflowing_add(b.parts[i]);
-            (result.parts[i], tmp_carry2) = sum.overflowing_add(carry as u64);
+            (parts[i], tmp_carry2) = sum.overflowing_add(carry as u64);
             carry = tmp_carry1 || tmp_carry2;
         }
         if carry {
@@ -202,74 +254,14 @@ impl Add for BigNum {
         result.compact();
         result
     }
-}
-
-impl Add for &BigNum {
-    type Output = BigNum;
-    fn add(self, rhs: Self) -> Self::Output {
-        self.clone() + rhs.clone()
-    }
-}
-
-impl Add<&BigNum> for BigNum {
-    type Output = BigNum;
-    fn add(self, rhs: &BigNum) -> Self::Output {
-        self + rhs.clone()
-    }
-}
-
-impl Add<BigNum> for &BigNum {
-    type Output = BigNum;
-    fn add(self, rhs: BigNum) -> Self::Output {
-        self.clone() + rhs
-    }
-}
 
-impl BigNum {
-    pub fn compact(&mut self) {
-        //remove any leading and trailing zeros
-        while self.parts.last() == Some(&0) {
-            self.parts.pop();
-        }
-        while self.parts.first() == Some(&0) {
-            self.parts.remove(0);
-            self.exp += 1;
-        }
-    }
-}
-
-//aligns both the exponents of a and b and the length of parts of a and b
-//this function does not change the value of a or b, just the representation
-fn align(a: &mut BigNum, b: &mut BigNum) {
-    a.compact();
-    b.compact();
-    while a.exp != b.exp {
-        if a.exp > b.exp {
-            a.parts.insert(0, 0);
-            a.exp -= 1;
-        } else {
-            b.parts.insert(0, 0);
-            b.exp -= 1;
-        }
-    }
-    while a.parts.len() != b.parts.len() {
-        if a.parts.len() > b.parts.len() {
-            b.parts.push(0);
-        } else {
-            a.parts.push(0);
-        }
-    }
-}
-
-impl Sub for BigNum {
-    type Output = BigNum;
-    fn sub(self, rhs: Self) -> Self::Output {
+    fn difference(self, rhs: BigNum) -> BigNum {
         // a - -b
         // a + b
         if !self.neg && rhs.neg {
             let mut pos_rhs = rhs;
             pos_rhs.neg = false;
-            return self + pos_rhs;
+            return self.sum(pos_rhs);
         }
 
         // -a - b
@@ -277,7 +269,7 @@ impl Sub for BigNum {
         if self.neg && !rhs.neg {
             let mut pos_self = self;
             pos_self.neg = false;
-            let mut result = pos_self + rhs;
+            let mut result = pos_self.sum(rhs);
             result.neg = true;
             return result;
         }
@@ -290,10 +282,21 @@ impl Sub for BigNum {
             pos_rhs.neg = false;
             let mut pos_self = self;
             pos_self.neg = false;
-            return pos_rhs - pos_self;
+            return pos_rhs.difference(pos_self);
         }
 
         // a - b
+        if let (&[x], &[y]) = (&*self.parts, &*rhs.parts) {
+            if self.exp == rhs.exp {
+                let mut result = BigNum {
+                    parts: Parts::from_slice(&[x.abs_diff(y)]),
+                    exp: self.exp,
+                    neg: x < y,
+                };
+                result.compact();
+                return result;
+            }
+        }
         //if a == b, return 0
         if self == rhs {
             return BigNum::new();
@@ -301,7 +304,7 @@ impl Sub for BigNum {
 
         //if a < b, return -(b - a)
         if self < rhs {
-            let mut result = rhs - self;
+            let mut result = rhs.difference(self);
             result.neg = true;
             return result;
         }
@@ -314,12 +317,14 @@ impl Sub for BigNum {
         //set the size of results parts to be the same as a
         result.parts.resize(a.parts.len(), 0);
         let mut borrow = false;
+        //borrow the limbs once rather than on every write
+        let parts = &mut *result.parts;
         for i in 0..a.parts.len() {
             let diff;
             let tmp_borrow1;
             let tmp_borrow2;
             (diff, tmp_borrow1) = a.parts[i].overflowing_sub(b.parts[i]);
-            (result.parts[i], tmp_borrow2) = diff.overflowing_sub(borrow as u64);
+            (parts[i], tmp_borrow2) = diff.overflowing_sub(borrow as u64);
             borrow = tmp_borrow1 || tmp_borrow2;
         }
         debug_assert!(!borrow);
@@ -327,6 +332,109 @@ impl Sub for BigNum {
         result.compact();
         result
     }
+
+    //self + rhs, or an error instead of the panic from Add when the exponent leaves the range
+    //or the operands are more than MAX_SPAN limbs apart
+    pub fn checked_add(&self, rhs: &BigNum) -> Result<BigNum, ExponentError> {
+        check_span(self, rhs)?;
+        self.clone().sum(rhs.clone()).check_exponent()
+    }
+
+    pub fn checked_sub(&self, rhs: &BigNum) -> Result<BigNum, ExponentError> {
+        check_span(self, rhs)?;
+        self.clone().difference(rhs.clone()).check_exponent()
+    }
+}
+
+impl Add for BigNum {
+    type Output = BigNum;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.sum(rhs).expect_exponent()
+    }
+}
+
+impl Add for &BigNum {
+    type Output = BigNum;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.clone() + rhs.clone()
+    }
+}
+
+impl Add<&BigNum> for BigNum {
+    type Output = BigNum;
+    fn add(self, rhs: &BigNum) -> Self::Output {
+        self + rhs.clone()
+    }
+}
+
+impl Add<BigNum> for &BigNum {
+    type Output = BigNum;
+    fn add(self, rhs: BigNum) -> Self::Output {
+        self.clone() + rhs
+    }
+}
+
+impl BigNum {
+    pub fn compact(&mut self) {
+        //remove any leading and trailing zeros
+        while self.parts.last() == Some(&0) {
+            self.parts.pop();
+        }
+        let zeros = self.parts.iter().take_while(|&&part| part == 0).count();
+        if zeros > 0 {
+            self.parts.remove_low(zeros);
+            //saturating so an exponent already near i64::MAX ends up out of range for
+            //check_exponent rather than wrapping around
+            self.exp = self.exp.saturating_add(zeros as i64);
+        }
+    }
+}
+
+//TooWide when the limbs of a and b together, with the zero limbs between them, span more than
+//MAX_SPAN
+fn check_span(a: &BigNum, b: &BigNum) -> Result<(), ExponentError> {
+    let (x, x_exp) = a.trimmed();
+    let (y, y_exp) = b.trimmed();
+    if x.is_empty() || y.is_empty() {
+        return Ok(());
+    }
+    let top = (x_exp + x.len() as i64).max(y_exp + y.len() as i64);
+    if top - x_exp.min(y_exp) > BigNum::MAX_SPAN {
+        return Err(ExponentError::TooWide);
+    }
+    Ok(())
+}
+
+//aligns both the exponents of a and b and the length of parts of a and b
+//this function does not change the value of a or b, just the representation
+//panics instead of filling in more than MAX_SPAN limbs
+fn align(a: &mut BigNum, b: &mut BigNum) {
+    check_span(a, b).unwrap_or_else(|e| panic!("{}", e));
+    a.compact();
+    b.compact();
+    //zero takes the other exponent rather than filling in limbs all the way to it
+    if a.parts.is_empty() {
+        a.exp = b.exp;
+    } else if b.parts.is_empty() {
+        b.exp = a.exp;
+    }
+    if a.exp > b.exp {
+        a.parts.insert_low((a.exp - b.exp) as usize);
+        a.exp = b.exp;
+    } else if b.exp > a.exp {
+        b.parts.insert_low((b.exp - a.exp) as usize);
+        b.exp = a.exp;
+    }
+    let len = a.parts.len().max(b.parts.len());
+    a.parts.resize(len, 0);
+    b.parts.resize(len, 0);
+}
+
+impl Sub for BigNum {
+    type Output = BigNum;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.difference(rhs).expect_exponent()
+    }
 }
 
 impl Sub for &BigNum {
@@ -350,33 +458,11 @@ impl Sub<BigNum> for &BigNum {
     }
 }
 
+//compares by the position of the top limb first instead of aligning, so values with far apart
+//exponents compare without building the limbs in between
 impl PartialEq for BigNum {
     fn eq(&self, other: &Self) -> bool {
-        let mut a = self.clone();
-        let mut b = other.clone();
-        align(&mut a, &mut b);
-        //remove any leading zeros
-        while a.parts.last() == Some(&0) {
-            a.parts.pop();
-        }
-        while b.parts.last() == Some(&0) {
-            b.parts.pop();
-        }
-        if a.parts.is_empty() && b.parts.is_empty() {
-            return true;
-        }
-        if self.neg != other.neg {
-            return false;
-        }
-        if a.parts.len() != b.parts.len() {
-            return false;
-        }
-        for i in 0..a.parts.len() {
-            if a.parts[i] != b.parts[i] {
-                return false;
-            }
-        }
-        true
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -390,52 +476,62 @@ impl PartialOrd for BigNum {
 
 impl Ord for BigNum {
     fn cmp(&self, other: &Self) -> Ordering {
-        if self == other {
-            return Ordering::Equal;
-        }
-        if self.neg && !other.neg {
-            return Ordering::Less;
-        }
-        if !self.neg && other.neg {
-            return Ordering::Greater;
-        }
-
-        let mut a = self.clone();
-        let mut b = other.clone();
-        align(&mut a, &mut b);
-        if a.parts.len() > b.parts.len() {
-            if a.neg {
-                return Ordering::Less;
+        let (a, a_exp) = self.trimmed();
+        let (b, b_exp) = other.trimmed();
+        //zero has no sign
+        let a_neg = self.neg && !a.is_empty();
+        let b_neg = other.neg && !b.is_empty();
+        if a_neg != b_neg {
+            return if a_neg {
+                Ordering::Less
             } else {
-                return Ordering::Greater;
-            }
+                Ordering::Greater
+            };
         }
-        if a.parts.len() < b.parts.len() {
-            if a.neg {
-                return Ordering::Greater;
-            } else {
-                return Ordering::Less;
-            }
+        let magnitude = cmp_magnitude(a, a_exp, b, b_exp);
+        if a_neg {
+            magnitude.reverse()
+        } else {
+            magnitude
         }
+    }
+}
 
-        for i in (0..a.parts.len()).rev() {
-            if a.parts[i] > b.parts[i] {
-                if a.neg {
-                    return Ordering::Less;
-                } else {
-                    return Ordering::Greater;
-                }
-            }
-            if a.parts[i] < b.parts[i] {
-                if a.neg {
-                    return Ordering::Greater;
-                } else {
-                    return Ordering::Less;
-                }
-            }
+impl BigNum {
+    //parts without zero limbs at either end, and the exponent of the lowest limb left
+    fn trimmed(&self) -> (&[u64], i64) {
+        let start = self.parts.iter().take_while(|&&part| part == 0).count();
+        let end = self
+            .parts
+            .iter()
+            .rposition(|&part| part != 0)
+            .map_or(start, |i| i + 1);
+        (&self.parts[start..end], self.exp + start as i64)
+    }
+}
+
+//|a| against |b| for trimmed limbs
+fn cmp_magnitude(a: &[u64], a_exp: i64, b: &[u64], b_exp: i64) -> Ordering {
+    match (a.is_empty(), b.is_empty()) {
+        (true, true) => return Ordering::Equal,
+        (true, false) => return Ordering::Less,
+        (false, true) => return Ordering::Greater,
+        (false, false) => {}
+    }
+    let a_top = a_exp + a.len() as i64;
+    let b_top = b_exp + b.len() as i64;
+    if a_top != b_top {
+        return a_top.cmp(&b_top);
+    }
+    //same top limb, compare downward with the shorter one padded by zeros
+    let limb = |x: &[u64], i: usize| x.len().checked_sub(i + 1).map_or(0, |j| x[j]);
+    for i in 0..a.len().max(b.len()) {
+        match limb(a, i).cmp(&limb(b, i)) {
+            Ordering::Equal => {}
+            unequal => return unequal,
         }
-        Ordering::Equal
     }
+    Ordering::Equal
 }
 
 impl Neg for BigNum {
@@ -457,41 +553,34 @@ impl Neg for &BigNum {
 impl Mul for BigNum {
     type Output = BigNum;
     fn mul(self, rhs: Self) -> Self::Output {
-        let mut result = BigNum::new();
-        let mut a = self;
-        let mut b = rhs;
-        align(&mut a, &mut b);
-
-        //set the size of result.parts to the length of the digits of a and b summed
-        result.parts.resize(a.parts.len() + b.parts.len(), 0);
-
-        for i in 0..a.parts.len() {
-            for j in 0..b.parts.len() {
-                let (prod, mut carry) = widening_mul(a.parts[i], b.parts[j]);
-                let mut k = i + j;
-                //add prod, check for carry and add it to carry
-                let (tmp, tmp_carry) = result.parts[k].overflowing_add(prod);
-                result.parts[k] = tmp;
-                carry += tmp_carry as u64;
-                k += 1;
-                while carry > 0 {
-                    if k >= result.parts.len() {
-                        result.parts.push(0);
-                    }
-                    let (tmp, tmp_carry) = result.parts[k].overflowing_add(carry);
-                    result.parts[k] = tmp;
-                    carry = tmp_carry as u64;
-                    k += 1;
-                }
-            }
-        }
-        result.exp = a.exp + b.exp;
-        result.neg = a.neg ^ b.neg;
-        result.compact();
-        result
+        multiply(self, rhs).expect_exponent()
     }
 }
 
+//the product without checking the exponent, which can't wrap with both in range
+fn multiply(a: BigNum, b: BigNum) -> BigNum {
+    //no need to align, the exponents just add, and aligning far apart exponents would fill
+    //the gap with zero limbs
+    let mut a = a;
+    let mut b = b;
+    a.compact();
+    b.compact();
+    //two single limbs multiply straight into inline storage
+    let parts = if let (&[x], &[y]) = (&*a.parts, &*b.parts) {
+        let (lower, upper) = widening_mul(x, y);
+        Parts::from_slice(&[lower, upper])
+    } else {
+        Parts::from(limbs::mul(&a.parts, &b.parts))
+    };
+    let mut result = BigNum {
+        parts,
+        exp: a.exp + b.exp,
+        neg: a.neg ^ b.neg,
+    };
+    result.compact();
+    result
+}
+
 impl Mul for &BigNum {
     type Output = BigNum;
     fn mul(self, rhs: Self) -> Self::Output {
@@ -547,7 +636,9 @@ impl Div for BigNum {
             let mut sub_count = 0;
             let exponent = BigNum::from(u64::MAX) + BigNum::from(1);
             let mut did_iterate = false;
-            while &tmp * &exponent < a {
+            //the probes can step past MAX_EXP while a is still in range, so they go through
+            //multiply without the exponent check
+            while multiply(tmp.clone(), exponent.clone()) < a {
                 did_iterate = true;
                 tmp.exp += 1;
                 count += 1;
@@ -558,7 +649,7 @@ impl Div for BigNum {
                 count -= 1;
             }
 
-            while &tmp * BigNum::from(2) <= a {
+            while multiply(tmp.clone(), BigNum::from(2)) <= a {
                 tmp = tmp * BigNum::from(2);
                 sub_count += 1;
             }
@@ -594,9 +685,7 @@ impl Div for BigNum {
             }
         }
         result.neg = a.neg ^ b.neg;
-        result.compact();
-
-        result
+        result.expect_exponent()
     }
 }
 
@@ -736,3 +825,350 @@ impl BigNum {
         a.parts[0]
     }
 }
+
+impl BigNum {
+    pub fn is_integer(&self) -> bool {
+        if self.exp >= 0 {
+            return true;
+        }
+        self.parts
+            .iter()
+            .take(-self.exp as usize)
+            .all(|&part| part == 0)
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.parts.iter().all(|&part| part == 0)
+    }
+
+    //the magnitude of the integer part as limbs with exp = 0, least significant limb first
+    pub(crate) fn magnitude(&self) -> Vec<u64> {
+        let mut result = if self.exp >= 0 {
+            let mut result = vec![0; self.exp as usize];
+            result.extend_from_slice(&self.parts);
+            result
+        } else {
+            self.parts
+                .iter()
+                .skip(-self.exp as usize)
+                .copied()
+                .collect()
+        };
+        limbs::trim(&mut result);
+        result
+    }
+
+    //self * 2^(64 * n), only the exponent changes, ldexp counted in limbs
+    pub fn scale_by_limbs(mut self, n: i64) -> BigNum {
+        self.exp = self.exp.saturating_add(n);
+        self.expect_exponent()
+    }
+
+    //floor(log2(|self|)), the position of the highest set bit, None for zero
+    pub(crate) fn log2_floor(&self) -> Option<i64> {
+        let len = limbs::bit_len(&self.parts) as i64;
+        if len == 0 {
+            return None;
+        }
+        Some(len - 1 + 64 * self.exp)
+    }
+
+    //self * 2^bits, ldexp
+    pub fn scale_by_bits(self, bits: i64) -> BigNum {
+        self.mul_pow2(bits)
+    }
+
+    //self * 2^bits for any integer number of bits, exact
+    pub(crate) fn mul_pow2(self, bits: i64) -> BigNum {
+        let limb_shift = bits.div_euclid(64);
+        let bit_shift = bits.rem_euclid(64) as usize;
+        let mut result = BigNum::from_magnitude(self.neg, limbs::shl(&self.parts, bit_shift));
+        if !result.parts.is_empty() {
+            result.exp += self.exp + limb_shift;
+        }
+        result.expect_exponent()
+    }
+
+    //keeps the n most significant limbs, rounding toward zero
+    pub(crate) fn round_to_limbs(mut self, n: usize) -> BigNum {
+        self.compact();
+        if self.parts.len() > n {
+            let dropped = self.parts.len() - n;
+            self.parts.remove_low(dropped);
+            self.exp += dropped as i64;
+        }
+        self
+    }
+
+    //self / rhs with at least n significant limbs, rounding toward zero
+    pub(crate) fn div_to_limbs(&self, rhs: &BigNum, n: usize) -> BigNum {
+        self.checked_div_to_limbs(rhs, n)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    //div_to_limbs with an error when the quotient's exponent leaves the range
+    pub(crate) fn checked_div_to_limbs(
+        &self,
+        rhs: &BigNum,
+        n: usize,
+    ) -> Result<BigNum, ExponentError> {
+        let a = self.clone().round_to_limbs(usize::MAX);
+        let b = rhs.clone().round_to_limbs(usize::MAX);
+        if b.parts.is_empty() {
+            panic!("Divide by zero");
+        }
+        if a.parts.is_empty() {
+            return Ok(BigNum::new());
+        }
+        //shift a up by k limbs so the integer quotient has enough limbs
+        let k = (n + b.parts.len() + 1).saturating_sub(a.parts.len());
+        let (quotient, _) = limbs::div_rem(&limbs::shl(&a.parts, 64 * k), &b.parts);
+        let mut result = BigNum::from_magnitude(a.neg ^ b.neg, quotient);
+        result.exp += a.exp - b.exp - k as i64;
+        result.check_exponent()
+    }
+
+    //square root with at least n significant limbs, rounding toward zero, self must not be negative
+    pub(crate) fn sqrt_to_limbs(&self, n: usize) -> BigNum {
+        let mut a = self.clone().round_to_limbs(usize::MAX);
+        if a.parts.is_empty() {
+            return BigNum::new();
+        }
+        assert!(!a.neg, "square root of a negative number");
+        //make the exponent even so it can be halved
+        if a.exp % 2 != 0 {
+            a.parts.insert(0, 0);
+            a.exp -= 1;
+        }
+        let k = (2 * n + 2).saturating_sub(a.parts.len()).div_ceil(2);
+        let root = limbs::isqrt(&limbs::shl(&a.parts, 128 * k));
+        let mut result = BigNum::from_magnitude(false, root);
+        result.exp += a.exp / 2 - k as i64;
+        result
+    }
+
+    pub(crate) fn from_magnitude(neg: bool, parts: Vec<u64>) -> BigNum {
+        let mut result = BigNum {
+            parts: Parts::from(parts),
+            exp: 0,
+            neg,
+        };
+        result.compact();
+        if result.parts.is_empty() {
+            result.neg = false;
+        }
+        result
+    }
+
+    //self if its exponent is in range, zero always is
+    fn check_exponent(mut self) -> Result<BigNum, ExponentError> {
+        self.compact();
+        if self.parts.is_empty() {
+            self.exp = 0;
+            self.neg = false;
+        } else if self.exp > BigNum::MAX_EXP {
+            return Err(ExponentError::Overflow);
+        } else if self.exp < BigNum::MIN_EXP {
+            return Err(ExponentError::Underflow);
+        }
+        Ok(self)
+    }
+
+    //check_exponent for operators, which have nowhere to return the error
+    fn expect_exponent(self) -> BigNum {
+        self.check_exponent().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    //self * rhs, or an error instead of the panic from Mul when the exponent leaves the range
+    pub fn checked_mul(&self, rhs: &BigNum) -> Result<BigNum, ExponentError> {
+        multiply(self.clone(), rhs.clone()).check_exponent()
+    }
+}
+
+//the representation for other libraries and binary formats: the value is
+//(-1)^neg * sum of limbs[i] * 2^(64 * (exp + i)), least significant limb first
+//everything here is normalized, no zero limbs at either end and zero is an empty list with
+//exponent 0 that is never negative
+impl BigNum {
+    //panics like the operators if the normalized exponent is out of range
+    pub fn from_parts(neg: bool, limbs: Vec<u64>, exp: i64) -> BigNum {
+        BigNum {
+            parts: Parts::from(limbs),
+            exp,
+            neg,
+        }
+        .expect_exponent()
+    }
+
+    //(neg, limbs, exp) as taken by from_parts
+    pub fn into_parts(self) -> (bool, Vec<u64>, i64) {
+        let neg = self.is_negative();
+        let (limbs, exp) = self.trimmed();
+        if limbs.is_empty() {
+            return (false, Vec::new(), 0);
+        }
+        (neg, limbs.to_vec(), exp)
+    }
+
+    pub fn as_limbs(&self) -> &[u64] {
+        self.trimmed().0
+    }
+
+    //the exponent of the lowest limb in as_limbs
+    pub fn exponent(&self) -> i64 {
+        match self.trimmed() {
+            ([], _) => 0,
+            (_, exp) => exp,
+        }
+    }
+
+    //false for zero
+    pub fn is_negative(&self) -> bool {
+        self.neg && !self.as_limbs().is_empty()
+    }
+
+    //the limbs from least to most significant
+    pub fn limbs(&self) -> impl DoubleEndedIterator<Item = u64> + ExactSizeIterator + '_ {
+        self.as_limbs().iter().copied()
+    }
+
+    //the limbs from most to least significant
+    pub fn limbs_rev(&self) -> impl DoubleEndedIterator<Item = u64> + ExactSizeIterator + '_ {
+        self.limbs().rev()
+    }
+
+    //(m, e) with self = m * 2^e and 1/2 <= |m| < 1, (0, 0) for zero, frexp
+    pub fn frexp(&self) -> (BigNum, i64) {
+        match self.log2_floor() {
+            None => (BigNum::new(), 0),
+            Some(top) => (self.clone().mul_pow2(-(top + 1)), top + 1),
+        }
+    }
+}
+
+//the operators build a new BigNum for every result, these write it into one that already exists
+//and reuse its heap limbs when no clone shares them, so a loop that keeps writing into the same
+//outputs stops allocating once they are big enough
+impl BigNum {
+    //zero with room for `limbs` limbs
+    pub fn with_capacity(limbs: usize) -> BigNum {
+        BigNum {
+            parts: Parts::with_capacity(limbs),
+            exp: 0,
+            neg: false,
+        }
+    }
+
+    //out = a + b
+    pub fn add_into(a: &BigNum, b: &BigNum, out: &mut BigNum) {
+        add_signed_into(a, b, b.neg, out);
+    }
+
+    //out = a - b
+    pub fn sub_into(a: &BigNum, b: &BigNum, out: &mut BigNum) {
+        add_signed_into(a, b, !b.neg, out);
+    }
+
+    //out = a * b, exact like Mul
+    pub fn mul_into(a: &BigNum, b: &BigNum, out: &mut BigNum) {
+        let (x, x_exp) = a.trimmed();
+        let (y, y_exp) = b.trimmed();
+        limbs::mul_into(x, y, out.parts.reset(x.len() + y.len()));
+        out.exp = x_exp + y_exp;
+        out.neg = a.neg ^ b.neg;
+        out.finish();
+    }
+
+    //out = a * a
+    pub fn square_into(a: &BigNum, out: &mut BigNum) {
+        BigNum::mul_into(a, a, out);
+    }
+
+    //out = a / b to as many limbs as a and b have together, rounding toward zero
+    pub fn div_into(a: &BigNum, b: &BigNum, out: &mut BigNum) {
+        let (x, x_exp) = a.trimmed();
+        let (y, y_exp) = b.trimmed();
+        if y.is_empty() {
+            panic!("Divide by zero");
+        }
+        //x shifted up by k limbs so the integer quotient has x.len() + y.len() limbs, with a
+        //spare zero limb on top for the long division
+        let k = 2 * y.len() + 1;
+        let u = out.parts.reset(k + x.len() + 1);
+        u[k..k + x.len()].copy_from_slice(x);
+        if let [d] = *y {
+            let mut rem: u128 = 0;
+            for limb in u.iter_mut().rev() {
+                let cur = (rem << 64) | *limb as u128;
+                *limb = (cur / d as u128) as u64;
+                rem = cur % d as u128;
+            }
+        } else {
+            limbs::div_rem_in_place(u, y);
+            out.parts.remove_low(y.len());
+        }
+        out.exp = x_exp - y_exp - k as i64;
+        out.neg = a.neg ^ b.neg;
+        out.finish();
+    }
+
+    //compacts a result written into self, panicking like the operators if its exponent is out
+    //of range
+    fn finish(&mut self) {
+        *self = std::mem::take(self).expect_exponent();
+    }
+}
+
+//out = a + b with b's sign replaced by b_neg
+fn add_signed_into(a: &BigNum, b: &BigNum, b_neg: bool, out: &mut BigNum) {
+    check_span(a, b).unwrap_or_else(|e| panic!("{}", e));
+    let (x, x_exp) = a.trimmed();
+    let (y, y_exp) = b.trimmed();
+    let subtract = a.neg != b_neg;
+    //when subtracting, x is the larger magnitude and gives the sign
+    let ((x, x_exp, neg), (y, y_exp)) =
+        if subtract && cmp_magnitude(x, x_exp, y, y_exp) == Ordering::Less {
+            ((y, y_exp, b_neg), (x, x_exp))
+        } else {
+            ((x, x_exp, a.neg), (y, y_exp))
+        };
+    let present = [(x, x_exp), (y, y_exp)]
+        .into_iter()
+        .filter(|(p, _)| !p.is_empty());
+    let bottom = present.clone().map(|(_, e)| e).min().unwrap_or(0);
+    let top = present.map(|(p, e)| e + p.len() as i64).max().unwrap_or(0);
+    let len = (top - bottom) as usize;
+    //limb i of p counting from bottom, zero outside p
+    let limb = |p: &[u64], p_exp: i64, i: usize| {
+        let at = bottom + i as i64 - p_exp;
+        if at >= 0 && (at as usize) < p.len() {
+            p[at as usize]
+        } else {
+            0
+        }
+    };
+
+    let parts = out.parts.reset(len + 1);
+    if subtract {
+        let mut borrow = false;
+        for (i, part) in parts.iter_mut().take(len).enumerate() {
+            let (diff, borrow1) = limb(x, x_exp, i).overflowing_sub(limb(y, y_exp, i));
+            let (diff, borrow2) = diff.overflowing_sub(borrow as u64);
+            *part = diff;
+            borrow = borrow1 || borrow2;
+        }
+        debug_assert!(!borrow);
+    } else {
+        let mut carry = false;
+        for (i, part) in parts.iter_mut().take(len).enumerate() {
+            let (sum, carry1) = limb(x, x_exp, i).overflowing_add(limb(y, y_exp, i));
+            let (sum, carry2) = sum.overflowing_add(carry as u64);
+            *part = sum;
+            carry = carry1 || carry2;
+        }
+        parts[len] = carry as u64;
+    }
+    out.exp = bottom;
+    out.neg = neg;
+    out.finish();
+}
@@ -0,0 +1,161 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use crate::big_num::BigNum;
+
+//integers as bytes straight from the limbs, the unsigned forms for non negative values and the
+//signed ones in two's complement, all as short as possible except for zero, which is one zero
+//byte like in other bignum libraries
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BytesError {
+    NotAnInteger,
+    //an unsigned form of a negative value
+    Negative,
+    //the value needs `len` bytes but only `max` were allowed
+    TooLong { len: usize, max: usize },
+}
+
+impl Display for BytesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BytesError::NotAnInteger => write!(f, "Only integers can be written as bytes"),
+            BytesError::Negative => {
+                write!(f, "Negative value in an unsigned byte form")
+            }
+            BytesError::TooLong { len, max } => {
+                write!(f, "Value needs {} bytes but only {} are allowed", len, max)
+            }
+        }
+    }
+}
+
+impl Error for BytesError {}
+
+//the magnitude least significant byte first, with no zero bytes on top
+fn magnitude_le(x: &BigNum) -> Result<Vec<u8>, BytesError> {
+    if !x.is_integer() {
+        return Err(BytesError::NotAnInteger);
+    }
+    let mut bytes: Vec<u8> = x
+        .magnitude()
+        .iter()
+        .flat_map(|limb| limb.to_le_bytes())
+        .collect();
+    while bytes.last() == Some(&0) {
+        bytes.pop();
+    }
+    Ok(bytes)
+}
+
+//-x in two's complement over the same number of bytes
+fn negate_le(bytes: &mut [u8]) {
+    let mut carry = true;
+    for byte in bytes {
+        (*byte, carry) = (!*byte).overflowing_add(carry as u8);
+    }
+}
+
+fn from_magnitude_le(neg: bool, bytes: &[u8]) -> BigNum {
+    let limbs = bytes
+        .chunks(8)
+        .map(|chunk| {
+            let mut limb = [0; 8];
+            limb[..chunk.len()].copy_from_slice(chunk);
+            u64::from_le_bytes(limb)
+        })
+        .collect();
+    BigNum::from_magnitude(neg, limbs)
+}
+
+impl BigNum {
+    pub fn to_bytes_le(&self) -> Result<Vec<u8>, BytesError> {
+        if self.is_negative() {
+            return Err(BytesError::Negative);
+        }
+        let mut bytes = magnitude_le(self)?;
+        if bytes.is_empty() {
+            bytes.push(0);
+        }
+        Ok(bytes)
+    }
+
+    pub fn to_bytes_be(&self) -> Result<Vec<u8>, BytesError> {
+        let mut bytes = self.to_bytes_le()?;
+        bytes.reverse();
+        Ok(bytes)
+    }
+
+    //exactly `len` bytes, zero padded on the left
+    pub fn to_bytes_be_padded(&self, len: usize) -> Result<Vec<u8>, BytesError> {
+        if self.is_negative() {
+            return Err(BytesError::Negative);
+        }
+        let mut bytes = magnitude_le(self)?;
+        if bytes.len() > len {
+            return Err(BytesError::TooLong {
+                len: bytes.len(),
+                max: len,
+            });
+        }
+        bytes.resize(len, 0);
+        bytes.reverse();
+        Ok(bytes)
+    }
+
+    //two's complement, the top bit of the last byte is the sign
+    pub fn to_signed_bytes_le(&self) -> Result<Vec<u8>, BytesError> {
+        let mut bytes = magnitude_le(self)?;
+        if self.is_negative() {
+            negate_le(&mut bytes);
+            //a top byte below 0x80 would read as positive, 0xff above one that doesn't is
+            //redundant
+            if bytes.last() < Some(&0x80) {
+                bytes.push(0xff);
+            }
+            while bytes.len() > 1
+                && bytes[bytes.len() - 1] == 0xff
+                && bytes[bytes.len() - 2] >= 0x80
+            {
+                bytes.pop();
+            }
+        } else if bytes.last().is_none_or(|&top| top >= 0x80) {
+            bytes.push(0);
+        }
+        Ok(bytes)
+    }
+
+    pub fn to_signed_bytes_be(&self) -> Result<Vec<u8>, BytesError> {
+        let mut bytes = self.to_signed_bytes_le()?;
+        bytes.reverse();
+        Ok(bytes)
+    }
+
+    pub fn from_bytes_le(bytes: &[u8]) -> BigNum {
+        from_magnitude_le(false, bytes)
+    }
+
+    pub fn from_bytes_be(bytes: &[u8]) -> BigNum {
+        let mut bytes = bytes.to_vec();
+        bytes.reverse();
+        from_magnitude_le(false, &bytes)
+    }
+
+    //two's complement, empty is zero
+    pub fn from_signed_bytes_le(bytes: &[u8]) -> BigNum {
+        match bytes.last() {
+            Some(&top) if top >= 0x80 => {
+                let mut magnitude = bytes.to_vec();
+                negate_le(&mut magnitude);
+                from_magnitude_le(true, &magnitude)
+            }
+            _ => from_magnitude_le(false, bytes),
+        }
+    }
+
+    pub fn from_signed_bytes_be(bytes: &[u8]) -> BigNum {
+        let mut bytes = bytes.to_vec();
+        bytes.reverse();
+        BigNum::from_signed_bytes_le(&bytes)
+    }
+}
@@ -0,0 +1,390 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+
+use crate::big_num::{BigNum, ExponentError};
+use crate::decimal::BigDecimal;
+
+//numbers in CBOR (RFC 8949) without a CBOR library
+//
+//integers are major type 0 or 1 while they fit in 64 bits and tag 2 or 3 around the big-endian
+//bytes of n or -1 - n beyond that, other BigNums are tag 5 bigfloats [e, m] for m * 2^e with m
+//odd, and BigDecimal is a tag 4 decimal fraction [-scale, unscaled]
+//integers with more zero limbs below them than limbs that aren't zero are bigfloats too, their
+//bytes would mostly be zeros and far enough up wouldn't fit in memory
+//the encoder writes the preferred serialization, shortest heads and no leading zero bytes, the
+//decoder takes any well-formed encoding of these items including indefinite-length strings and
+//arrays
+//a few bytes of exponent can ask for a power of ten or five with millions of digits, so
+//decoding refuses to build one longer than a limit in limbs, by default DEFAULT_LIMBS more
+//than one per input byte, which is 8 times what a bignum spelled out in the input could take
+
+const UNSIGNED: u8 = 0;
+const NEGATIVE: u8 = 1;
+const BYTES: u8 = 2;
+const ARRAY: u8 = 4;
+const TAG: u8 = 6;
+
+const POSITIVE_BIGNUM: u64 = 2;
+const NEGATIVE_BIGNUM: u64 = 3;
+const DECIMAL_FRACTION: u64 = 4;
+const BIGFLOAT: u64 = 5;
+
+const BREAK: u8 = 0xff;
+
+const DEFAULT_LIMBS: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CborError {
+    //the input ended partway through an item
+    Truncated,
+    //bytes left over after the item
+    TrailingBytes,
+    //not well-formed CBOR, or a tag whose content has the wrong shape
+    Malformed,
+    //well-formed but not one of the number items handled here
+    Unsupported,
+    //a decimal fraction with no exact BigNum, such as 0.1
+    NotDyadic,
+    //the exponent needs a power of ten or five longer than the limit
+    TooLarge,
+    Exponent(ExponentError),
+}
+
+impl Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CborError::Truncated => write!(f, "CBOR input ended in the middle of an item"),
+            CborError::TrailingBytes => write!(f, "Bytes left over after the CBOR item"),
+            CborError::Malformed => write!(f, "Malformed CBOR"),
+            CborError::Unsupported => write!(f, "CBOR item is not a supported number"),
+            CborError::NotDyadic => {
+                write!(f, "Decimal fraction has no exact binary representation")
+            }
+            CborError::TooLarge => write!(f, "CBOR exponent needs a power past the limit"),
+            CborError::Exponent(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl Error for CborError {}
+
+impl From<ExponentError> for CborError {
+    fn from(e: ExponentError) -> Self {
+        CborError::Exponent(e)
+    }
+}
+
+//the initial byte and argument in the fewest bytes
+fn write_head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    if arg < 24 {
+        out.push(major | arg as u8);
+    } else if arg <= u8::MAX as u64 {
+        out.extend_from_slice(&[major | 24, arg as u8]);
+    } else if arg <= u16::MAX as u64 {
+        out.push(major | 25);
+        out.extend_from_slice(&(arg as u16).to_be_bytes());
+    } else if arg <= u32::MAX as u64 {
+        out.push(major | 26);
+        out.extend_from_slice(&(arg as u32).to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+//an exponent, always small enough for major type 0 or 1
+fn write_small(out: &mut Vec<u8>, value: i128) {
+    if value < 0 {
+        write_head(out, NEGATIVE, (-1 - value) as u64);
+    } else {
+        write_head(out, UNSIGNED, value as u64);
+    }
+}
+
+fn write_integer(out: &mut Vec<u8>, x: &BigNum) {
+    let (major, tag, n) = if x.is_negative() {
+        (NEGATIVE, NEGATIVE_BIGNUM, -x - BigNum::from(1))
+    } else {
+        (UNSIGNED, POSITIVE_BIGNUM, x.clone())
+    };
+    let bytes = n.to_bytes_be().expect("a non negative integer");
+    if bytes.len() <= 8 {
+        let mut arg = [0; 8];
+        arg[8 - bytes.len()..].copy_from_slice(&bytes);
+        write_head(out, major, u64::from_be_bytes(arg));
+    } else {
+        write_head(out, TAG, tag);
+        write_head(out, BYTES, bytes.len() as u64);
+        out.extend_from_slice(&bytes);
+    }
+}
+
+//[e, m] under a tag 4 or 5
+fn write_fraction(out: &mut Vec<u8>, tag: u64, exponent: i128, mantissa: &BigNum) {
+    write_head(out, TAG, tag);
+    write_head(out, ARRAY, 2);
+    write_small(out, exponent);
+    write_integer(out, mantissa);
+}
+
+//m * 10^e or m * 2^e as they were written, the exponent wide enough for any major type 1
+enum Number {
+    Integer(BigNum),
+    Decimal { exponent: i128, mantissa: BigNum },
+    Bigfloat { exponent: i128, mantissa: BigNum },
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], CborError> {
+        if n > self.bytes.len() {
+            return Err(CborError::Truncated);
+        }
+        let (taken, rest) = self.bytes.split_at(n);
+        self.bytes = rest;
+        Ok(taken)
+    }
+
+    //true and past it if the next byte is a break
+    fn at_break(&mut self) -> Result<bool, CborError> {
+        match self.bytes.first() {
+            None => Err(CborError::Truncated),
+            Some(&BREAK) => {
+                self.bytes = &self.bytes[1..];
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+        }
+    }
+
+    //the major type and argument, None for indefinite length
+    fn head(&mut self) -> Result<(u8, Option<u64>), CborError> {
+        let initial = self.take(1)?[0];
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let arg = match info {
+            0..=23 => Some(info as u64),
+            24..=27 => {
+                let bytes = self.take(1 << (info - 24))?;
+                Some(bytes.iter().fold(0, |arg, &byte| arg << 8 | byte as u64))
+            }
+            31 if matches!(major, BYTES..=5) => None,
+            _ => return Err(CborError::Malformed),
+        };
+        Ok((major, arg))
+    }
+
+    //the content of a tag 2 or 3, chunked or not
+    fn byte_string(&mut self) -> Result<Vec<u8>, CborError> {
+        match self.head()? {
+            (BYTES, Some(len)) => {
+                let len = usize::try_from(len).map_err(|_| CborError::Truncated)?;
+                Ok(self.take(len)?.to_vec())
+            }
+            (BYTES, None) => {
+                let mut bytes = Vec::new();
+                while !self.at_break()? {
+                    match self.head()? {
+                        (BYTES, Some(len)) => {
+                            let len = usize::try_from(len).map_err(|_| CborError::Truncated)?;
+                            bytes.extend_from_slice(self.take(len)?);
+                        }
+                        _ => return Err(CborError::Malformed),
+                    }
+                }
+                Ok(bytes)
+            }
+            _ => Err(CborError::Malformed),
+        }
+    }
+
+    //an integer or bignum whose head has been read
+    fn integer_after(&mut self, major: u8, arg: Option<u64>) -> Result<BigNum, CborError> {
+        match (major, arg) {
+            (UNSIGNED, Some(n)) => Ok(BigNum::from(n)),
+            (NEGATIVE, Some(n)) => Ok(-BigNum::from(n) - BigNum::from(1)),
+            (TAG, Some(POSITIVE_BIGNUM)) => Ok(BigNum::from_bytes_be(&self.byte_string()?)),
+            (TAG, Some(NEGATIVE_BIGNUM)) => {
+                Ok(-BigNum::from_bytes_be(&self.byte_string()?) - BigNum::from(1))
+            }
+            _ => Err(CborError::Unsupported),
+        }
+    }
+
+    //the [e, m] of a tag 4 or 5
+    fn fraction(&mut self) -> Result<(i128, BigNum), CborError> {
+        let indefinite = match self.head()? {
+            (ARRAY, Some(2)) => false,
+            (ARRAY, None) => true,
+            _ => return Err(CborError::Malformed),
+        };
+        let exponent = match self.head()? {
+            (UNSIGNED, Some(n)) => n as i128,
+            (NEGATIVE, Some(n)) => -1 - n as i128,
+            _ => return Err(CborError::Malformed),
+        };
+        let (major, arg) = self.head()?;
+        let mantissa = match self.integer_after(major, arg) {
+            Err(CborError::Unsupported) => return Err(CborError::Malformed),
+            mantissa => mantissa?,
+        };
+        if indefinite && !self.at_break()? {
+            return Err(CborError::Malformed);
+        }
+        Ok((exponent, mantissa))
+    }
+
+    fn number(&mut self) -> Result<Number, CborError> {
+        let number = match self.head()? {
+            (TAG, Some(DECIMAL_FRACTION)) => {
+                let (exponent, mantissa) = self.fraction()?;
+                Number::Decimal { exponent, mantissa }
+            }
+            (TAG, Some(BIGFLOAT)) => {
+                let (exponent, mantissa) = self.fraction()?;
+                Number::Bigfloat { exponent, mantissa }
+            }
+            (major, arg) => Number::Integer(self.integer_after(major, arg)?),
+        };
+        Ok(number)
+    }
+}
+
+//the one number in bytes, nothing after it
+fn decode(bytes: &[u8]) -> Result<Number, CborError> {
+    let mut reader = Reader { bytes };
+    let number = reader.number()?;
+    if !reader.bytes.is_empty() {
+        return Err(CborError::TrailingBytes);
+    }
+    Ok(number)
+}
+
+//mantissa * 2^exponent, checked against the exponent range before anything is shifted
+fn bigfloat(exponent: i128, mantissa: BigNum) -> Result<BigNum, CborError> {
+    let Some(&low) = mantissa.as_limbs().first() else {
+        return Ok(BigNum::new());
+    };
+    let zeros = 64 * mantissa.exponent() as i128 + low.trailing_zeros() as i128;
+    let low_exp = (exponent + zeros).div_euclid(64);
+    if low_exp > BigNum::MAX_EXP as i128 {
+        return Err(ExponentError::Overflow.into());
+    }
+    if low_exp < BigNum::MIN_EXP as i128 {
+        return Err(ExponentError::Underflow.into());
+    }
+    Ok(mantissa.scale_by_bits(exponent as i64))
+}
+
+fn default_limit(bytes: &[u8]) -> usize {
+    bytes.len().saturating_add(DEFAULT_LIMBS)
+}
+
+//TooLarge unless base^exponent, with 100 log2(base) rounded up to bits_per_100, fits in
+//max_limbs
+fn check_power(exponent: i128, bits_per_100: i128, max_limbs: usize) -> Result<(), CborError> {
+    if exponent.unsigned_abs() * bits_per_100 as u128 / 6400 >= max_limbs as u128 {
+        return Err(CborError::TooLarge);
+    }
+    Ok(())
+}
+
+//unscaled * 10^-scale, with the scale in range of an i64
+fn decimal(exponent: i128, mantissa: BigNum) -> Result<BigDecimal, CborError> {
+    match i64::try_from(-exponent) {
+        Ok(scale) => Ok(BigDecimal::new(mantissa, scale)),
+        Err(_) if exponent > 0 => Err(ExponentError::Overflow.into()),
+        Err(_) => Err(ExponentError::Underflow.into()),
+    }
+}
+
+impl BigNum {
+    //an integer, bignum or bigfloat item
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        if self.is_integer() && self.exponent() <= self.as_limbs().len() as i64 {
+            write_integer(&mut out, self);
+        } else {
+            //self = m * 2^e with m odd
+            let (neg, limbs, exp) = self.clone().into_parts();
+            let zeros = limbs[0].trailing_zeros() as i64;
+            let mantissa = BigNum::from_parts(neg, limbs, 0).scale_by_bits(-zeros);
+            write_fraction(&mut out, BIGFLOAT, (64 * exp + zeros) as i128, &mantissa);
+        }
+        out
+    }
+
+    //any of the number items, a decimal fraction only when its value is dyadic
+    pub fn from_cbor(bytes: &[u8]) -> Result<BigNum, CborError> {
+        BigNum::from_cbor_limited(bytes, default_limit(bytes))
+    }
+
+    //from_cbor building no power of ten longer than max_limbs
+    pub fn from_cbor_limited(bytes: &[u8], max_limbs: usize) -> Result<BigNum, CborError> {
+        match decode(bytes)? {
+            Number::Integer(x) => Ok(x),
+            Number::Bigfloat { exponent, mantissa } => bigfloat(exponent, mantissa),
+            Number::Decimal { exponent, mantissa } => {
+                if mantissa.is_zero() {
+                    return Ok(BigNum::new());
+                }
+                //10^e has more than 3e bits, and 5^-e only divides m when it is at most m
+                let bits = 64 * (mantissa.exponent() + mantissa.as_limbs().len() as i64) as i128;
+                if exponent > 64 * BigNum::MAX_EXP as i128 / 3 {
+                    return Err(ExponentError::Overflow.into());
+                }
+                if -exponent > bits {
+                    return Err(CborError::NotDyadic);
+                }
+                if exponent > 0 {
+                    check_power(exponent, 333, max_limbs)?;
+                }
+                decimal(exponent, mantissa)?
+                    .to_bignum()
+                    .ok_or(CborError::NotDyadic)
+            }
+        }
+    }
+}
+
+impl BigDecimal {
+    //a decimal fraction keeping the scale, so 1.50 stays [-2, 150]
+    pub fn to_cbor(&self) -> Vec<u8> {
+        let mut out = Vec::new();
+        write_fraction(
+            &mut out,
+            DECIMAL_FRACTION,
+            -(self.scale() as i128),
+            self.unscaled(),
+        );
+        out
+    }
+
+    //any of the number items, integers with scale 0
+    //decimal fractions keep their exponent as the scale without building anything, bigfloats
+    //below one need 5^-e
+    pub fn from_cbor(bytes: &[u8]) -> Result<BigDecimal, CborError> {
+        BigDecimal::from_cbor_limited(bytes, default_limit(bytes))
+    }
+
+    //from_cbor building no power of five longer than max_limbs
+    pub fn from_cbor_limited(bytes: &[u8], max_limbs: usize) -> Result<BigDecimal, CborError> {
+        match decode(bytes)? {
+            Number::Integer(x) => Ok(BigDecimal::new(x, 0)),
+            Number::Decimal { exponent, mantissa } => decimal(exponent, mantissa),
+            Number::Bigfloat { exponent, mantissa } => {
+                let value = bigfloat(exponent, mantissa)?;
+                if !value.is_integer() {
+                    //the scale From<BigNum> picks, the bits below the point
+                    let bits = 64 * value.exponent() as i128
+                        + value.as_limbs()[0].trailing_zeros() as i128;
+                    check_power(bits, 233, max_limbs)?;
+                }
+                Ok(BigDecimal::from(value))
+            }
+        }
+    }
+}
@@ -0,0 +1,264 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::big_num::BigNum;
+use crate::factor::primes_below;
+use crate::limbs;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CombinatoricsError {
+    NotAnInteger,
+    Negative,
+    //the argument does not fit in a u64 or the work to get the result is out of reach, the
+    //result would not fit in memory anyway
+    TooLarge,
+}
+
+impl Display for CombinatoricsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CombinatoricsError::NotAnInteger => write!(f, "arguments must be integers"),
+            CombinatoricsError::Negative => write!(f, "arguments must not be negative"),
+            CombinatoricsError::TooLarge => write!(f, "argument is too large"),
+        }
+    }
+}
+
+impl Error for CombinatoricsError {}
+
+fn small(n: &BigNum) -> Result<u64, CombinatoricsError> {
+    if !n.is_integer() {
+        return Err(CombinatoricsError::NotAnInteger);
+    }
+    if n < &BigNum::from(0) {
+        return Err(CombinatoricsError::Negative);
+    }
+    match n.magnitude().as_slice() {
+        [] => Ok(0),
+        [x] => Ok(*x),
+        _ => Err(CombinatoricsError::TooLarge),
+    }
+}
+
+//lo * (lo + step) * ... up to and including hi, split in halves so both sides of every
+//multiplication have about the same size
+fn product_range(lo: u64, hi: u64, step: u64) -> Vec<u64> {
+    if lo > hi {
+        return vec![1];
+    }
+    let count = (hi - lo) / step + 1;
+    if count <= 4 {
+        //counted rather than stepped past hi, which can be u64::MAX
+        let mut result = vec![1];
+        for i in 0..count {
+            result = limbs::mul_small(&result, lo + i * step);
+        }
+        return result;
+    }
+    let mid = lo + (count / 2) * step;
+    limbs::mul(
+        &product_range(lo, mid - step, step),
+        &product_range(mid, hi, step),
+    )
+}
+
+fn product_tree(values: &[Vec<u64>]) -> Vec<u64> {
+    match values {
+        [] => vec![1],
+        [x] => x.clone(),
+        _ => {
+            let (left, right) = values.split_at(values.len() / 2);
+            limbs::mul(&product_tree(left), &product_tree(right))
+        }
+    }
+}
+
+fn pow_small(base: u64, exp: u64) -> Vec<u64> {
+    let mut result = vec![1];
+    for i in (0..64 - exp.leading_zeros()).rev() {
+        result = limbs::mul(&result, &result);
+        if (exp >> i) & 1 == 1 {
+            result = limbs::mul_small(&result, base);
+        }
+    }
+    result
+}
+
+//exponent of p in n! (legendre's formula)
+fn legendre_exponent(mut n: u64, p: u64) -> u64 {
+    let mut e = 0;
+    while n > 0 {
+        n /= p;
+        e += n;
+    }
+    e
+}
+
+//an upper bound on the bits of base^exp
+fn power_bits(base: u64, exp: u64) -> Option<u64> {
+    exp.checked_mul(64 - base.leading_zeros() as u64)
+}
+
+fn within(bits: Option<u64>, limit: u64) -> Result<(), CombinatoricsError> {
+    match bits {
+        Some(bits) if bits <= limit => Ok(()),
+        _ => Err(CombinatoricsError::TooLarge),
+    }
+}
+
+//the largest factorial, double factorial or power inside stirling2 built, in bits, half a
+//megabyte
+const RESULT_LIMIT: u64 = 1 << 22;
+//stirling2 sums k + 1 powers of up to n log2(k) bits, this bounds their total
+const TERMS_LIMIT: u64 = 1 << 26;
+//partitions keeps every p(m) up to n, about 3.7 sqrt(m) bits each, and does n^1.5 additions
+const PARTITIONS_LIMIT: u64 = 1 << 16;
+
+//up to this k binomials are (n - k + 1) * ... * n / k!, which costs the same for any n
+const PRODUCT_LIMIT: u64 = 1 << 12;
+//beyond it they come from the primes up to n, and the sieve for those takes n bytes
+const SIEVE_LIMIT: u64 = 1 << 24;
+
+fn binomial(n: u64, k: u64) -> Result<Vec<u64>, CombinatoricsError> {
+    if k > n {
+        return Ok(Vec::new());
+    }
+    let k = k.min(n - k);
+    if k <= PRODUCT_LIMIT {
+        let (quotient, _) =
+            limbs::div_rem(&product_range(n - k + 1, n, 1), &product_range(1, k, 1));
+        return Ok(quotient);
+    }
+    let limit = n
+        .checked_add(1)
+        .filter(|&limit| limit <= SIEVE_LIMIT)
+        .ok_or(CombinatoricsError::TooLarge)?;
+    //multiply together the prime powers of n! / (k! (n - k)!)
+    let powers: Vec<Vec<u64>> = primes_below(limit)
+        .filter_map(|p| {
+            let e = legendre_exponent(n, p) - legendre_exponent(k, p) - legendre_exponent(n - k, p);
+            (e > 0).then(|| pow_small(p, e))
+        })
+        .collect();
+    Ok(product_tree(&powers))
+}
+
+impl BigNum {
+    //TooLarge past RESULT_LIMIT bits, around 230000!
+    pub fn factorial(&self) -> Result<BigNum, CombinatoricsError> {
+        let n = small(self)?;
+        within(power_bits(n, n), RESULT_LIMIT)?;
+        Ok(BigNum::from_magnitude(false, product_range(1, n, 1)))
+    }
+
+    //n * (n - 2) * (n - 4) * ... down to 1 or 2
+    pub fn double_factorial(&self) -> Result<BigNum, CombinatoricsError> {
+        let n = small(self)?;
+        if n == 0 {
+            return Ok(BigNum::from(1));
+        }
+        within(power_bits(n, n.div_ceil(2)), RESULT_LIMIT)?;
+        let lo = if n % 2 == 0 { 2 } else { 1 };
+        Ok(BigNum::from_magnitude(false, product_range(lo, n, 2)))
+    }
+
+    //n choose k, zero when k > n, TooLarge when n is past SIEVE_LIMIT and neither k nor n - k
+    //is below PRODUCT_LIMIT
+    pub fn binomial(&self, k: &BigNum) -> Result<BigNum, CombinatoricsError> {
+        let n = small(self)?;
+        let k = small(k)?;
+        Ok(BigNum::from_magnitude(false, binomial(n, k)?))
+    }
+
+    //(k_1 + ... + k_m)! / (k_1! ... k_m!)
+    pub fn multinomial(ks: &[BigNum]) -> Result<BigNum, CombinatoricsError> {
+        let mut result = vec![1];
+        let mut total: u64 = 0;
+        for k in ks {
+            let k = small(k)?;
+            total = total.checked_add(k).ok_or(CombinatoricsError::TooLarge)?;
+            result = limbs::mul(&result, &binomial(total, k)?);
+        }
+        Ok(BigNum::from_magnitude(false, result))
+    }
+
+    //(2n choose n) / (n + 1)
+    pub fn catalan(&self) -> Result<BigNum, CombinatoricsError> {
+        let n = small(self)?;
+        let two_n = n.checked_mul(2).ok_or(CombinatoricsError::TooLarge)?;
+        let (quotient, _) = limbs::div_rem_small(&binomial(two_n, n)?, n + 1);
+        Ok(BigNum::from_magnitude(false, quotient))
+    }
+
+    //stirling numbers of the second kind, the ways to split n items into k non empty sets
+    //TooLarge once k^n passes RESULT_LIMIT bits or the k + 1 terms together pass TERMS_LIMIT
+    pub fn stirling2(&self, k: &BigNum) -> Result<BigNum, CombinatoricsError> {
+        let n = small(self)?;
+        let k = small(k)?;
+        if k > n {
+            return Ok(BigNum::from(0));
+        }
+        if k == 1 || k == n {
+            return Ok(BigNum::from(1));
+        }
+        let term_bits = power_bits(k, n);
+        within(term_bits, RESULT_LIMIT)?;
+        within(
+            term_bits.and_then(|bits| bits.checked_mul(k + 1)),
+            TERMS_LIMIT,
+        )?;
+        //k! S(n, k) = sum over j of (-1)^j (k choose j) (k - j)^n, positive and negative terms
+        //are summed separately so everything stays unsigned
+        let mut positive = Vec::new();
+        let mut negative = Vec::new();
+        //k choose j, stepped along with j
+        let mut choose = vec![1];
+        for j in 0..=k {
+            let term = limbs::mul(&choose, &pow_small(k - j, n));
+            if j % 2 == 0 {
+                positive = limbs::add(&positive, &term);
+            } else {
+                negative = limbs::add(&negative, &term);
+            }
+            choose = limbs::div_rem_small(&limbs::mul_small(&choose, k - j), j + 1).0;
+        }
+        let (quotient, _) =
+            limbs::div_rem(&limbs::sub(&positive, &negative), &product_range(1, k, 1));
+        Ok(BigNum::from_magnitude(false, quotient))
+    }
+
+    //the number of ways to write n as a sum of positive integers, ignoring order
+    //TooLarge past PARTITIONS_LIMIT
+    pub fn partitions(&self) -> Result<BigNum, CombinatoricsError> {
+        let n = small(self)?;
+        within(Some(n), PARTITIONS_LIMIT)?;
+        let n = n as usize;
+        //euler's pentagonal number theorem:
+        //p(m) = sum over k >= 1 of (-1)^(k + 1) (p(m - k(3k - 1)/2) + p(m - k(3k + 1)/2))
+        let mut p: Vec<Vec<u64>> = vec![vec![1]];
+        for m in 1..=n {
+            let mut positive = Vec::new();
+            let mut negative = Vec::new();
+            for k in 1.. {
+                let first = k * (3 * k - 1) / 2;
+                if first > m {
+                    break;
+                }
+                let mut term = p[m - first].clone();
+                let second = k * (3 * k + 1) / 2;
+                if second <= m {
+                    term = limbs::add(&term, &p[m - second]);
+                }
+                if k % 2 == 1 {
+                    positive = limbs::add(&positive, &term);
+                } else {
+                    negative = limbs::add(&negative, &term);
+                }
+            }
+            p.push(limbs::sub(&positive, &negative));
+        }
+        Ok(BigNum::from_magnitude(false, p.swap_remove(n)))
+    }
+}
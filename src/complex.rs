@@ -0,0 +1,317 @@
+use std::fmt::{self, Display};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::big_num::BigNum;
+use crate::codec::{encode, Base};
+use crate::functions::{limbs_for, DomainError};
+use crate::limbs;
+
+//re + im i
+//add, sub and mul are exact like on BigNum, division and the transcendental functions round to
+//a number of bits, with the same error bounds as the real functions they are built from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BigComplex {
+    pub re: BigNum,
+    pub im: BigNum,
+}
+
+impl BigComplex {
+    pub fn new(re: BigNum, im: BigNum) -> BigComplex {
+        BigComplex { re, im }
+    }
+
+    pub fn i() -> BigComplex {
+        BigComplex::new(BigNum::new(), BigNum::from(1))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.re.is_zero() && self.im.is_zero()
+    }
+
+    pub fn is_real(&self) -> bool {
+        self.im.is_zero()
+    }
+
+    pub fn conj(&self) -> BigComplex {
+        BigComplex::new(self.re.clone(), -&self.im)
+    }
+
+    //|self|^2, exact
+    pub fn norm_sqr(&self) -> BigNum {
+        &self.re * &self.re + &self.im * &self.im
+    }
+
+    pub fn abs(&self, precision: usize) -> BigNum {
+        self.norm_sqr().sqrt_to_limbs(limbs_for(precision))
+    }
+
+    //the principal argument in (-pi, pi]
+    pub fn arg(&self, precision: usize) -> BigNum {
+        self.im.atan2(&self.re, precision)
+    }
+
+    //(abs, arg)
+    pub fn to_polar(&self, precision: usize) -> (BigNum, BigNum) {
+        (self.abs(precision), self.arg(precision))
+    }
+
+    pub fn from_polar(r: &BigNum, theta: &BigNum, precision: usize) -> BigComplex {
+        let bits = precision + 64 + r.log2_floor().unwrap_or(0).max(0) as usize;
+        let (sin, cos) = theta.sin_cos(bits);
+        let n = limbs_for(precision);
+        BigComplex::new((r * cos).round_to_limbs(n), (r * sin).round_to_limbs(n))
+    }
+
+    //self / rhs with at least `precision` bits in each part
+    pub fn div_to(&self, rhs: &BigComplex, precision: usize) -> BigComplex {
+        let denominator = rhs.norm_sqr();
+        if denominator.is_zero() {
+            panic!("Divide by zero");
+        }
+        let numerator = self * rhs.conj();
+        let n = limbs_for(precision);
+        BigComplex::new(
+            numerator.re.div_to_limbs(&denominator, n),
+            numerator.im.div_to_limbs(&denominator, n),
+        )
+    }
+
+    //the principal square root, the one with re >= 0
+    pub fn sqrt(&self, precision: usize) -> BigComplex {
+        if self.is_zero() {
+            return BigComplex::default();
+        }
+        let bits = precision + 64;
+        let n = limbs_for(bits);
+        //t = sqrt((|z| + |re|) / 2) avoids cancellation, the other part is im / (2t)
+        let abs = self.abs(bits);
+        let re_abs = if self.re < BigNum::from(0) {
+            -&self.re
+        } else {
+            self.re.clone()
+        };
+        let t = (abs + re_abs).mul_pow2(-1).sqrt_to_limbs(n);
+        let other = self.im.div_to_limbs(&t, n).mul_pow2(-1);
+        let n = limbs_for(precision);
+        if self.re >= BigNum::from(0) {
+            BigComplex::new(t.round_to_limbs(n), other.round_to_limbs(n))
+        } else if self.im < BigNum::from(0) {
+            BigComplex::new((-other).round_to_limbs(n), (-t).round_to_limbs(n))
+        } else {
+            BigComplex::new(other.round_to_limbs(n), t.round_to_limbs(n))
+        }
+    }
+
+    //e^re (cos im + i sin im)
+    pub fn exp(&self, precision: usize) -> BigComplex {
+        let bits = precision + 64;
+        let scale = self.re.exp(bits);
+        let (sin, cos) = self.im.sin_cos(bits);
+        let n = limbs_for(precision);
+        BigComplex::new(
+            (&scale * cos).round_to_limbs(n),
+            (scale * sin).round_to_limbs(n),
+        )
+    }
+
+    //the principal logarithm ln|z| + i arg z
+    pub fn ln(&self, precision: usize) -> Result<BigComplex, DomainError> {
+        if self.is_zero() {
+            return Err(DomainError::new("logarithm of zero"));
+        }
+        let bits = precision + 64;
+        //ln|z| = ln(|z|^2) / 2 keeps it exact up to the logarithm itself
+        let re = self.norm_sqr().ln(bits)?.mul_pow2(-1);
+        let n = limbs_for(precision);
+        Ok(BigComplex::new(re.round_to_limbs(n), self.arg(precision)))
+    }
+
+    //the principal value of self^exp = e^(exp ln self), exact for positive integer exponents
+    pub fn pow(&self, exp: &BigComplex, precision: usize) -> Result<BigComplex, DomainError> {
+        if exp.is_zero() {
+            return Ok(BigComplex::from(BigNum::from(1)));
+        }
+        if self.is_zero() {
+            if exp.re > BigNum::from(0) {
+                return Ok(BigComplex::default());
+            }
+            return Err(DomainError::new(
+                "zero to a power without a positive real part",
+            ));
+        }
+        //non negative integer powers are exact by repeated squaring
+        if exp.is_real() && exp.re.is_integer() && exp.re > BigNum::from(0) {
+            let n = exp.re.magnitude();
+            let mut result = BigComplex::from(BigNum::from(1));
+            for i in (0..limbs::bit_len(&n)).rev() {
+                result = &result * &result;
+                if limbs::bit(&n, i) {
+                    result = result * self;
+                }
+            }
+            return Ok(result);
+        }
+        let log = self.ln(precision + 64)?;
+        //the error in exp(exp ln z) grows with the size of that product, so take more bits
+        //of the logarithm when it is large
+        let product = exp * &log;
+        let magnitude = product.re.log2_floor().unwrap_or(0).max(0) as usize
+            + product.im.log2_floor().unwrap_or(0).max(0) as usize;
+        let exponent = if magnitude > 0 {
+            exp * self.ln(precision + 64 + magnitude)?
+        } else {
+            product
+        };
+        Ok(exponent.exp(precision))
+    }
+}
+
+impl From<BigNum> for BigComplex {
+    fn from(value: BigNum) -> Self {
+        BigComplex::new(value, BigNum::new())
+    }
+}
+
+impl Add for BigComplex {
+    type Output = BigComplex;
+    fn add(self, rhs: Self) -> Self::Output {
+        BigComplex::new(self.re + rhs.re, self.im + rhs.im)
+    }
+}
+
+impl Add for &BigComplex {
+    type Output = BigComplex;
+    fn add(self, rhs: Self) -> Self::Output {
+        BigComplex::new(&self.re + &rhs.re, &self.im + &rhs.im)
+    }
+}
+
+impl Add<&BigComplex> for BigComplex {
+    type Output = BigComplex;
+    fn add(self, rhs: &BigComplex) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<BigComplex> for &BigComplex {
+    type Output = BigComplex;
+    fn add(self, rhs: BigComplex) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for BigComplex {
+    type Output = BigComplex;
+    fn sub(self, rhs: Self) -> Self::Output {
+        BigComplex::new(self.re - rhs.re, self.im - rhs.im)
+    }
+}
+
+impl Sub for &BigComplex {
+    type Output = BigComplex;
+    fn sub(self, rhs: Self) -> Self::Output {
+        BigComplex::new(&self.re - &rhs.re, &self.im - &rhs.im)
+    }
+}
+
+impl Sub<&BigComplex> for BigComplex {
+    type Output = BigComplex;
+    fn sub(self, rhs: &BigComplex) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<BigComplex> for &BigComplex {
+    type Output = BigComplex;
+    fn sub(self, rhs: BigComplex) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl Mul for BigComplex {
+    type Output = BigComplex;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul for &BigComplex {
+    type Output = BigComplex;
+    fn mul(self, rhs: Self) -> Self::Output {
+        BigComplex::new(
+            &self.re * &rhs.re - &self.im * &rhs.im,
+            &self.re * &rhs.im + &self.im * &rhs.re,
+        )
+    }
+}
+
+impl Mul<&BigComplex> for BigComplex {
+    type Output = BigComplex;
+    fn mul(self, rhs: &BigComplex) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<BigComplex> for &BigComplex {
+    type Output = BigComplex;
+    fn mul(self, rhs: BigComplex) -> Self::Output {
+        self * &rhs
+    }
+}
+
+//like Div on BigNum the precision defaults to the limbs of both operands together
+impl Div for &BigComplex {
+    type Output = BigComplex;
+    fn div(self, rhs: Self) -> Self::Output {
+        let limb_count = self.re.get_precision().max(self.im.get_precision())
+            + rhs.re.get_precision().max(rhs.im.get_precision());
+        self.div_to(rhs, 64 * limb_count)
+    }
+}
+
+impl Div for BigComplex {
+    type Output = BigComplex;
+    fn div(self, rhs: Self) -> Self::Output {
+        &self / &rhs
+    }
+}
+
+impl Div<&BigComplex> for BigComplex {
+    type Output = BigComplex;
+    fn div(self, rhs: &BigComplex) -> Self::Output {
+        &self / rhs
+    }
+}
+
+impl Div<BigComplex> for &BigComplex {
+    type Output = BigComplex;
+    fn div(self, rhs: BigComplex) -> Self::Output {
+        self / &rhs
+    }
+}
+
+impl Neg for BigComplex {
+    type Output = BigComplex;
+    fn neg(self) -> Self::Output {
+        BigComplex::new(-self.re, -self.im)
+    }
+}
+
+impl Neg for &BigComplex {
+    type Output = BigComplex;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+impl Display for BigComplex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let re = encode(self.re.clone(), Base::Decimal);
+        let im = encode(self.im.clone(), Base::Decimal);
+        if im.starts_with('-') {
+            write!(f, "{}{}i", re, im)
+        } else {
+            write!(f, "{}+{}i", re, im)
+        }
+    }
+}
@@ -0,0 +1,552 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::str::FromStr;
+
+use crate::big_num::BigNum;
+use crate::limbs;
+use crate::rational::BigRational;
+
+//the largest power of ten in a limb, for converting digits 19 at a time
+const TEN_19: u64 = 10_000_000_000_000_000_000;
+
+//digits kept after the point by Div when the quotient does not terminate, on top of the larger
+//scale of the operands
+const DIVISION_DIGITS: i64 = 32;
+
+//how to pick between the two neighbouring values when a result has to drop digits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoundingMode {
+    //toward zero
+    Down,
+    //away from zero
+    Up,
+    //toward negative infinity
+    Floor,
+    //toward positive infinity
+    Ceiling,
+    //to the nearest, ties away from zero
+    HalfUp,
+    //to the nearest, ties toward zero
+    HalfDown,
+    //to the nearest, ties to the even neighbour (banker's rounding)
+    HalfEven,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseDecimalError {
+    message: String,
+}
+
+impl Display for ParseDecimalError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for ParseDecimalError {}
+
+//unscaled * 10^-scale, so 0.1 is exactly 1 with scale 1
+//the scale is part of the value the way it is written (1.50 keeps its two digits) but
+//comparisons only look at the number itself, so 1.50 == 1.5
+//results are exact, so add, sub and with_scale take time and memory that grow with the
+//difference of the scales, 1e-1000000 + 1 really has a million digits, while comparisons and
+//to_bignum only look that far when the operands themselves are that long
+#[derive(Debug, Clone)]
+pub struct BigDecimal {
+    unscaled: BigNum,
+    scale: i64,
+}
+
+//5^n by squaring
+pub(crate) fn pow5(n: u64) -> BigNum {
+    let mut result = vec![1];
+    for i in (0..64 - n.leading_zeros()).rev() {
+        result = limbs::mul(&result, &result);
+        if (n >> i) & 1 == 1 {
+            result = limbs::mul_small(&result, 5);
+        }
+    }
+    BigNum::from_magnitude(false, result)
+}
+
+//10^n = 5^n 2^n, the power of two only moves bits
+pub(crate) fn pow10(n: u64) -> BigNum {
+    pow5(n).mul_pow2(n as i64)
+}
+
+//the number of bits in an integer, 0 for zero
+fn bit_len(x: &BigNum) -> i128 {
+    match x.as_limbs().last() {
+        None => 0,
+        Some(top) => {
+            64 * (x.exponent() as i128 + x.as_limbs().len() as i128) - top.leading_zeros() as i128
+        }
+    }
+}
+
+//n / d rounded to an integer, both integers with d != 0
+pub(crate) fn div_round(n: &BigNum, d: &BigNum, mode: RoundingMode) -> BigNum {
+    assert!(!d.is_zero(), "Divide by zero");
+    let negative = (*n < BigNum::new()) != (*d < BigNum::new());
+    let d = d.magnitude();
+    let (q, r) = limbs::div_rem(&n.magnitude(), &d);
+    let away = if r.is_empty() {
+        false
+    } else {
+        let half = limbs::cmp(&limbs::shl(&r, 1), &d);
+        match mode {
+            RoundingMode::Down => false,
+            RoundingMode::Up => true,
+            RoundingMode::Floor => negative,
+            RoundingMode::Ceiling => !negative,
+            RoundingMode::HalfUp => half != Ordering::Less,
+            RoundingMode::HalfDown => half == Ordering::Greater,
+            RoundingMode::HalfEven => {
+                half == Ordering::Greater || (half == Ordering::Equal && limbs::bit(&q, 0))
+            }
+        }
+    };
+    let q = if away { limbs::add(&q, &[1]) } else { q };
+    BigNum::from_magnitude(negative, q)
+}
+
+//the decimal digits of an integer magnitude, "0" for zero
+fn to_digits(magnitude: &[u64]) -> String {
+    let mut chunks = Vec::new();
+    let mut rest = magnitude.to_vec();
+    limbs::trim(&mut rest);
+    while !rest.is_empty() {
+        let (q, r) = limbs::div_rem_small(&rest, TEN_19);
+        chunks.push(r);
+        rest = q;
+    }
+    match chunks.split_last() {
+        None => "0".to_string(),
+        Some((top, lower)) => {
+            let mut digits = top.to_string();
+            for chunk in lower.iter().rev() {
+                digits.push_str(&format!("{:019}", chunk));
+            }
+            digits
+        }
+    }
+}
+
+//an integer from ascii digits, which the caller has checked
+fn from_digits(digits: &str) -> BigNum {
+    let mut result = Vec::new();
+    for chunk in digits.as_bytes().chunks(19) {
+        let value = chunk
+            .iter()
+            .fold(0u64, |acc, d| acc * 10 + (d - b'0') as u64);
+        result = limbs::add(
+            &limbs::mul_small(&result, 10u64.pow(chunk.len() as u32)),
+            &[value],
+        );
+    }
+    BigNum::from_magnitude(false, result)
+}
+
+impl BigDecimal {
+    //unscaled * 10^-scale, unscaled has to be an integer
+    pub fn new(unscaled: BigNum, scale: i64) -> BigDecimal {
+        assert!(unscaled.is_integer(), "unscaled value must be an integer");
+        BigDecimal { unscaled, scale }
+    }
+
+    pub fn zero() -> BigDecimal {
+        BigDecimal::new(BigNum::new(), 0)
+    }
+
+    pub fn unscaled(&self) -> &BigNum {
+        &self.unscaled
+    }
+
+    pub fn scale(&self) -> i64 {
+        self.scale
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.unscaled.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.unscaled < BigNum::new()
+    }
+
+    pub fn abs(&self) -> BigDecimal {
+        BigDecimal::new(
+            BigNum::from_magnitude(false, self.unscaled.magnitude()),
+            self.scale,
+        )
+    }
+
+    //the same value with the given scale, rounding when that drops digits
+    pub fn with_scale(&self, scale: i64, mode: RoundingMode) -> BigDecimal {
+        let unscaled = match scale.cmp(&self.scale) {
+            Ordering::Equal => self.unscaled.clone(),
+            Ordering::Greater => &self.unscaled * pow10((scale - self.scale) as u64),
+            Ordering::Less => div_round(&self.unscaled, &pow10((self.scale - scale) as u64), mode),
+        };
+        BigDecimal::new(unscaled, scale)
+    }
+
+    //with_scale in place
+    pub fn rescale(&mut self, scale: i64, mode: RoundingMode) {
+        *self = self.with_scale(scale, mode);
+    }
+
+    //the same value without trailing zeros after the point
+    pub fn normalized(&self) -> BigDecimal {
+        let mut magnitude = self.unscaled.magnitude();
+        let mut scale = self.scale;
+        while scale > 0 && !magnitude.is_empty() {
+            let (q, r) = limbs::div_rem_small(&magnitude, 10);
+            if r != 0 {
+                break;
+            }
+            magnitude = q;
+            scale -= 1;
+        }
+        if magnitude.is_empty() {
+            scale = scale.min(0);
+        }
+        BigDecimal::new(BigNum::from_magnitude(self.is_negative(), magnitude), scale)
+    }
+
+    //self / rhs with the given scale
+    pub fn div_to(&self, rhs: &BigDecimal, scale: i64, mode: RoundingMode) -> BigDecimal {
+        //self / rhs = (a / b) 10^(rhs.scale - self.scale), so shift a or b until the quotient
+        //of the unscaled values has `scale` digits after the point
+        let shift = scale + rhs.scale - self.scale;
+        let (n, d) = if shift >= 0 {
+            (&self.unscaled * pow10(shift as u64), rhs.unscaled.clone())
+        } else {
+            (self.unscaled.clone(), &rhs.unscaled * pow10(-shift as u64))
+        };
+        BigDecimal::new(div_round(&n, &d, mode), scale)
+    }
+
+    //the exact value as a fraction
+    pub fn to_rational(&self) -> BigRational {
+        if self.scale >= 0 {
+            BigRational::new(self.unscaled.clone(), pow10(self.scale as u64))
+        } else {
+            BigRational::from_integer(&self.unscaled * pow10(-self.scale as u64))
+        }
+    }
+
+    //the exact value as a BigNum, None unless the denominator is a power of two
+    pub fn to_bignum(&self) -> Option<BigNum> {
+        if self.scale <= 0 {
+            return Some(&self.unscaled * pow10(-self.scale as u64));
+        }
+        if self.is_zero() {
+            return Some(BigNum::new());
+        }
+        //unscaled / 10^s = (unscaled / 5^s) / 2^s, and 5^s > 2^(2.32 s) can only divide an
+        //unscaled value that is longer than that
+        if self.scale as i128 * 232 / 100 >= bit_len(&self.unscaled) {
+            return None;
+        }
+        let five_pow = pow5(self.scale as u64).magnitude();
+        let (q, r) = limbs::div_rem(&self.unscaled.magnitude(), &five_pow);
+        if !r.is_empty() {
+            return None;
+        }
+        Some(BigNum::from_magnitude(self.is_negative(), q).mul_pow2(-self.scale))
+    }
+
+    //both values with the larger scale
+    fn aligned(&self, rhs: &BigDecimal) -> (BigNum, BigNum, i64) {
+        let scale = self.scale.max(rhs.scale);
+        let a = self.with_scale(scale, RoundingMode::Down).unscaled;
+        let b = rhs.with_scale(scale, RoundingMode::Down).unscaled;
+        (a, b, scale)
+    }
+}
+
+//every BigNum has a finite decimal expansion since 2^-k = 5^k / 10^k
+impl From<BigNum> for BigDecimal {
+    fn from(value: BigNum) -> Self {
+        if value.is_integer() {
+            return BigDecimal::new(value, 0);
+        }
+        //value = m 2^-b with m odd is m 5^b / 10^b, and m 5^b is odd so b is already the
+        //shortest scale
+        let bits = -(64 * value.exponent() + value.as_limbs()[0].trailing_zeros() as i64);
+        let odd = value.mul_pow2(bits);
+        BigDecimal::new(odd * pow5(bits as u64), bits)
+    }
+}
+
+impl From<i64> for BigDecimal {
+    fn from(value: i64) -> Self {
+        BigDecimal::new(BigNum::from(value), 0)
+    }
+}
+
+impl PartialEq for BigDecimal {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for BigDecimal {}
+
+impl PartialOrd for BigDecimal {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+//bounds on log10 |x| for x != 0 from the bit length of unscaled, with log10 2 = 0.30103
+//rounded down and up
+fn log10_bounds(x: &BigDecimal) -> (i128, i128) {
+    let bits = bit_len(&x.unscaled);
+    let scale = x.scale as i128;
+    (
+        (bits - 1) * 30102 / 100000 - scale - 1,
+        bits * 30103 / 100000 + 1 - scale,
+    )
+}
+
+impl Ord for BigDecimal {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let sign = self.unscaled.cmp(&BigNum::new());
+        let other_sign = other.unscaled.cmp(&BigNum::new());
+        if sign != other_sign || sign == Ordering::Equal {
+            return sign.cmp(&other_sign);
+        }
+        //far apart magnitudes are decided without aligning, which could take a huge power of
+        //ten for something like 1e-1000000 against 1
+        let (lo, hi) = log10_bounds(self);
+        let (other_lo, other_hi) = log10_bounds(other);
+        let magnitude = if hi < other_lo {
+            Ordering::Less
+        } else if lo > other_hi {
+            Ordering::Greater
+        } else {
+            let (a, b, _) = self.aligned(other);
+            return a.cmp(&b);
+        };
+        if sign == Ordering::Less {
+            magnitude.reverse()
+        } else {
+            magnitude
+        }
+    }
+}
+
+impl Add for BigDecimal {
+    type Output = BigDecimal;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl Add for &BigDecimal {
+    type Output = BigDecimal;
+    fn add(self, rhs: Self) -> Self::Output {
+        let (a, b, scale) = self.aligned(rhs);
+        BigDecimal::new(a + b, scale)
+    }
+}
+
+impl Add<&BigDecimal> for BigDecimal {
+    type Output = BigDecimal;
+    fn add(self, rhs: &BigDecimal) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn add(self, rhs: BigDecimal) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for BigDecimal {
+    type Output = BigDecimal;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl Sub for &BigDecimal {
+    type Output = BigDecimal;
+    fn sub(self, rhs: Self) -> Self::Output {
+        let (a, b, scale) = self.aligned(rhs);
+        BigDecimal::new(a - b, scale)
+    }
+}
+
+impl Sub<&BigDecimal> for BigDecimal {
+    type Output = BigDecimal;
+    fn sub(self, rhs: &BigDecimal) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn sub(self, rhs: BigDecimal) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl Mul for BigDecimal {
+    type Output = BigDecimal;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul for &BigDecimal {
+    type Output = BigDecimal;
+    fn mul(self, rhs: Self) -> Self::Output {
+        BigDecimal::new(&self.unscaled * &rhs.unscaled, self.scale + rhs.scale)
+    }
+}
+
+impl Mul<&BigDecimal> for BigDecimal {
+    type Output = BigDecimal;
+    fn mul(self, rhs: &BigDecimal) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn mul(self, rhs: BigDecimal) -> Self::Output {
+        self * &rhs
+    }
+}
+
+//exact whenever the quotient has a finite decimal expansion, otherwise rounded half even to
+//DIVISION_DIGITS more digits than either operand has after the point
+impl Div for &BigDecimal {
+    type Output = BigDecimal;
+    fn div(self, rhs: Self) -> Self::Output {
+        if rhs.is_zero() {
+            panic!("Divide by zero");
+        }
+        let quotient = self.to_rational() / rhs.to_rational();
+        //the reduced denominator is 2^a 5^b exactly when the quotient terminates, after
+        //max(a, b) digits
+        let mut den = quotient.denom().magnitude();
+        let twos = limbs::trailing_zeros(&den);
+        den = limbs::shr(&den, twos);
+        let mut fives = 0;
+        loop {
+            let (q, r) = limbs::div_rem_small(&den, 5);
+            if r != 0 {
+                break;
+            }
+            den = q;
+            fives += 1;
+        }
+        if limbs::is_one(&den) {
+            let scale = twos.max(fives) as i64;
+            let unscaled = quotient.numer() * pow10(scale as u64);
+            return BigDecimal::new(
+                div_round(&unscaled, quotient.denom(), RoundingMode::Down),
+                scale,
+            );
+        }
+        let scale = self.scale.max(rhs.scale).max(0) + DIVISION_DIGITS;
+        self.div_to(rhs, scale, RoundingMode::HalfEven)
+    }
+}
+
+impl Div for BigDecimal {
+    type Output = BigDecimal;
+    fn div(self, rhs: Self) -> Self::Output {
+        &self / &rhs
+    }
+}
+
+impl Div<&BigDecimal> for BigDecimal {
+    type Output = BigDecimal;
+    fn div(self, rhs: &BigDecimal) -> Self::Output {
+        &self / rhs
+    }
+}
+
+impl Div<BigDecimal> for &BigDecimal {
+    type Output = BigDecimal;
+    fn div(self, rhs: BigDecimal) -> Self::Output {
+        self / &rhs
+    }
+}
+
+impl Neg for BigDecimal {
+    type Output = BigDecimal;
+    fn neg(self) -> Self::Output {
+        BigDecimal::new(-self.unscaled, self.scale)
+    }
+}
+
+impl Neg for &BigDecimal {
+    type Output = BigDecimal;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+//plain decimal like -12.50, optionally with an exponent like 1.25e-3
+impl FromStr for BigDecimal {
+    type Err = ParseDecimalError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseDecimalError {
+            message: format!("Invalid decimal: `{}`", s),
+        };
+        let (mantissa, exponent) = match s.find(['e', 'E']) {
+            Some(i) => (&s[..i], s[i + 1..].parse::<i64>().map_err(|_| error())?),
+            None => (s, 0),
+        };
+        let (negative, mantissa) = match mantissa.strip_prefix('-') {
+            Some(rest) => (true, rest),
+            None => (false, mantissa.strip_prefix('+').unwrap_or(mantissa)),
+        };
+        let (whole, fraction) = mantissa.split_once('.').unwrap_or((mantissa, ""));
+        let digits = format!("{}{}", whole, fraction);
+        if digits.is_empty() || !digits.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(error());
+        }
+        let scale = (fraction.len() as i64)
+            .checked_sub(exponent)
+            .ok_or_else(error)?;
+        let unscaled = from_digits(&digits);
+        Ok(BigDecimal::new(
+            if negative { -unscaled } else { unscaled },
+            scale,
+        ))
+    }
+}
+
+//every digit down to the scale, no exponent
+impl Display for BigDecimal {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut digits = to_digits(&self.unscaled.magnitude());
+        if self.is_negative() {
+            write!(f, "-")?;
+        }
+        if self.scale <= 0 {
+            if !self.is_zero() {
+                digits.push_str(&"0".repeat(-self.scale as usize));
+            }
+            return write!(f, "{}", digits);
+        }
+        let scale = self.scale as usize;
+        if digits.len() <= scale {
+            digits.insert_str(0, &"0".repeat(scale + 1 - digits.len()));
+        }
+        let point = digits.len() - scale;
+        write!(f, "{}.{}", &digits[..point], &digits[point..])
+    }
+}
@@ -0,0 +1,410 @@
+use std::ops::{Add, Div, Mul, Neg, Sub};
+use std::sync::{Arc, Mutex};
+
+use crate::big_num::BigNum;
+use crate::decimal::{BigDecimal, RoundingMode};
+use crate::functions::{limbs_for, trunc_to_i64};
+
+//how close to zero a divisor (or the argument of ln) may get before we give up telling it
+//apart from zero, since no finite amount of precision can prove a value is exactly zero
+const ZERO_TEST_BITS: usize = 4096;
+
+//the most extra bits to_string spends on a value that sits right on a cut between two outputs
+const MAX_GUARD_BITS: usize = 4096;
+
+//the most bits e^x may have above the point, so x up to about 11354, past that exp panics
+//instead of computing ln 2 and the series to hundreds of thousands of bits
+const MAX_EXP_BITS: usize = 1 << 14;
+
+#[derive(Debug)]
+enum Node {
+    Exact(BigNum),
+    Pi,
+    Neg(ExactReal),
+    Add(ExactReal, ExactReal),
+    Mul(ExactReal, ExactReal),
+    Recip(ExactReal),
+    Sqrt(ExactReal),
+    Exp(ExactReal),
+    Ln(ExactReal),
+    Sin(ExactReal),
+    Cos(ExactReal),
+    Atan(ExactReal),
+}
+
+#[derive(Debug)]
+struct Inner {
+    node: Node,
+    //the most precise approximation computed so far and its bits
+    cache: Mutex<Option<(usize, BigNum)>>,
+}
+
+//a real number kept as the recipe for computing it rather than as digits
+//nothing is evaluated until an approximation is asked for, and then every node asks its inputs
+//for exactly as many bits as it needs to meet the requested error, so results never silently
+//lose digits the way a fixed precision does
+//approximations are cached, clones share them, and the whole thing can move between threads
+#[derive(Debug, Clone)]
+pub struct ExactReal {
+    inner: Arc<Inner>,
+}
+
+//x with the bits below 2^-bits dropped, an error below 2^-bits
+fn truncate_bits(x: BigNum, bits: usize) -> BigNum {
+    //get_integer_part hands back values with no limb above the point unchanged
+    match x.log2_floor() {
+        Some(e) if e >= -(bits as i64) => x
+            .mul_pow2(bits as i64)
+            .get_integer_part()
+            .mul_pow2(-(bits as i64)),
+        _ => BigNum::new(),
+    }
+}
+
+fn abs(x: &BigNum) -> BigNum {
+    if x < &BigNum::new() {
+        -x
+    } else {
+        x.clone()
+    }
+}
+
+impl ExactReal {
+    fn from_node(node: Node) -> ExactReal {
+        ExactReal {
+            inner: Arc::new(Inner {
+                node,
+                cache: Mutex::new(None),
+            }),
+        }
+    }
+
+    pub fn pi() -> ExactReal {
+        ExactReal::from_node(Node::Pi)
+    }
+
+    //the value itself when it is a known BigNum, which stays true through +, - and *
+    fn exact(&self) -> Option<&BigNum> {
+        match &self.inner.node {
+            Node::Exact(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    pub fn recip(&self) -> ExactReal {
+        ExactReal::from_node(Node::Recip(self.clone()))
+    }
+
+    pub fn sqrt(&self) -> ExactReal {
+        ExactReal::from_node(Node::Sqrt(self.clone()))
+    }
+
+    pub fn exp(&self) -> ExactReal {
+        ExactReal::from_node(Node::Exp(self.clone()))
+    }
+
+    pub fn ln(&self) -> ExactReal {
+        ExactReal::from_node(Node::Ln(self.clone()))
+    }
+
+    pub fn sin(&self) -> ExactReal {
+        ExactReal::from_node(Node::Sin(self.clone()))
+    }
+
+    pub fn cos(&self) -> ExactReal {
+        ExactReal::from_node(Node::Cos(self.clone()))
+    }
+
+    pub fn atan(&self) -> ExactReal {
+        ExactReal::from_node(Node::Atan(self.clone()))
+    }
+
+    //a BigNum within 2^-bits of the value
+    //panics for a divisor or logarithm argument within 2^-4096 of zero, for the square root
+    //or logarithm of a negative value, and for exp of a value above about 11354
+    pub fn approximate(&self, bits: usize) -> BigNum {
+        if let Some(value) = self.exact() {
+            return value.clone();
+        }
+        if let Some((cached_bits, value)) = &*self.inner.cache.lock().unwrap() {
+            if *cached_bits >= bits {
+                return value.clone();
+            }
+        }
+        let value = self.evaluate(bits);
+        *self.inner.cache.lock().unwrap() = Some((bits, value.clone()));
+        value
+    }
+
+    //k with |self| <= 2^k
+    fn upper_bits(&self) -> i64 {
+        let a = abs(&self.approximate(0)) + BigNum::from(1);
+        a.log2_floor().unwrap() + 1
+    }
+
+    //k with |self| >= 2^-k, by asking for more and more bits until the value is clearly away
+    //from zero
+    fn lower_bits(&self, message: &str) -> usize {
+        let mut bits = 8;
+        loop {
+            let a = abs(&self.approximate(bits));
+            if a > BigNum::from(1).mul_pow2(1 - bits as i64) {
+                return bits;
+            }
+            if bits > ZERO_TEST_BITS {
+                panic!("{}", message);
+            }
+            bits *= 2;
+        }
+    }
+
+    fn evaluate(&self, bits: usize) -> BigNum {
+        //every case gets within 2^-(bits + 1) and then drops what is below 2^-(bits + 1)
+        let target = bits + 1;
+        let value = match &self.inner.node {
+            Node::Exact(value) => value.clone(),
+            Node::Pi => BigNum::pi(target + 4),
+            Node::Neg(x) => -x.approximate(target),
+            Node::Add(x, y) => x.approximate(target + 1) + y.approximate(target + 1),
+            Node::Mul(x, y) => {
+                //|xy - x'y'| <= |x| |y - y'| + |y'| |x - x'|
+                let x_bits = x.upper_bits().max(0) as usize;
+                let y_bits = y.upper_bits().max(0) as usize;
+                x.approximate(target + y_bits + 2) * y.approximate(target + x_bits + 3)
+            }
+            Node::Recip(x) => {
+                //with |x| >= 2^-m, 1 / x moves by at most 2^(2m + 1) times the error in x
+                let m = x.lower_bits("Divide by zero");
+                let a = x.approximate(target + 2 * m + 2);
+                BigNum::from(1).div_to_limbs(&a, limbs_for(target + m + 3))
+            }
+            Node::Sqrt(x) => {
+                //|sqrt(x) - sqrt(x')| <= sqrt(|x - x'|) no matter how close to zero x is
+                let a = x.approximate(2 * target + 4);
+                if a < -BigNum::from(1).mul_pow2(-(2 * target as i64 + 4)) {
+                    panic!("square root of a negative number");
+                }
+                if a <= BigNum::new() {
+                    return BigNum::new();
+                }
+                let root_bits = (x.upper_bits().max(0) / 2) as usize;
+                a.sqrt_to_limbs(limbs_for(target + root_bits + 2))
+            }
+            Node::Exp(x) => {
+                //e^x = 2^(x / ln 2) with 1 / ln 2 < 1.443, which bounds both how much an error
+                //in x is magnified and how many bits the result has above the point, and for
+                //x <= 0 neither is more than 1
+                let upper = x.approximate(0) + BigNum::from(1);
+                //and e^x <= 2^x below zero, so far enough down it is 0 to the bits asked for
+                if upper <= -BigNum::from(target as u64 + 1) {
+                    return BigNum::new();
+                }
+                let result_bits = if upper <= BigNum::new() {
+                    1
+                } else {
+                    if upper > BigNum::from((MAX_EXP_BITS * 1000 / 1443) as u64) {
+                        panic!("exp of a number too large to compute");
+                    }
+                    (trunc_to_i64(&upper) as usize + 1) * 1443 / 1000 + 1
+                };
+                x.approximate(target + result_bits + 2)
+                    .exp(target + result_bits + 4)
+            }
+            Node::Ln(x) => {
+                let m = x.lower_bits("logarithm of a number that is not positive");
+                let a = x.approximate(target + m + 2);
+                if a <= BigNum::new() {
+                    panic!("logarithm of a number that is not positive");
+                }
+                let result_bits = 64 - (m as u64 + x.upper_bits().unsigned_abs()).leading_zeros();
+                a.ln(target + result_bits as usize + 4).unwrap()
+            }
+            //all three move by at most as much as their argument does
+            Node::Sin(x) => x.approximate(target + 2).sin(target + 2),
+            Node::Cos(x) => x.approximate(target + 2).cos(target + 2),
+            Node::Atan(x) => x.approximate(target + 2).atan(target + 3),
+        };
+        truncate_bits(value, target)
+    }
+
+    //the value with `digits` digits after the decimal point, truncated toward zero
+    //approximations are refined until both ends of their error bound print the same, so every
+    //digit shown is certain; a value exactly on a cut between two outputs (0.3 with five digits,
+    //say) can never be proven to be, so once the approximation is within 2^-4096 of a cut it is
+    //taken to be on it
+    pub fn to_string(&self, digits: usize) -> String {
+        let scale = digits as i64;
+        if let Some(value) = self.exact() {
+            return BigDecimal::from(value.clone())
+                .with_scale(scale, RoundingMode::Down)
+                .to_string();
+        }
+        //log2(10) < 3.33
+        let base_bits = digits * 333 / 100 + 1;
+        let mut guard = 8;
+        loop {
+            let bits = base_bits + guard;
+            let a = self.approximate(bits);
+            let error = BigNum::from(1).mul_pow2(-(bits as i64));
+            let lo = BigDecimal::from(&a - &error).with_scale(scale, RoundingMode::Down);
+            let hi = BigDecimal::from(&a + &error).with_scale(scale, RoundingMode::Down);
+            if lo == hi {
+                return lo.to_string();
+            }
+            if guard >= MAX_GUARD_BITS {
+                return BigDecimal::from(a)
+                    .with_scale(scale, RoundingMode::HalfUp)
+                    .to_string();
+            }
+            guard *= 2;
+        }
+    }
+}
+
+impl From<BigNum> for ExactReal {
+    fn from(value: BigNum) -> Self {
+        ExactReal::from_node(Node::Exact(value))
+    }
+}
+
+impl From<i64> for ExactReal {
+    fn from(value: i64) -> Self {
+        ExactReal::from(BigNum::from(value))
+    }
+}
+
+impl Add for ExactReal {
+    type Output = ExactReal;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl Add for &ExactReal {
+    type Output = ExactReal;
+    fn add(self, rhs: Self) -> Self::Output {
+        if let (Some(a), Some(b)) = (self.exact(), rhs.exact()) {
+            return ExactReal::from(a + b);
+        }
+        ExactReal::from_node(Node::Add(self.clone(), rhs.clone()))
+    }
+}
+
+impl Add<&ExactReal> for ExactReal {
+    type Output = ExactReal;
+    fn add(self, rhs: &ExactReal) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<ExactReal> for &ExactReal {
+    type Output = ExactReal;
+    fn add(self, rhs: ExactReal) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for ExactReal {
+    type Output = ExactReal;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl Sub for &ExactReal {
+    type Output = ExactReal;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + &-rhs
+    }
+}
+
+impl Sub<&ExactReal> for ExactReal {
+    type Output = ExactReal;
+    fn sub(self, rhs: &ExactReal) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<ExactReal> for &ExactReal {
+    type Output = ExactReal;
+    fn sub(self, rhs: ExactReal) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl Mul for ExactReal {
+    type Output = ExactReal;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul for &ExactReal {
+    type Output = ExactReal;
+    fn mul(self, rhs: Self) -> Self::Output {
+        if let (Some(a), Some(b)) = (self.exact(), rhs.exact()) {
+            return ExactReal::from(a * b);
+        }
+        ExactReal::from_node(Node::Mul(self.clone(), rhs.clone()))
+    }
+}
+
+impl Mul<&ExactReal> for ExactReal {
+    type Output = ExactReal;
+    fn mul(self, rhs: &ExactReal) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<ExactReal> for &ExactReal {
+    type Output = ExactReal;
+    fn mul(self, rhs: ExactReal) -> Self::Output {
+        self * &rhs
+    }
+}
+
+//lazy like everything else, dividing by zero only panics once an approximation is asked for
+impl Div for &ExactReal {
+    type Output = ExactReal;
+    fn div(self, rhs: Self) -> Self::Output {
+        ExactReal::from_node(Node::Mul(self.clone(), rhs.recip()))
+    }
+}
+
+impl Div for ExactReal {
+    type Output = ExactReal;
+    fn div(self, rhs: Self) -> Self::Output {
+        &self / &rhs
+    }
+}
+
+impl Div<&ExactReal> for ExactReal {
+    type Output = ExactReal;
+    fn div(self, rhs: &ExactReal) -> Self::Output {
+        &self / rhs
+    }
+}
+
+impl Div<ExactReal> for &ExactReal {
+    type Output = ExactReal;
+    fn div(self, rhs: ExactReal) -> Self::Output {
+        self / &rhs
+    }
+}
+
+impl Neg for ExactReal {
+    type Output = ExactReal;
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl Neg for &ExactReal {
+    type Output = ExactReal;
+    fn neg(self) -> Self::Output {
+        if let Some(value) = self.exact() {
+            return ExactReal::from(-value);
+        }
+        ExactReal::from_node(Node::Neg(self.clone()))
+    }
+}
@@ -0,0 +1,537 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::big_num::BigNum;
+use crate::limbs;
+
+//how hard factorize_with tries before giving up on a composite
+//the defaults reliably split off prime factors of up to about 13 digits in a second or two, and
+//larger ones only when p - 1 is smooth or a curve gets lucky, so a 60-100 digit number factors
+//when all but its largest prime factor are that small
+//raise rho_iterations and ecm_curves to reach further
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FactorOptions {
+    //trial divide by every prime below this bound
+    pub trial_limit: u64,
+    //stage 1 bound for pollard p-1
+    pub pm1_bound: u64,
+    //total iterations of brent's rho per composite, split over several polynomials
+    pub rho_iterations: u64,
+    //number of elliptic curves to try, 0 disables ecm
+    pub ecm_curves: u32,
+    //stage 1 bound for every elliptic curve
+    pub ecm_bound: u64,
+}
+
+impl Default for FactorOptions {
+    fn default() -> Self {
+        FactorOptions {
+            trial_limit: 10_000,
+            pm1_bound: 100_000,
+            rho_iterations: 500_000,
+            ecm_curves: 50,
+            ecm_bound: 11_000,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FactorError {
+    NotAnInteger,
+    Zero,
+    //the budget ran out, `found` holds the prime factors found so far and
+    //`remaining` the composite cofactors that could not be split
+    BudgetExhausted {
+        found: Vec<(BigNum, u32)>,
+        remaining: Vec<BigNum>,
+    },
+}
+
+impl Display for FactorError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FactorError::NotAnInteger => write!(f, "only integers can be factored"),
+            FactorError::Zero => write!(f, "zero has no prime factorization"),
+            FactorError::BudgetExhausted { remaining, .. } => {
+                write!(f, "gave up factoring")?;
+                for (i, n) in remaining.iter().enumerate() {
+                    let sep = if i == 0 { " " } else { ", " };
+                    write!(f, "{}{}", sep, n)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Error for FactorError {}
+
+impl BigNum {
+    //prime factors of self with their multiplicities, sorted by factor
+    //negative numbers get a leading factor of -1
+    pub fn factorize(&self) -> Result<Vec<(BigNum, u32)>, FactorError> {
+        self.factorize_with(&FactorOptions::default())
+    }
+
+    pub fn factorize_with(
+        &self,
+        options: &FactorOptions,
+    ) -> Result<Vec<(BigNum, u32)>, FactorError> {
+        if !self.is_integer() {
+            return Err(FactorError::NotAnInteger);
+        }
+        let n = self.magnitude();
+        if n.is_empty() {
+            return Err(FactorError::Zero);
+        }
+
+        let mut primes = Vec::new();
+        let mut remaining = Vec::new();
+        let mut stack = vec![trial_division(n, options.trial_limit, &mut primes)];
+        while let Some(m) = stack.pop() {
+            if limbs::is_one(&m) {
+                continue;
+            }
+            if is_probable_prime(&m) {
+                primes.push(m);
+                continue;
+            }
+            match split(&m, options) {
+                Some(d) => {
+                    let (q, _) = limbs::div_rem(&m, &d);
+                    stack.push(d);
+                    stack.push(q);
+                }
+                None => remaining.push(m),
+            }
+        }
+
+        primes.sort_by(|a, b| limbs::cmp(a, b));
+        let mut found: Vec<(BigNum, u32)> = Vec::new();
+        if self < &BigNum::from(0) {
+            found.push((BigNum::from(-1), 1));
+        }
+        for p in primes {
+            let p = BigNum::from_magnitude(false, p);
+            match found.last_mut() {
+                Some((last, count)) if *last == p => *count += 1,
+                _ => found.push((p, 1)),
+            }
+        }
+
+        if remaining.is_empty() {
+            Ok(found)
+        } else {
+            remaining.sort_by(|a, b| limbs::cmp(a, b));
+            Err(FactorError::BudgetExhausted {
+                found,
+                remaining: remaining
+                    .into_iter()
+                    .map(|m| BigNum::from_magnitude(false, m))
+                    .collect(),
+            })
+        }
+    }
+
+    //miller-rabin with the first 20 primes as witnesses, deterministic below 2^64 and
+    //wrong with probability at most 4^-20 above that
+    pub fn is_probable_prime(&self) -> bool {
+        if !self.is_integer() || self < &BigNum::from(0) {
+            return false;
+        }
+        is_probable_prime(&self.magnitude())
+    }
+}
+
+const SMALL_PRIMES: [u64; 20] = [
+    2, 3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71,
+];
+
+//numbers sieved at a time, so a huge bound costs time as the primes are used rather than memory
+const SEGMENT: u64 = 1 << 16;
+
+//the primes below a bound in increasing order, from a segmented sieve of eratosthenes
+pub(crate) struct Primes {
+    limit: u64,
+    //sieve[i] tells whether low + i is prime, next is the first index not yet returned
+    low: u64,
+    sieve: Vec<bool>,
+    next: usize,
+    //primes crossing out their multiples, pulled from `more` once a segment reaches their square
+    sieving: Vec<u64>,
+    more: Option<Box<Primes>>,
+}
+
+pub(crate) fn primes_below(limit: u64) -> Primes {
+    let end = limit.min(SEGMENT) as usize;
+    let mut sieve = vec![true; end];
+    for not_prime in sieve.iter_mut().take(2) {
+        *not_prime = false;
+    }
+    let mut i = 2;
+    while i * i < end {
+        if sieve[i] {
+            let mut j = i * i;
+            while j < end {
+                sieve[j] = false;
+                j += i;
+            }
+        }
+        i += 1;
+    }
+    Primes {
+        limit,
+        low: 0,
+        sieve,
+        next: 0,
+        sieving: Vec::new(),
+        more: None,
+    }
+}
+
+impl Primes {
+    fn sieve_segment(&mut self, low: u64, high: u64) {
+        let more = self
+            .more
+            .get_or_insert_with(|| Box::new(primes_below(u64::MAX)));
+        while self
+            .sieving
+            .last()
+            .is_none_or(|&p| p.saturating_mul(p) < high)
+        {
+            match more.next() {
+                Some(p) => self.sieving.push(p),
+                None => break,
+            }
+        }
+        self.sieve.clear();
+        self.sieve.resize((high - low) as usize, true);
+        for &p in self.sieving.iter() {
+            let first = match low.div_ceil(p).checked_mul(p) {
+                Some(first) => first.max(p.saturating_mul(p)),
+                None => continue,
+            };
+            for j in (first..high).step_by(p as usize) {
+                self.sieve[(j - low) as usize] = false;
+            }
+        }
+        self.low = low;
+        self.next = 0;
+    }
+}
+
+impl Iterator for Primes {
+    type Item = u64;
+
+    fn next(&mut self) -> Option<u64> {
+        loop {
+            while self.next < self.sieve.len() {
+                let i = self.next;
+                self.next += 1;
+                if self.sieve[i] {
+                    return Some(self.low + i as u64);
+                }
+            }
+            let low = self.low + self.sieve.len() as u64;
+            if low >= self.limit {
+                return None;
+            }
+            self.sieve_segment(low, self.limit.min(low.saturating_add(SEGMENT)));
+        }
+    }
+}
+
+fn trial_division(mut n: Vec<u64>, limit: u64, primes: &mut Vec<Vec<u64>>) -> Vec<u64> {
+    for p in primes_below(limit) {
+        loop {
+            let (q, r) = limbs::div_rem_small(&n, p);
+            if r != 0 {
+                break;
+            }
+            primes.push(vec![p]);
+            n = q;
+        }
+        //whatever is left has no factor below p, so it is prime if it is below p^2
+        if limbs::cmp(&n, &limbs::mul_small(&[p], p)) == Ordering::Less {
+            break;
+        }
+    }
+    n
+}
+
+pub(crate) fn is_probable_prime(n: &[u64]) -> bool {
+    if n.is_empty() || limbs::is_one(n) {
+        return false;
+    }
+    for &p in SMALL_PRIMES.iter() {
+        if limbs::cmp(n, &[p]) == Ordering::Equal {
+            return true;
+        }
+        if limbs::div_rem_small(n, p).1 == 0 {
+            return false;
+        }
+    }
+
+    //n - 1 = d * 2^s with d odd
+    let n_minus_one = limbs::sub(n, &[1]);
+    let s = limbs::trailing_zeros(&n_minus_one);
+    let d = limbs::shr(&n_minus_one, s);
+
+    'witness: for &a in SMALL_PRIMES.iter() {
+        let mut x = limbs::pow_mod(&[a], &d, n);
+        if limbs::is_one(&x) || limbs::cmp(&x, &n_minus_one) == Ordering::Equal {
+            continue;
+        }
+        for _ in 1..s {
+            x = limbs::mul_mod(&x, &x, n);
+            if limbs::cmp(&x, &n_minus_one) == Ordering::Equal {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+//find a non trivial factor of the composite n
+fn split(n: &[u64], options: &FactorOptions) -> Option<Vec<u64>> {
+    //perfect squares defeat p-1 and rho often enough to check for them first
+    let root = limbs::isqrt(n);
+    if limbs::cmp(&limbs::mul(&root, &root), n) == Ordering::Equal {
+        return Some(root);
+    }
+    if let Some(d) = pollard_pm1(n, options.pm1_bound) {
+        return Some(d);
+    }
+    if let Some(d) = pollard_brent(n, options.rho_iterations) {
+        return Some(d);
+    }
+    ecm(n, options.ecm_curves, options.ecm_bound)
+}
+
+fn is_proper_factor(d: &[u64], n: &[u64]) -> bool {
+    !limbs::is_one(d) && limbs::cmp(d, n) == Ordering::Less
+}
+
+fn pollard_pm1(n: &[u64], bound: u64) -> Option<Vec<u64>> {
+    let mut a = vec![2];
+    //a before the primes applied since the last gcd, to step through them again when that gcd
+    //catches every prime factor at once
+    let mut checkpoint = a.clone();
+    let mut batch = Vec::new();
+    let mut primes = primes_below(bound);
+    loop {
+        let p = primes.next();
+        if let Some(p) = p {
+            //raise to the largest power of p that stays below the bound
+            let mut pk = p;
+            while pk <= bound / p {
+                pk *= p;
+            }
+            a = limbs::pow_mod(&a, &[pk], n);
+            batch.push(p);
+            if batch.len() < 64 {
+                continue;
+            }
+        }
+        let d = limbs::gcd(&limbs::sub_mod(&a, &[1], n), n);
+        if is_proper_factor(&d, n) {
+            return Some(d);
+        }
+        if !limbs::is_one(&d) {
+            return pm1_backtrack(checkpoint, &batch, bound, n);
+        }
+        p?;
+        checkpoint = a.clone();
+        batch.clear();
+    }
+}
+
+//redo a batch of p-1 one prime at a time, taking the gcd after every step, which separates
+//factors unless one prime power catches them all
+fn pm1_backtrack(mut a: Vec<u64>, batch: &[u64], bound: u64, n: &[u64]) -> Option<Vec<u64>> {
+    for &p in batch {
+        let mut pk = p;
+        loop {
+            a = limbs::pow_mod(&a, &[p], n);
+            let d = limbs::gcd(&limbs::sub_mod(&a, &[1], n), n);
+            if is_proper_factor(&d, n) {
+                return Some(d);
+            }
+            if !limbs::is_one(&d) {
+                return None;
+            }
+            if pk > bound / p {
+                break;
+            }
+            pk *= p;
+        }
+    }
+    None
+}
+
+//brent's variant of pollard's rho with x -> x^2 + c, trying a new c when a cycle gives n back
+fn pollard_brent(n: &[u64], max_iterations: u64) -> Option<Vec<u64>> {
+    const BATCH: u64 = 128;
+    let mut iterations = 0;
+    for c in 1u64.. {
+        let f = |x: &[u64]| limbs::add_mod(&limbs::mul_mod(x, x, n), &limbs::rem(&[c], n), n);
+        let mut y = vec![2];
+        let mut x = y.clone();
+        let mut ys = y.clone();
+        let mut q = vec![1];
+        let mut d = vec![1];
+        let mut r = 1;
+        while limbs::is_one(&d) {
+            x = y.clone();
+            for _ in 0..r {
+                y = f(&y);
+            }
+            let mut k = 0;
+            while k < r && limbs::is_one(&d) {
+                ys = y.clone();
+                let steps = BATCH.min(r - k);
+                for _ in 0..steps {
+                    y = f(&y);
+                    q = limbs::mul_mod(&q, &abs_diff(&x, &y), n);
+                }
+                d = limbs::gcd(&q, n);
+                k += steps;
+                iterations += steps;
+                if iterations >= max_iterations {
+                    break;
+                }
+            }
+            if iterations >= max_iterations {
+                break;
+            }
+            r *= 2;
+        }
+        if limbs::cmp(&d, n) == Ordering::Equal {
+            //the batch overshot, redo it one step at a time
+            loop {
+                ys = f(&ys);
+                d = limbs::gcd(&abs_diff(&x, &ys), n);
+                if !limbs::is_one(&d) {
+                    break;
+                }
+            }
+        }
+        if is_proper_factor(&d, n) {
+            return Some(d);
+        }
+        if iterations >= max_iterations {
+            return None;
+        }
+    }
+    None
+}
+
+fn abs_diff(a: &[u64], b: &[u64]) -> Vec<u64> {
+    if limbs::cmp(a, b) == Ordering::Less {
+        limbs::sub(b, a)
+    } else {
+        limbs::sub(a, b)
+    }
+}
+
+//lenstra's elliptic curve method, stage 1 only, on montgomery curves with suyama's parametrization
+fn ecm(n: &[u64], curves: u32, bound: u64) -> Option<Vec<u64>> {
+    for sigma in 6..6 + curves as u64 {
+        let sigma = limbs::rem(&[sigma], n);
+        let u = limbs::sub_mod(&limbs::mul_mod(&sigma, &sigma, n), &limbs::rem(&[5], n), n);
+        let v = limbs::mul_mod(&sigma, &[4], n);
+        let u3 = limbs::mul_mod(&limbs::mul_mod(&u, &u, n), &u, n);
+        let v_minus_u = limbs::sub_mod(&v, &u, n);
+        let v_minus_u3 = limbs::mul_mod(&limbs::mul_mod(&v_minus_u, &v_minus_u, n), &v_minus_u, n);
+        let three_u_plus_v = limbs::add_mod(&limbs::mul_mod(&u, &[3], n), &v, n);
+
+        //a24 = (A + 2) / 4 = (v - u)^3 (3u + v) / (16 u^3 v)
+        let numerator = limbs::mul_mod(&v_minus_u3, &three_u_plus_v, n);
+        let denominator = limbs::mul_mod(&limbs::mul_mod(&u3, &v, n), &[16], n);
+        let a24 = match limbs::inv_mod(&denominator, n) {
+            Ok(inv) => limbs::mul_mod(&numerator, &inv, n),
+            Err(d) if is_proper_factor(&d, n) => return Some(d),
+            Err(_) => continue,
+        };
+
+        let curve = Curve { n, a24 };
+        let mut point = (u3, limbs::mul_mod(&limbs::mul_mod(&v, &v, n), &v, n));
+        for p in primes_below(bound) {
+            let mut pk = p;
+            while pk <= bound / p {
+                pk *= p;
+            }
+            point = curve.ladder(&point, pk);
+        }
+        let d = limbs::gcd(&point.1, n);
+        if is_proper_factor(&d, n) {
+            return Some(d);
+        }
+    }
+    None
+}
+
+type Point = (Vec<u64>, Vec<u64>);
+
+struct Curve<'a> {
+    n: &'a [u64],
+    a24: Vec<u64>,
+}
+
+impl Curve<'_> {
+    fn double(&self, p: &Point) -> Point {
+        let n = self.n;
+        let sum = limbs::add_mod(&p.0, &p.1, n);
+        let diff = limbs::sub_mod(&p.0, &p.1, n);
+        let sum2 = limbs::mul_mod(&sum, &sum, n);
+        let diff2 = limbs::mul_mod(&diff, &diff, n);
+        let t = limbs::sub_mod(&sum2, &diff2, n);
+        let x = limbs::mul_mod(&sum2, &diff2, n);
+        let z = limbs::mul_mod(
+            &t,
+            &limbs::add_mod(&diff2, &limbs::mul_mod(&self.a24, &t, n), n),
+            n,
+        );
+        (x, z)
+    }
+
+    //p + q given p - q
+    fn add(&self, p: &Point, q: &Point, diff: &Point) -> Point {
+        let n = self.n;
+        let u = limbs::mul_mod(
+            &limbs::sub_mod(&p.0, &p.1, n),
+            &limbs::add_mod(&q.0, &q.1, n),
+            n,
+        );
+        let v = limbs::mul_mod(
+            &limbs::add_mod(&p.0, &p.1, n),
+            &limbs::sub_mod(&q.0, &q.1, n),
+            n,
+        );
+        let sum = limbs::add_mod(&u, &v, n);
+        let difference = limbs::sub_mod(&u, &v, n);
+        let x = limbs::mul_mod(&diff.1, &limbs::mul_mod(&sum, &sum, n), n);
+        let z = limbs::mul_mod(&diff.0, &limbs::mul_mod(&difference, &difference, n), n);
+        (x, z)
+    }
+
+    //montgomery ladder computing k * p
+    fn ladder(&self, p: &Point, k: u64) -> Point {
+        let mut r0 = p.clone();
+        let mut r1 = self.double(p);
+        for i in (0..63 - k.leading_zeros()).rev() {
+            if (k >> i) & 1 == 1 {
+                r0 = self.add(&r1, &r0, p);
+                r1 = self.double(&r1);
+            } else {
+                r1 = self.add(&r1, &r0, p);
+                r0 = self.double(&r0);
+            }
+        }
+        r0
+    }
+}
@@ -0,0 +1,425 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::big_num::{BigNum, ExponentError};
+use crate::codec::{encode, Base};
+use crate::functions::{limbs_for, ln2};
+
+#[derive(Debug, Clone)]
+enum Value {
+    //never zero, zeros carry their sign separately
+    Finite(BigNum),
+    Zero { negative: bool },
+    Infinity { negative: bool },
+    NaN,
+}
+
+//a BigNum extended with signed zero, the infinities and NaN, following IEEE 754: dividing a
+//non zero value by zero is an infinity, 0 / 0, inf - inf, sqrt(-1) and ln(-1) are NaN, ln(0) is
+//-inf, and results past the exponent range become an infinity or a zero instead of panicking
+//finite arithmetic is the same as on BigNum, add, sub and mul are exact and division rounds
+//toward zero to as many limbs as both operands have together, except that a sum of operands more
+//than BigNum::MAX_SPAN limbs apart rounds to the larger one instead of panicking
+#[derive(Debug, Clone)]
+pub struct BigFloat {
+    value: Value,
+}
+
+impl BigFloat {
+    pub fn zero() -> BigFloat {
+        BigFloat::signed_zero(false)
+    }
+
+    pub fn neg_zero() -> BigFloat {
+        BigFloat::signed_zero(true)
+    }
+
+    pub fn infinity() -> BigFloat {
+        BigFloat::signed_infinity(false)
+    }
+
+    pub fn neg_infinity() -> BigFloat {
+        BigFloat::signed_infinity(true)
+    }
+
+    pub fn nan() -> BigFloat {
+        BigFloat { value: Value::NaN }
+    }
+
+    fn signed_zero(negative: bool) -> BigFloat {
+        BigFloat {
+            value: Value::Zero { negative },
+        }
+    }
+
+    fn signed_infinity(negative: bool) -> BigFloat {
+        BigFloat {
+            value: Value::Infinity { negative },
+        }
+    }
+
+    //an exact result or what it saturates to when its exponent is out of range
+    fn saturate(result: Result<BigNum, ExponentError>, negative: bool) -> BigFloat {
+        match result {
+            Ok(value) => BigFloat::from(value),
+            Err(ExponentError::Overflow) => BigFloat::signed_infinity(negative),
+            Err(ExponentError::Underflow) => BigFloat::signed_zero(negative),
+            //only add and sub give this, and add rounds instead
+            Err(ExponentError::TooWide) => unreachable!(),
+        }
+    }
+
+    pub fn is_nan(&self) -> bool {
+        matches!(self.value, Value::NaN)
+    }
+
+    pub fn is_infinite(&self) -> bool {
+        matches!(self.value, Value::Infinity { .. })
+    }
+
+    //zero or any other BigNum
+    pub fn is_finite(&self) -> bool {
+        matches!(self.value, Value::Finite(_) | Value::Zero { .. })
+    }
+
+    pub fn is_zero(&self) -> bool {
+        matches!(self.value, Value::Zero { .. })
+    }
+
+    //true for -0 and -inf too, NaN has no sign
+    pub fn is_sign_negative(&self) -> bool {
+        match &self.value {
+            Value::Finite(value) => value < &BigNum::new(),
+            Value::Zero { negative } | Value::Infinity { negative } => *negative,
+            Value::NaN => false,
+        }
+    }
+
+    //the value for finite ones, both zeros give BigNum zero
+    pub fn to_bignum(&self) -> Option<BigNum> {
+        match &self.value {
+            Value::Finite(value) => Some(value.clone()),
+            Value::Zero { .. } => Some(BigNum::new()),
+            _ => None,
+        }
+    }
+
+    //self / rhs to `precision` bits, rounding toward zero
+    pub fn div_to(&self, rhs: &BigFloat, precision: usize) -> BigFloat {
+        let negative = self.is_sign_negative() != rhs.is_sign_negative();
+        match (&self.value, &rhs.value) {
+            (Value::NaN, _) | (_, Value::NaN) => BigFloat::nan(),
+            (Value::Infinity { .. }, Value::Infinity { .. }) => BigFloat::nan(),
+            (Value::Zero { .. }, Value::Zero { .. }) => BigFloat::nan(),
+            (Value::Infinity { .. }, _) => BigFloat::signed_infinity(negative),
+            (_, Value::Infinity { .. }) => BigFloat::signed_zero(negative),
+            (Value::Zero { .. }, _) => BigFloat::signed_zero(negative),
+            (Value::Finite(_), Value::Zero { .. }) => BigFloat::signed_infinity(negative),
+            (Value::Finite(a), Value::Finite(b)) => {
+                BigFloat::saturate(a.checked_div_to_limbs(b, limbs_for(precision)), negative)
+            }
+        }
+    }
+
+    //the square root, sqrt(-0) is -0 and anything below that is NaN
+    pub fn sqrt(&self, precision: usize) -> BigFloat {
+        match &self.value {
+            Value::NaN | Value::Infinity { negative: true } => BigFloat::nan(),
+            Value::Zero { .. } | Value::Infinity { negative: false } => self.clone(),
+            Value::Finite(value) => match value.sqrt(precision) {
+                Ok(root) => BigFloat::from(root),
+                Err(_) => BigFloat::nan(),
+            },
+        }
+    }
+
+    //the natural log, -inf at either zero and NaN below them
+    pub fn ln(&self, precision: usize) -> BigFloat {
+        match &self.value {
+            Value::NaN | Value::Infinity { negative: true } => BigFloat::nan(),
+            Value::Zero { .. } => BigFloat::neg_infinity(),
+            Value::Infinity { negative: false } => self.clone(),
+            Value::Finite(value) => match value.ln(precision) {
+                Ok(log) => BigFloat::from(log),
+                Err(_) => BigFloat::nan(),
+            },
+        }
+    }
+
+    //e^self, +inf or +0 once the result would be past the exponent range
+    pub fn exp(&self, precision: usize) -> BigFloat {
+        match &self.value {
+            Value::NaN => BigFloat::nan(),
+            Value::Zero { .. } => BigFloat::from(BigNum::from(1)),
+            Value::Infinity { negative } => {
+                if *negative {
+                    BigFloat::zero()
+                } else {
+                    BigFloat::infinity()
+                }
+            }
+            Value::Finite(value) => {
+                //e^x has its top limb near x / (64 ln 2), which has to leave room for the
+                //limbs below it on the way down
+                let room = BigNum::MAX_EXP as u64 - limbs_for(precision) as u64 - 2;
+                let limit = ln2(64) * BigNum::from(64 * room);
+                if value >= &limit {
+                    BigFloat::infinity()
+                } else if value <= &-limit {
+                    BigFloat::zero()
+                } else {
+                    BigFloat::from(value.exp(precision))
+                }
+            }
+        }
+    }
+
+    //orders every value, NaN included: -inf, negatives, -0, +0, positives, +inf, NaN
+    //this is IEEE totalOrder with the one NaN taking the place of a positive NaN
+    pub fn total_cmp(&self, other: &BigFloat) -> Ordering {
+        match (self.is_nan(), other.is_nan()) {
+            (true, true) => Ordering::Equal,
+            (true, false) => Ordering::Greater,
+            (false, true) => Ordering::Less,
+            (false, false) => self
+                .numeric_cmp(other)
+                .then(other.is_sign_negative().cmp(&self.is_sign_negative())),
+        }
+    }
+
+    //the IEEE comparison of two values that aren't NaN, which has -0 == +0
+    fn numeric_cmp(&self, other: &BigFloat) -> Ordering {
+        //-inf, negatives, zeros, positives, +inf
+        let class = |x: &BigFloat| match &x.value {
+            Value::Infinity { negative: true } => -2,
+            Value::Infinity { negative: false } => 2,
+            Value::Zero { .. } | Value::NaN => 0,
+            Value::Finite(_) if x.is_sign_negative() => -1,
+            Value::Finite(_) => 1,
+        };
+        match (&self.value, &other.value) {
+            (Value::Finite(a), Value::Finite(b)) => a.cmp(b),
+            _ => class(self).cmp(&class(other)),
+        }
+    }
+}
+
+impl From<BigNum> for BigFloat {
+    fn from(value: BigNum) -> Self {
+        if value.is_zero() {
+            BigFloat::zero()
+        } else {
+            BigFloat {
+                value: Value::Finite(value),
+            }
+        }
+    }
+}
+
+impl Add for BigFloat {
+    type Output = BigFloat;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl Add for &BigFloat {
+    type Output = BigFloat;
+    fn add(self, rhs: Self) -> Self::Output {
+        match (&self.value, &rhs.value) {
+            (Value::NaN, _) | (_, Value::NaN) => BigFloat::nan(),
+            (Value::Infinity { negative: a }, Value::Infinity { negative: b }) => {
+                if a == b {
+                    self.clone()
+                } else {
+                    BigFloat::nan()
+                }
+            }
+            (Value::Infinity { .. }, _) => self.clone(),
+            (_, Value::Infinity { .. }) => rhs.clone(),
+            //-0 + -0 is the only sum that is -0
+            (Value::Zero { negative: a }, Value::Zero { negative: b }) => {
+                BigFloat::signed_zero(*a && *b)
+            }
+            (Value::Zero { .. }, _) => rhs.clone(),
+            (_, Value::Zero { .. }) => self.clone(),
+            //only a sum of two values with the same sign can leave the range
+            (Value::Finite(a), Value::Finite(b)) => match a.checked_add(b) {
+                //the smaller one is below the last of MAX_SPAN limbs of the larger
+                Err(ExponentError::TooWide) => {
+                    if a.log2_floor() > b.log2_floor() {
+                        self.clone()
+                    } else {
+                        rhs.clone()
+                    }
+                }
+                result => BigFloat::saturate(result, a.is_negative()),
+            },
+        }
+    }
+}
+
+impl Add<&BigFloat> for BigFloat {
+    type Output = BigFloat;
+    fn add(self, rhs: &BigFloat) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<BigFloat> for &BigFloat {
+    type Output = BigFloat;
+    fn add(self, rhs: BigFloat) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for BigFloat {
+    type Output = BigFloat;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl Sub for &BigFloat {
+    type Output = BigFloat;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + &-rhs
+    }
+}
+
+impl Sub<&BigFloat> for BigFloat {
+    type Output = BigFloat;
+    fn sub(self, rhs: &BigFloat) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<BigFloat> for &BigFloat {
+    type Output = BigFloat;
+    fn sub(self, rhs: BigFloat) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl Mul for BigFloat {
+    type Output = BigFloat;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul for &BigFloat {
+    type Output = BigFloat;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let negative = self.is_sign_negative() != rhs.is_sign_negative();
+        match (&self.value, &rhs.value) {
+            (Value::NaN, _) | (_, Value::NaN) => BigFloat::nan(),
+            (Value::Infinity { .. }, Value::Zero { .. })
+            | (Value::Zero { .. }, Value::Infinity { .. }) => BigFloat::nan(),
+            (Value::Infinity { .. }, _) | (_, Value::Infinity { .. }) => {
+                BigFloat::signed_infinity(negative)
+            }
+            (Value::Zero { .. }, _) | (_, Value::Zero { .. }) => BigFloat::signed_zero(negative),
+            (Value::Finite(a), Value::Finite(b)) => BigFloat::saturate(a.checked_mul(b), negative),
+        }
+    }
+}
+
+impl Mul<&BigFloat> for BigFloat {
+    type Output = BigFloat;
+    fn mul(self, rhs: &BigFloat) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<BigFloat> for &BigFloat {
+    type Output = BigFloat;
+    fn mul(self, rhs: BigFloat) -> Self::Output {
+        self * &rhs
+    }
+}
+
+impl Div for BigFloat {
+    type Output = BigFloat;
+    fn div(self, rhs: Self) -> Self::Output {
+        &self / &rhs
+    }
+}
+
+impl Div for &BigFloat {
+    type Output = BigFloat;
+    fn div(self, rhs: Self) -> Self::Output {
+        let limbs = |x: &BigFloat| match &x.value {
+            Value::Finite(value) => value.get_precision(),
+            _ => 0,
+        };
+        self.div_to(rhs, 64 * (limbs(self) + limbs(rhs)))
+    }
+}
+
+impl Div<&BigFloat> for BigFloat {
+    type Output = BigFloat;
+    fn div(self, rhs: &BigFloat) -> Self::Output {
+        &self / rhs
+    }
+}
+
+impl Div<BigFloat> for &BigFloat {
+    type Output = BigFloat;
+    fn div(self, rhs: BigFloat) -> Self::Output {
+        self / &rhs
+    }
+}
+
+impl Neg for BigFloat {
+    type Output = BigFloat;
+    fn neg(self) -> Self::Output {
+        let value = match self.value {
+            Value::Finite(value) => Value::Finite(-value),
+            Value::Zero { negative } => Value::Zero {
+                negative: !negative,
+            },
+            Value::Infinity { negative } => Value::Infinity {
+                negative: !negative,
+            },
+            Value::NaN => Value::NaN,
+        };
+        BigFloat { value }
+    }
+}
+
+impl Neg for &BigFloat {
+    type Output = BigFloat;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+//NaN is unequal to everything, itself included, and -0 == +0
+impl PartialEq for BigFloat {
+    fn eq(&self, other: &Self) -> bool {
+        self.partial_cmp(other) == Some(Ordering::Equal)
+    }
+}
+
+//None whenever NaN is involved
+impl PartialOrd for BigFloat {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        if self.is_nan() || other.is_nan() {
+            return None;
+        }
+        Some(self.numeric_cmp(other))
+    }
+}
+
+impl Display for BigFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.value {
+            Value::Finite(value) => write!(f, "{}", encode(value.clone(), Base::Decimal)),
+            Value::Zero { negative } => write!(f, "{}0", if *negative { "-" } else { "" }),
+            Value::Infinity { negative } => write!(f, "{}inf", if *negative { "-" } else { "" }),
+            Value::NaN => write!(f, "NaN"),
+        }
+    }
+}
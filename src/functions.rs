@@ -0,0 +1,291 @@
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::big_num::BigNum;
+
+//elementary functions evaluated to a requested number of bits
+//exp, ln and sqrt have a relative error below 2^-precision, sin, cos and atan an absolute one
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DomainError {
+    message: String,
+}
+
+impl DomainError {
+    pub(crate) fn new<T: Into<String>>(message: T) -> DomainError {
+        DomainError {
+            message: message.into(),
+        }
+    }
+}
+
+impl Display for DomainError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl Error for DomainError {}
+
+//limbs needed to hold `bits` bits with a guard limb to spare
+pub(crate) fn limbs_for(bits: usize) -> usize {
+    bits.div_ceil(64) + 1
+}
+
+//true when |x| < 2^-bits
+pub(crate) fn negligible(x: &BigNum, bits: usize) -> bool {
+    match x.log2_floor() {
+        None => true,
+        Some(e) => e < -(bits as i64),
+    }
+}
+
+//the integer part of x as an i64, rounding toward zero
+pub(crate) fn trunc_to_i64(x: &BigNum) -> i64 {
+    let magnitude = x.magnitude();
+    assert!(
+        magnitude.len() <= 1 && magnitude.first().unwrap_or(&0) >> 63 == 0,
+        "exponent overflow"
+    );
+    let value = *magnitude.first().unwrap_or(&0) as i64;
+    if x < &BigNum::from(0) {
+        -value
+    } else {
+        value
+    }
+}
+
+//sum of z^(2k + 1) / (2k + 1), needs |z| well below 1 to converge quickly
+fn atanh_series(z: &BigNum, bits: usize) -> BigNum {
+    let n = limbs_for(bits);
+    let z2 = (z * z).round_to_limbs(n);
+    let mut power = z.clone();
+    let mut sum = z.clone();
+    for k in 1u64.. {
+        power = (&power * &z2).round_to_limbs(n);
+        let term = power.div_to_limbs(&BigNum::from(2 * k + 1), n);
+        if negligible(&term, bits) {
+            break;
+        }
+        sum = (sum + term).round_to_limbs(n + 1);
+    }
+    sum
+}
+
+//atan(1 / m) = sum of (-1)^k / ((2k + 1) m^(2k + 1))
+fn atan_inv(m: u64, bits: usize) -> BigNum {
+    let n = limbs_for(bits);
+    let m2 = BigNum::from(m * m);
+    let mut power = BigNum::from(1).div_to_limbs(&BigNum::from(m), n);
+    let mut sum = power.clone();
+    for k in 1u64.. {
+        power = power.div_to_limbs(&m2, n);
+        let term = power.div_to_limbs(&BigNum::from(2 * k + 1), n);
+        if negligible(&term, bits) {
+            break;
+        }
+        if k % 2 == 1 {
+            sum = (sum - term).round_to_limbs(n + 1);
+        } else {
+            sum = (sum + term).round_to_limbs(n + 1);
+        }
+    }
+    sum
+}
+
+//ln 2 = 2 atanh(1/3)
+pub(crate) fn ln2(bits: usize) -> BigNum {
+    let third = BigNum::from(1).div_to_limbs(&BigNum::from(3), limbs_for(bits + 8));
+    atanh_series(&third, bits + 8).mul_pow2(1)
+}
+
+impl BigNum {
+    //pi by machin's formula, pi = 16 atan(1/5) - 4 atan(1/239)
+    pub fn pi(precision: usize) -> BigNum {
+        let bits = precision + 16;
+        let pi = atan_inv(5, bits).mul_pow2(4) - atan_inv(239, bits).mul_pow2(2);
+        pi.round_to_limbs(limbs_for(precision))
+    }
+
+    pub fn exp(&self, precision: usize) -> BigNum {
+        if self.is_zero() {
+            return BigNum::from(1);
+        }
+        //self = k ln 2 + r with |r| < ln 2, then e^self = 2^k e^r
+        let int_bits = self.log2_floor().unwrap().max(0) as usize;
+        let ln2 = ln2(precision + int_bits + 64);
+        let k = trunc_to_i64(&self.div_to_limbs(&ln2, limbs_for(int_bits + 8)));
+        let r = self - BigNum::from(k) * &ln2;
+
+        //shrink r further so the taylor series converges fast, then square the result back up
+        let halvings = (precision as f64).sqrt() as usize + 1;
+        let bits = precision + halvings + 64;
+        let n = limbs_for(bits);
+        let r = r.mul_pow2(-(halvings as i64));
+        let mut sum = BigNum::from(1);
+        let mut term = BigNum::from(1);
+        for i in 1u64.. {
+            term = (&term * &r)
+                .round_to_limbs(n)
+                .div_to_limbs(&BigNum::from(i), n);
+            if negligible(&term, bits) {
+                break;
+            }
+            sum = (sum + &term).round_to_limbs(n + 1);
+        }
+        for _ in 0..halvings {
+            sum = (&sum * &sum).round_to_limbs(n);
+        }
+        sum.mul_pow2(k).round_to_limbs(limbs_for(precision))
+    }
+
+    pub fn ln(&self, precision: usize) -> Result<BigNum, DomainError> {
+        if self <= &BigNum::from(0) {
+            return Err(DomainError::new(
+                "logarithm of a number that is not positive",
+            ));
+        }
+        //self = m 2^e with m in [2/3, 4/3), then ln(self) = 2 atanh((m - 1) / (m + 1)) + e ln 2
+        let mut e = self.log2_floor().unwrap();
+        let mut m = self.clone().mul_pow2(-e);
+        if &m * BigNum::from(3) >= BigNum::from(4) {
+            m = m.mul_pow2(-1);
+            e += 1;
+        }
+        let bits = precision + 64 + (64 - e.unsigned_abs().leading_zeros()) as usize;
+        let n = limbs_for(bits);
+        let z = (&m - BigNum::from(1)).div_to_limbs(&(&m + BigNum::from(1)), n);
+        let result = atanh_series(&z, bits).mul_pow2(1) + BigNum::from(e) * ln2(bits);
+        Ok(result.round_to_limbs(limbs_for(precision)))
+    }
+
+    pub fn sqrt(&self, precision: usize) -> Result<BigNum, DomainError> {
+        if self < &BigNum::from(0) {
+            return Err(DomainError::new("square root of a negative number"));
+        }
+        Ok(self.sqrt_to_limbs(limbs_for(precision)))
+    }
+
+    //(sin, cos) with an absolute error below 2^-precision each
+    pub fn sin_cos(&self, precision: usize) -> (BigNum, BigNum) {
+        //take out whole turns first
+        let int_bits = self.log2_floor().unwrap_or(0).max(0) as usize;
+        let two_pi = BigNum::pi(precision + int_bits + 64).mul_pow2(1);
+        let turns = trunc_to_i64(&self.div_to_limbs(&two_pi, limbs_for(int_bits + 8)));
+        let y = self - BigNum::from(turns) * two_pi;
+
+        //evaluate at y / 2^halvings and use the double angle formulas to get back
+        let halvings = (precision as f64).sqrt() as usize / 2 + 3;
+        let bits = precision + 2 * halvings + 64;
+        let n = limbs_for(bits);
+        let y = y.mul_pow2(-(halvings as i64));
+        let mut sin = BigNum::new();
+        let mut cos = BigNum::from(1);
+        let mut term = BigNum::from(1);
+        for j in 1u64.. {
+            term = (&term * &y)
+                .round_to_limbs(n)
+                .div_to_limbs(&BigNum::from(j), n);
+            if negligible(&term, bits) {
+                break;
+            }
+            match j % 4 {
+                0 => cos = cos + &term,
+                1 => sin = sin + &term,
+                2 => cos = cos - &term,
+                _ => sin = sin - &term,
+            }
+            sin = sin.round_to_limbs(n + 1);
+            cos = cos.round_to_limbs(n + 1);
+        }
+        for _ in 0..halvings {
+            let next_sin = (&sin * &cos).mul_pow2(1).round_to_limbs(n);
+            cos = (&cos * &cos - &sin * &sin).round_to_limbs(n);
+            sin = next_sin;
+        }
+        (
+            sin.round_to_limbs(limbs_for(precision)),
+            cos.round_to_limbs(limbs_for(precision)),
+        )
+    }
+
+    pub fn sin(&self, precision: usize) -> BigNum {
+        self.sin_cos(precision).0
+    }
+
+    pub fn cos(&self, precision: usize) -> BigNum {
+        self.sin_cos(precision).1
+    }
+
+    pub fn atan(&self, precision: usize) -> BigNum {
+        if self.is_zero() {
+            return BigNum::new();
+        }
+        let bits = precision + 64;
+        let n = limbs_for(bits);
+        let one = BigNum::from(1);
+        //atan(x) = pi / 2 - atan(1 / x) for x > 1, and the mirror image below -1
+        if self > &one || self < &-&one {
+            let half_pi = BigNum::pi(bits).mul_pow2(-1);
+            let inner = one.div_to_limbs(self, n).atan(bits);
+            let result = if self > &one {
+                half_pi - inner
+            } else {
+                -half_pi - inner
+            };
+            return result.round_to_limbs(limbs_for(precision));
+        }
+
+        //atan(x) = 2 atan(x / (1 + sqrt(1 + x^2))), applied until the series converges quickly
+        let halvings = (precision as f64).sqrt() as usize / 2 + 2;
+        let bits = bits + halvings;
+        let n = limbs_for(bits);
+        let mut x = self.clone();
+        for _ in 0..halvings {
+            let root = (&one + &x * &x).sqrt_to_limbs(n);
+            x = x.div_to_limbs(&(&one + root), n);
+        }
+        let x2 = (&x * &x).round_to_limbs(n);
+        let mut power = x.clone();
+        let mut sum = x;
+        for k in 1u64.. {
+            power = (&power * &x2).round_to_limbs(n);
+            let term = power.div_to_limbs(&BigNum::from(2 * k + 1), n);
+            if negligible(&term, bits) {
+                break;
+            }
+            if k % 2 == 1 {
+                sum = (sum - term).round_to_limbs(n + 1);
+            } else {
+                sum = (sum + term).round_to_limbs(n + 1);
+            }
+        }
+        sum.mul_pow2(halvings as i64)
+            .round_to_limbs(limbs_for(precision))
+    }
+
+    //the angle of the point (x, self) in (-pi, pi], zero at the origin
+    pub fn atan2(&self, x: &BigNum, precision: usize) -> BigNum {
+        let zero = BigNum::from(0);
+        let bits = precision + 64;
+        if x.is_zero() {
+            let half_pi = BigNum::pi(precision).mul_pow2(-1);
+            return match self.cmp(&zero) {
+                std::cmp::Ordering::Greater => half_pi,
+                std::cmp::Ordering::Less => -half_pi,
+                std::cmp::Ordering::Equal => zero,
+            };
+        }
+        let angle = self.div_to_limbs(x, limbs_for(bits)).atan(bits);
+        let result = if x > &zero {
+            angle
+        } else if self < &zero {
+            angle - BigNum::pi(bits)
+        } else {
+            angle + BigNum::pi(bits)
+        };
+        result.round_to_limbs(limbs_for(precision))
+    }
+}
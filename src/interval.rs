@@ -0,0 +1,401 @@
+use std::fmt::{self, Display};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::big_num::BigNum;
+use crate::functions::{limbs_for, DomainError};
+use crate::limbs;
+
+//a closed interval [lo, hi] that is guaranteed to contain the true value
+//add, sub and mul are exact on BigNum so they stay exact here, everything that has to round
+//(division, square roots, powers) rounds lo down and hi up so the result still encloses every
+//possible value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigInterval {
+    lo: BigNum,
+    hi: BigNum,
+}
+
+//bounds for the true value of q, which was rounded toward zero while keeping at least n limbs
+//and so is short by less than one unit in its n-th limb
+fn enclose_truncated(q: BigNum, n: usize) -> (BigNum, BigNum) {
+    match q.log2_floor() {
+        None => (q.clone(), q),
+        Some(e) => {
+            let ulp = BigNum::from(1).mul_pow2(e - 64 * (n as i64 - 1));
+            if q < BigNum::from(0) {
+                (&q - ulp, q)
+            } else {
+                (q.clone(), q + ulp)
+            }
+        }
+    }
+}
+
+//bounds for a / b with b != 0
+fn div_bounds(a: &BigNum, b: &BigNum, n: usize) -> (BigNum, BigNum) {
+    let q = a.div_to_limbs(b, n);
+    if &q * b == *a {
+        return (q.clone(), q);
+    }
+    enclose_truncated(q, n)
+}
+
+//bounds for sqrt(x) with x >= 0
+fn sqrt_bounds(x: &BigNum, n: usize) -> (BigNum, BigNum) {
+    let q = x.sqrt_to_limbs(n);
+    if &q * &q == *x {
+        return (q.clone(), q);
+    }
+    enclose_truncated(q, n)
+}
+
+//x^n exactly, n given as limbs
+fn pow_exact(x: &BigNum, n: &[u64]) -> BigNum {
+    let mut result = BigNum::from(1);
+    for i in (0..limbs::bit_len(n)).rev() {
+        result = &result * &result;
+        if limbs::bit(n, i) {
+            result = result * x;
+        }
+    }
+    result
+}
+
+fn min_max(values: Vec<BigNum>) -> (BigNum, BigNum) {
+    let lo = values.iter().min().unwrap().clone();
+    let hi = values.into_iter().max().unwrap();
+    (lo, hi)
+}
+
+impl BigInterval {
+    pub fn new(lo: BigNum, hi: BigNum) -> BigInterval {
+        assert!(lo <= hi, "interval bounds are out of order");
+        BigInterval { lo, hi }
+    }
+
+    //the interval holding exactly one value
+    pub fn point(value: BigNum) -> BigInterval {
+        BigInterval {
+            lo: value.clone(),
+            hi: value,
+        }
+    }
+
+    pub fn lo(&self) -> &BigNum {
+        &self.lo
+    }
+
+    pub fn hi(&self) -> &BigNum {
+        &self.hi
+    }
+
+    pub fn width(&self) -> BigNum {
+        &self.hi - &self.lo
+    }
+
+    pub fn midpoint(&self) -> BigNum {
+        (&self.lo + &self.hi).mul_pow2(-1)
+    }
+
+    pub fn contains(&self, value: &BigNum) -> bool {
+        &self.lo <= value && value <= &self.hi
+    }
+
+    //true when every value in other is also in self
+    pub fn encloses(&self, other: &BigInterval) -> bool {
+        self.lo <= other.lo && other.hi <= self.hi
+    }
+
+    pub fn intersect(&self, other: &BigInterval) -> Option<BigInterval> {
+        let lo = (&self.lo).max(&other.lo);
+        let hi = (&self.hi).min(&other.hi);
+        (lo <= hi).then(|| BigInterval::new(lo.clone(), hi.clone()))
+    }
+
+    //self / rhs rounded outward to at least `precision` bits, None when rhs contains zero
+    pub fn checked_div(&self, rhs: &BigInterval, precision: usize) -> Option<BigInterval> {
+        if rhs.contains(&BigNum::from(0)) {
+            return None;
+        }
+        let n = limbs_for(precision);
+        let mut lower = Vec::with_capacity(4);
+        let mut upper = Vec::with_capacity(4);
+        for a in [&self.lo, &self.hi] {
+            for b in [&rhs.lo, &rhs.hi] {
+                let (down, up) = div_bounds(a, b, n);
+                lower.push(down);
+                upper.push(up);
+            }
+        }
+        Some(BigInterval::new(min_max(lower).0, min_max(upper).1))
+    }
+
+    //the square roots of the non negative part of self, rounded outward
+    pub fn sqrt(&self, precision: usize) -> Result<BigInterval, DomainError> {
+        if self.hi < BigNum::from(0) {
+            return Err(DomainError::new("square root of a negative interval"));
+        }
+        let n = limbs_for(precision);
+        let lo = if self.lo > BigNum::from(0) {
+            sqrt_bounds(&self.lo, n).0
+        } else {
+            BigNum::new()
+        };
+        Ok(BigInterval::new(lo, sqrt_bounds(&self.hi, n).1))
+    }
+
+    //self^exp rounded outward
+    //integer exponents work for any interval (negative ones as long as it does not contain zero),
+    //other exponents need self >= 0 and go through exp(exp * ln(x)), whose relative error is
+    //below (|exp ln x| + 2) 2^-bits at the working precision, and the bounds are widened by that
+    pub fn pow(&self, exp: &BigNum, precision: usize) -> Result<BigInterval, DomainError> {
+        if exp.is_zero() {
+            return Ok(BigInterval::point(BigNum::from(1)));
+        }
+        if exp.is_integer() {
+            //x^n is monotonic for odd n and for even n on either side of zero, so the
+            //endpoints decide the result and it stays exact
+            let n = exp.magnitude();
+            let lo = pow_exact(&self.lo, &n);
+            let hi = pow_exact(&self.hi, &n);
+            let result = if n.first().unwrap_or(&0) & 1 == 1 {
+                BigInterval::new(lo, hi)
+            } else if self.contains(&BigNum::from(0)) {
+                BigInterval::new(BigNum::new(), lo.max(hi))
+            } else {
+                let (lo, hi) = min_max(vec![lo, hi]);
+                BigInterval::new(lo, hi)
+            };
+            if exp < &BigNum::from(0) {
+                return BigInterval::point(BigNum::from(1))
+                    .checked_div(&result, precision)
+                    .ok_or_else(|| {
+                        DomainError::new("negative power of an interval containing zero")
+                    });
+            }
+            return Ok(result);
+        }
+
+        if self.lo < BigNum::from(0) {
+            return Err(DomainError::new(
+                "fractional power of an interval with negative values",
+            ));
+        }
+        let bits = precision + 64;
+        let bound = |x: &BigNum| -> (BigNum, BigNum) {
+            if x.is_zero() {
+                return (BigNum::new(), BigNum::new());
+            }
+            let log = exp * x.ln(bits).unwrap();
+            let value = log.exp(bits);
+            let slack_bits = log.log2_floor().unwrap_or(0).max(1) + 3;
+            let slack = value.clone().mul_pow2(slack_bits - bits as i64);
+            (&value - &slack, value + slack)
+        };
+        let lo = bound(&self.lo);
+        let hi = bound(&self.hi);
+        //x^y rises with x for y > 0 and falls for y < 0
+        if exp > &BigNum::from(0) {
+            Ok(BigInterval::new(lo.0, hi.1))
+        } else {
+            if self.lo.is_zero() {
+                return Err(DomainError::new(
+                    "negative power of an interval containing zero",
+                ));
+            }
+            Ok(BigInterval::new(hi.0, lo.1))
+        }
+    }
+}
+
+impl From<BigNum> for BigInterval {
+    fn from(value: BigNum) -> Self {
+        BigInterval::point(value)
+    }
+}
+
+impl Add for BigInterval {
+    type Output = BigInterval;
+    fn add(self, rhs: Self) -> Self::Output {
+        BigInterval::new(self.lo + rhs.lo, self.hi + rhs.hi)
+    }
+}
+
+impl Add for &BigInterval {
+    type Output = BigInterval;
+    fn add(self, rhs: Self) -> Self::Output {
+        BigInterval::new(&self.lo + &rhs.lo, &self.hi + &rhs.hi)
+    }
+}
+
+impl Add<&BigInterval> for BigInterval {
+    type Output = BigInterval;
+    fn add(self, rhs: &BigInterval) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<BigInterval> for &BigInterval {
+    type Output = BigInterval;
+    fn add(self, rhs: BigInterval) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for BigInterval {
+    type Output = BigInterval;
+    fn sub(self, rhs: Self) -> Self::Output {
+        BigInterval::new(self.lo - rhs.hi, self.hi - rhs.lo)
+    }
+}
+
+impl Sub for &BigInterval {
+    type Output = BigInterval;
+    fn sub(self, rhs: Self) -> Self::Output {
+        BigInterval::new(&self.lo - &rhs.hi, &self.hi - &rhs.lo)
+    }
+}
+
+impl Sub<&BigInterval> for BigInterval {
+    type Output = BigInterval;
+    fn sub(self, rhs: &BigInterval) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<BigInterval> for &BigInterval {
+    type Output = BigInterval;
+    fn sub(self, rhs: BigInterval) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl Mul for BigInterval {
+    type Output = BigInterval;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul for &BigInterval {
+    type Output = BigInterval;
+    fn mul(self, rhs: Self) -> Self::Output {
+        let (lo, hi) = min_max(vec![
+            &self.lo * &rhs.lo,
+            &self.lo * &rhs.hi,
+            &self.hi * &rhs.lo,
+            &self.hi * &rhs.hi,
+        ]);
+        BigInterval::new(lo, hi)
+    }
+}
+
+impl Mul<&BigInterval> for BigInterval {
+    type Output = BigInterval;
+    fn mul(self, rhs: &BigInterval) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<BigInterval> for &BigInterval {
+    type Output = BigInterval;
+    fn mul(self, rhs: BigInterval) -> Self::Output {
+        self * &rhs
+    }
+}
+
+//like Div on BigNum the precision defaults to the limbs of both operands together
+impl Div for &BigInterval {
+    type Output = BigInterval;
+    fn div(self, rhs: Self) -> Self::Output {
+        let limb_count = self.lo.get_precision().max(self.hi.get_precision())
+            + rhs.lo.get_precision().max(rhs.hi.get_precision());
+        self.checked_div(rhs, 64 * limb_count)
+            .expect("Divide by an interval containing zero")
+    }
+}
+
+impl Div for BigInterval {
+    type Output = BigInterval;
+    fn div(self, rhs: Self) -> Self::Output {
+        &self / &rhs
+    }
+}
+
+impl Div<&BigInterval> for BigInterval {
+    type Output = BigInterval;
+    fn div(self, rhs: &BigInterval) -> Self::Output {
+        &self / rhs
+    }
+}
+
+impl Div<BigInterval> for &BigInterval {
+    type Output = BigInterval;
+    fn div(self, rhs: BigInterval) -> Self::Output {
+        self / &rhs
+    }
+}
+
+impl Neg for BigInterval {
+    type Output = BigInterval;
+    fn neg(self) -> Self::Output {
+        BigInterval {
+            lo: -self.hi,
+            hi: -self.lo,
+        }
+    }
+}
+
+impl Neg for &BigInterval {
+    type Output = BigInterval;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+//x with as many digits after the point as BigNum prints, rounded up or down instead of
+//truncated so the printed interval still encloses the real one
+fn format_bound(x: &BigNum, up: bool) -> String {
+    let mut x = x.clone();
+    x.compact();
+    let digits = (x.get_precision() as f64 * 64.0 / 10f64.log2()).floor() as usize;
+    let negative = x < BigNum::from(0);
+    let scaled = x * BigNum::from(10).pow(BigNum::from(digits as u64));
+    let exact = scaled.is_integer();
+    let mut units = scaled.magnitude();
+    //the magnitude was truncated, away from zero is up for positive bounds and down for
+    //negative ones
+    if !exact && up != negative {
+        units = limbs::add(&units, &[1]);
+    }
+    let mut result = String::new();
+    if negative && !units.is_empty() {
+        result.push('-');
+    }
+    let units = BigNum::from_magnitude(false, units).to_string();
+    let units = format!("{:0>width$}", units, width = digits + 1);
+    let (integer, fraction) = units.split_at(units.len() - digits);
+    result.push_str(integer);
+    //exact values print their shortest form like BigNum does
+    let fraction = if exact {
+        fraction.trim_end_matches('0')
+    } else {
+        fraction
+    };
+    if !fraction.is_empty() {
+        result.push('.');
+        result.push_str(fraction);
+    }
+    result
+}
+
+impl Display for BigInterval {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "[{}, {}]",
+            format_bound(&self.lo, false),
+            format_bound(&self.hi, true)
+        )
+    }
+}
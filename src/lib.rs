@@ -1,9 +1,54 @@
 mod big_num;
+mod limbs;
+mod parts;
 
 mod tests;
 
+pub mod approx;
+pub mod bytes;
+pub mod cbor;
 pub mod codec;
-pub use big_num::BigNum;
+pub mod combinatorics;
+pub mod complex;
+pub mod decimal;
+pub mod exact_real;
+pub mod factor;
+pub mod float;
+pub mod functions;
+pub mod interval;
+pub mod matrix;
+pub mod modular;
+pub mod money;
+pub mod montgomery;
+pub mod polynomial;
+pub mod random;
+pub mod rational;
+pub mod secret;
+pub mod sequences;
+pub mod special;
+pub mod wire;
+pub use approx::BigApprox;
+pub use big_num::{BigNum, ExponentError};
+pub use bytes::BytesError;
+pub use cbor::CborError;
 pub use codec::encode;
 pub use codec::parse;
 pub use codec::Base;
+pub use combinatorics::CombinatoricsError;
+pub use complex::BigComplex;
+pub use decimal::{BigDecimal, ParseDecimalError, RoundingMode};
+pub use exact_real::ExactReal;
+pub use factor::{FactorError, FactorOptions};
+pub use float::BigFloat;
+pub use functions::DomainError;
+pub use interval::BigInterval;
+pub use matrix::{BigMatrix, MatrixError, Scalar};
+pub use modular::ModularError;
+pub use money::{Currency, Money, MoneyError};
+pub use montgomery::{ModInt, MontgomeryContext};
+pub use polynomial::Polynomial;
+pub use random::{RandomBits, RandomFraction, UniformBelow, UniformBigNum};
+pub use rational::BigRational;
+pub use secret::{Choice, SecretBigNum};
+pub use sequences::LinearRecurrence;
+pub use wire::WireError;
@@ -0,0 +1,373 @@
+use std::cmp::Ordering;
+
+//helpers that work directly on magnitudes stored as u64 limbs, least significant limb first
+//none of these look at a sign or an exponent, the callers in big_num.rs take care of that
+
+pub(crate) fn trim(a: &mut Vec<u64>) {
+    while a.last() == Some(&0) {
+        a.pop();
+    }
+}
+
+fn significant_len(a: &[u64]) -> usize {
+    let mut len = a.len();
+    while len > 0 && a[len - 1] == 0 {
+        len -= 1;
+    }
+    len
+}
+
+pub(crate) fn is_one(a: &[u64]) -> bool {
+    significant_len(a) == 1 && a[0] == 1
+}
+
+pub(crate) fn cmp(a: &[u64], b: &[u64]) -> Ordering {
+    let a = &a[..significant_len(a)];
+    let b = &b[..significant_len(b)];
+    if a.len() != b.len() {
+        return a.len().cmp(&b.len());
+    }
+    for i in (0..a.len()).rev() {
+        match a[i].cmp(&b[i]) {
+            Ordering::Equal => continue,
+            ordering => return ordering,
+        }
+    }
+    Ordering::Equal
+}
+
+pub(crate) fn bit_len(a: &[u64]) -> usize {
+    let len = significant_len(a);
+    if len == 0 {
+        return 0;
+    }
+    len * 64 - a[len - 1].leading_zeros() as usize
+}
+
+pub(crate) fn bit(a: &[u64], i: usize) -> bool {
+    let limb = i / 64;
+    limb < a.len() && (a[limb] >> (i % 64)) & 1 == 1
+}
+
+pub(crate) fn trailing_zeros(a: &[u64]) -> usize {
+    for (i, &x) in a.iter().enumerate() {
+        if x != 0 {
+            return i * 64 + x.trailing_zeros() as usize;
+        }
+    }
+    0
+}
+
+pub(crate) fn add(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let (long, short) = if a.len() >= b.len() { (a, b) } else { (b, a) };
+    let mut result = Vec::with_capacity(long.len() + 1);
+    let mut carry = false;
+    for (i, &x) in long.iter().enumerate() {
+        let (sum, carry1) = x.overflowing_add(*short.get(i).unwrap_or(&0));
+        let (sum, carry2) = sum.overflowing_add(carry as u64);
+        result.push(sum);
+        carry = carry1 || carry2;
+    }
+    if carry {
+        result.push(1);
+    }
+    trim(&mut result);
+    result
+}
+
+//a - b, a must be at least as large as b
+pub(crate) fn sub(a: &[u64], b: &[u64]) -> Vec<u64> {
+    debug_assert!(cmp(a, b) != Ordering::Less);
+    let mut result = Vec::with_capacity(a.len());
+    let mut borrow = false;
+    for (i, &x) in a.iter().enumerate() {
+        let (diff, borrow1) = x.overflowing_sub(*b.get(i).unwrap_or(&0));
+        let (diff, borrow2) = diff.overflowing_sub(borrow as u64);
+        result.push(diff);
+        borrow = borrow1 || borrow2;
+    }
+    debug_assert!(!borrow);
+    trim(&mut result);
+    result
+}
+
+pub(crate) fn mul(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let a = &a[..significant_len(a)];
+    let b = &b[..significant_len(b)];
+    if a.is_empty() || b.is_empty() {
+        return Vec::new();
+    }
+    let mut result = vec![0u64; a.len() + b.len()];
+    mul_into(a, b, &mut result);
+    trim(&mut result);
+    result
+}
+
+//a * b written over out, which must be exactly a.len() + b.len() zero limbs
+pub(crate) fn mul_into(a: &[u64], b: &[u64], out: &mut [u64]) {
+    debug_assert!(out.len() == a.len() + b.len());
+    for i in 0..a.len() {
+        let mut carry: u128 = 0;
+        for j in 0..b.len() {
+            let t = (a[i] as u128) * (b[j] as u128) + out[i + j] as u128 + carry;
+            out[i + j] = t as u64;
+            carry = t >> 64;
+        }
+        out[i + b.len()] = carry as u64;
+    }
+}
+
+pub(crate) fn mul_small(a: &[u64], b: u64) -> Vec<u64> {
+    let mut result = Vec::with_capacity(a.len() + 1);
+    let mut carry: u128 = 0;
+    for &x in a {
+        let t = (x as u128) * (b as u128) + carry;
+        result.push(t as u64);
+        carry = t >> 64;
+    }
+    result.push(carry as u64);
+    trim(&mut result);
+    result
+}
+
+pub(crate) fn div_rem_small(a: &[u64], b: u64) -> (Vec<u64>, u64) {
+    assert!(b != 0, "Divide by zero");
+    let mut quotient = vec![0u64; a.len()];
+    let mut rem: u128 = 0;
+    for i in (0..a.len()).rev() {
+        let cur = (rem << 64) | a[i] as u128;
+        quotient[i] = (cur / b as u128) as u64;
+        rem = cur % b as u128;
+    }
+    trim(&mut quotient);
+    (quotient, rem as u64)
+}
+
+pub(crate) fn shl(a: &[u64], bits: usize) -> Vec<u64> {
+    let limbs = bits / 64;
+    let shift = bits % 64;
+    let mut result = vec![0u64; limbs];
+    if shift == 0 {
+        result.extend_from_slice(a);
+    } else {
+        let mut carry = 0;
+        for &x in a {
+            result.push((x << shift) | carry);
+            carry = x >> (64 - shift);
+        }
+        result.push(carry);
+    }
+    trim(&mut result);
+    result
+}
+
+pub(crate) fn shr(a: &[u64], bits: usize) -> Vec<u64> {
+    let limbs = bits / 64;
+    let shift = bits % 64;
+    if limbs >= a.len() {
+        return Vec::new();
+    }
+    let a = &a[limbs..];
+    let mut result = Vec::with_capacity(a.len());
+    if shift == 0 {
+        result.extend_from_slice(a);
+    } else {
+        for i in 0..a.len() {
+            let high = a.get(i + 1).map_or(0, |&x| x << (64 - shift));
+            result.push((a[i] >> shift) | high);
+        }
+    }
+    trim(&mut result);
+    result
+}
+
+//schoolbook long division (Knuth, TAOCP vol. 2, algorithm D)
+pub(crate) fn div_rem(a: &[u64], b: &[u64]) -> (Vec<u64>, Vec<u64>) {
+    let a = &a[..significant_len(a)];
+    let b = &b[..significant_len(b)];
+    assert!(!b.is_empty(), "Divide by zero");
+    if cmp(a, b) == Ordering::Less {
+        return (Vec::new(), a.to_vec());
+    }
+    if b.len() == 1 {
+        let (quotient, rem) = div_rem_small(a, b[0]);
+        let rem = if rem == 0 { Vec::new() } else { vec![rem] };
+        return (quotient, rem);
+    }
+
+    let mut u = Vec::with_capacity(a.len() + 1);
+    u.extend_from_slice(a);
+    u.push(0);
+    div_rem_in_place(&mut u, b);
+    let mut quotient = u.split_off(b.len());
+    trim(&mut quotient);
+    trim(&mut u);
+    (quotient, u)
+}
+
+//algorithm D without any buffers of its own: u is the dividend with a spare zero limb on top,
+//b a divisor with at least two limbs and a non zero top one
+//afterwards the low b.len() limbs of u hold the remainder and the limbs above them the quotient,
+//each quotient limb taking the place of the top of the window it came from, which is zero by then
+pub(crate) fn div_rem_in_place(u: &mut [u64], b: &[u64]) {
+    let n = b.len();
+    debug_assert!(n >= 2 && b[n - 1] != 0 && u.len() > n && u[u.len() - 1] == 0);
+    //normalize so the top bit of the divisor is set, this keeps the quotient estimate within 2 of the real digit
+    let shift = b[n - 1].leading_zeros();
+    let v = |i: usize| match (shift, i) {
+        (0, _) => b[i],
+        (_, 0) => b[0] << shift,
+        _ => (b[i] << shift) | (b[i - 1] >> (64 - shift)),
+    };
+    if shift > 0 {
+        for i in (1..u.len()).rev() {
+            u[i] = (u[i] << shift) | (u[i - 1] >> (64 - shift));
+        }
+        u[0] <<= shift;
+    }
+
+    let m = u.len() - 1 - n;
+    let v_top = v(n - 1) as u128;
+    let v_next = v(n - 2) as u128;
+
+    for j in (0..=m).rev() {
+        let num = ((u[j + n] as u128) << 64) | u[j + n - 1] as u128;
+        let mut q_hat = num / v_top;
+        let mut r_hat = num % v_top;
+        while q_hat > u64::MAX as u128 || q_hat * v_next > ((r_hat << 64) | u[j + n - 2] as u128) {
+            q_hat -= 1;
+            r_hat += v_top;
+            if r_hat > u64::MAX as u128 {
+                break;
+            }
+        }
+
+        //subtract q_hat * v from the current window of u
+        let mut carry: u128 = 0;
+        let mut borrow = false;
+        for i in 0..n {
+            let prod = q_hat * v(i) as u128 + carry;
+            carry = prod >> 64;
+            let (diff, borrow1) = u[i + j].overflowing_sub(prod as u64);
+            let (diff, borrow2) = diff.overflowing_sub(borrow as u64);
+            u[i + j] = diff;
+            borrow = borrow1 || borrow2;
+        }
+        let (diff, borrow1) = u[j + n].overflowing_sub(carry as u64);
+        let (diff, borrow2) = diff.overflowing_sub(borrow as u64);
+        u[j + n] = diff;
+
+        //the estimate was one too large, add the divisor back
+        if borrow1 || borrow2 {
+            q_hat -= 1;
+            let mut carry = false;
+            for i in 0..n {
+                let (sum, carry1) = u[i + j].overflowing_add(v(i));
+                let (sum, carry2) = sum.overflowing_add(carry as u64);
+                u[i + j] = sum;
+                carry = carry1 || carry2;
+            }
+        }
+        u[j + n] = q_hat as u64;
+    }
+
+    if shift > 0 {
+        for i in 0..n - 1 {
+            u[i] = (u[i] >> shift) | (u[i + 1] << (64 - shift));
+        }
+        u[n - 1] >>= shift;
+    }
+}
+
+pub(crate) fn rem(a: &[u64], b: &[u64]) -> Vec<u64> {
+    div_rem(a, b).1
+}
+
+pub(crate) fn isqrt(a: &[u64]) -> Vec<u64> {
+    if bit_len(a) == 0 {
+        return Vec::new();
+    }
+    //newton's method from above, starting at a power of two larger than the root
+    let mut x = shl(&[1], bit_len(a) / 2 + 1);
+    loop {
+        let y = shr(&add(&x, &div_rem(a, &x).0), 1);
+        if cmp(&y, &x) != Ordering::Less {
+            return x;
+        }
+        x = y;
+    }
+}
+
+pub(crate) fn gcd(a: &[u64], b: &[u64]) -> Vec<u64> {
+    let mut a = a.to_vec();
+    let mut b = b.to_vec();
+    trim(&mut a);
+    trim(&mut b);
+    while !b.is_empty() {
+        let r = rem(&a, &b);
+        a = b;
+        b = r;
+    }
+    a
+}
+
+//(a + b) mod n for a, b already reduced mod n
+pub(crate) fn add_mod(a: &[u64], b: &[u64], n: &[u64]) -> Vec<u64> {
+    let sum = add(a, b);
+    if cmp(&sum, n) == Ordering::Less {
+        sum
+    } else {
+        sub(&sum, n)
+    }
+}
+
+//(a - b) mod n for a, b already reduced mod n
+pub(crate) fn sub_mod(a: &[u64], b: &[u64], n: &[u64]) -> Vec<u64> {
+    if cmp(a, b) == Ordering::Less {
+        sub(&add(a, n), b)
+    } else {
+        sub(a, b)
+    }
+}
+
+pub(crate) fn mul_mod(a: &[u64], b: &[u64], n: &[u64]) -> Vec<u64> {
+    rem(&mul(a, b), n)
+}
+
+pub(crate) fn pow_mod(base: &[u64], exp: &[u64], n: &[u64]) -> Vec<u64> {
+    if is_one(n) {
+        return Vec::new();
+    }
+    let base = rem(base, n);
+    let mut result = vec![1];
+    for i in (0..bit_len(exp)).rev() {
+        result = mul_mod(&result, &result, n);
+        if bit(exp, i) {
+            result = mul_mod(&result, &base, n);
+        }
+    }
+    result
+}
+
+//inverse of a mod n, or the non trivial gcd of a and n if there is no inverse
+pub(crate) fn inv_mod(a: &[u64], n: &[u64]) -> Result<Vec<u64>, Vec<u64>> {
+    //extended euclid, keeping the coefficient of a reduced mod n so everything stays unsigned
+    let mut r0 = n.to_vec();
+    let mut r1 = rem(a, n);
+    let mut t0: Vec<u64> = Vec::new();
+    let mut t1: Vec<u64> = vec![1];
+    trim(&mut r0);
+    while !r1.is_empty() {
+        let (q, r) = div_rem(&r0, &r1);
+        let t = sub_mod(&t0, &mul_mod(&q, &t1, n), n);
+        r0 = r1;
+        r1 = r;
+        t0 = t1;
+        t1 = t;
+    }
+    if is_one(&r0) {
+        Ok(rem(&t0, n))
+    } else {
+        Err(r0)
+    }
+}
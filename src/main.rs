@@ -1,6 +1,6 @@
 use std::io::Write;
 
-use big_num::{codec, BigNum};
+use big_num::{codec, BigComplex, BigNum};
 
 mod ast;
 
@@ -34,30 +34,106 @@ fn convert() {
 }
 
 use lalrpop_util::lalrpop_mod;
-lalrpop_mod!(pub calculator);
+lalrpop_mod!(#[allow(clippy::empty_line_after_outer_attr)] pub calculator);
 
 fn parse(input: &str) -> AstNode {
     let ast = calculator::ExprParser::new().parse(input).unwrap();
     ast
 }
 
-fn eval(input: &AstNode) -> BigNum {
-    match input {
-        AstNode::Literal(x) => x.clone(),
-        AstNode::Add(x, y) => eval(x) + eval(y),
-        AstNode::Sub(x, y) => eval(x) - eval(y),
-        AstNode::Mul(x, y) => eval(x) * eval(y),
-        AstNode::Div(x, y) => eval(x) / eval(y),
-        AstNode::Pow(x, y) => eval(x).pow(eval(y)),
-        AstNode::Mod(x, y) => eval(x) % eval(y),
+//bits used for complex division and powers
+const COMPLEX_PRECISION: usize = 256;
+
+//real results go through the BigNum operators as before, anything involving i through BigComplex
+fn eval(input: &AstNode) -> Result<BigComplex, String> {
+    Ok(match input {
+        AstNode::Literal(x) => BigComplex::from(x.clone()),
+        AstNode::ImaginaryUnit => BigComplex::i(),
+        AstNode::Add(x, y) => eval(x)? + eval(y)?,
+        AstNode::Sub(x, y) => eval(x)? - eval(y)?,
+        AstNode::Mul(x, y) => eval(x)? * eval(y)?,
+        AstNode::Div(x, y) => match (eval(x)?, eval(y)?) {
+            (x, y) if x.is_real() && y.is_real() => BigComplex::from(x.re / y.re),
+            (_, y) if y.is_zero() => return Err("division by zero".to_string()),
+            (x, y) => x.div_to(&y, COMPLEX_PRECISION),
+        },
+        AstNode::Pow(x, y) => match (eval(x)?, eval(y)?) {
+            (x, y)
+                if x.is_real() && y.is_real() && (y.re.is_integer() || x.re >= BigNum::new()) =>
+            {
+                BigComplex::from(x.re.pow(y.re))
+            }
+            (x, y) => x.pow(&y, COMPLEX_PRECISION).map_err(|e| e.to_string())?,
+        },
+        AstNode::Mod(x, y) => BigComplex::from(real(eval(x)?)? % real(eval(y)?)?),
+        AstNode::Call(name, args) => {
+            let args = args
+                .iter()
+                .map(|arg| real(eval(arg)?))
+                .collect::<Result<Vec<_>, _>>()?;
+            BigComplex::from(call(name, &args)?)
+        }
+    })
+}
+
+fn real(value: BigComplex) -> Result<BigNum, String> {
+    if value.is_real() {
+        Ok(value.re)
+    } else {
+        Err(format!("{} is not a real number", value))
     }
 }
 
+fn call(name: &str, args: &[BigNum]) -> Result<BigNum, String> {
+    let result = match (name, args) {
+        ("factorial", [n]) => n.factorial(),
+        ("double_factorial", [n]) => n.double_factorial(),
+        ("binomial", [n, k]) => n.binomial(k),
+        ("multinomial", ks) => BigNum::multinomial(ks),
+        ("catalan", [n]) => n.catalan(),
+        ("stirling2", [n, k]) => n.stirling2(k),
+        ("partitions", [n]) => n.partitions(),
+        _ => {
+            return Err(format!(
+                "unknown function `{}` taking {} arguments",
+                name,
+                args.len()
+            ))
+        }
+    };
+    result.map_err(|e| e.to_string())
+}
+
 fn evaluate() {
     let input = input("Enter an expression: ");
     let ast = parse(&input);
-    let res = eval(&ast);
-    println!("result: {}", res);
+    match eval(&ast) {
+        Ok(res) if res.is_real() => println!("result: {}", res.re),
+        Ok(res) => println!("result: {}", res),
+        Err(e) => println!("error: {}", e),
+    }
+}
+
+fn factor() {
+    let input_string = input("Enter an integer: ");
+    let n = codec::parse(input_string, codec::Base::Decimal);
+
+    match n.factorize() {
+        Ok(factors) => {
+            let factors: Vec<String> = factors
+                .iter()
+                .map(|(p, k)| {
+                    if *k == 1 {
+                        format!("{}", p)
+                    } else {
+                        format!("{}^{}", p, k)
+                    }
+                })
+                .collect();
+            println!("result: {}", factors.join(" * "));
+        }
+        Err(e) => println!("error: {}", e),
+    }
 }
 
 fn main() {
@@ -65,12 +141,14 @@ fn main() {
     println!("options:");
     println!("(1) Convert a number from one base to another");
     println!("(2) Evaluate a mathematical expression");
+    println!("(3) Factor an integer");
 
     loop {
-        let option = input("What do you want to do? (1, 2) ");
+        let option = input("What do you want to do? (1, 2, 3) ");
         match option.as_str() {
             "1" => convert(),
             "2" => evaluate(),
+            "3" => factor(),
             _ => println!("Invalid option"),
         }
     }
@@ -0,0 +1,467 @@
+use std::{
+    error::Error,
+    fmt::{self, Debug, Display},
+    ops::{Add, Index, IndexMut, Mul, Neg, Sub},
+};
+
+use crate::big_num::BigNum;
+use crate::rational::BigRational;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MatrixError {
+    DimensionMismatch,
+    NotSquare,
+    Singular,
+    //the system has no solution at all
+    Inconsistent,
+}
+
+impl Display for MatrixError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MatrixError::DimensionMismatch => write!(f, "matrix dimensions do not match"),
+            MatrixError::NotSquare => write!(f, "matrix is not square"),
+            MatrixError::Singular => write!(f, "matrix is singular"),
+            MatrixError::Inconsistent => write!(f, "system of equations has no solution"),
+        }
+    }
+}
+
+impl Error for MatrixError {}
+
+//what a matrix entry has to support on top of the arithmetic operators on references
+pub trait Scalar: Clone + Ord + Debug + Display {
+    fn zero() -> Self;
+    fn one() -> Self;
+    fn is_zero(&self) -> bool;
+    //self / rhs, exact for BigRational, for BigNum exact whenever the quotient fits in the
+    //limbs of both operands together and rounded toward zero otherwise
+    fn divide(&self, rhs: &Self) -> Self;
+}
+
+//BigNum division never keeps fewer limbs than this
+const MIN_DIVISION_LIMBS: usize = 4;
+
+impl Scalar for BigNum {
+    fn zero() -> Self {
+        BigNum::new()
+    }
+    fn one() -> Self {
+        BigNum::from(1)
+    }
+    fn is_zero(&self) -> bool {
+        BigNum::is_zero(self)
+    }
+    fn divide(&self, rhs: &Self) -> Self {
+        let limbs = (self.get_precision() + rhs.get_precision()).max(MIN_DIVISION_LIMBS);
+        self.div_to_limbs(rhs, limbs)
+    }
+}
+
+impl Scalar for BigRational {
+    fn zero() -> Self {
+        BigRational::zero()
+    }
+    fn one() -> Self {
+        BigRational::from(1)
+    }
+    fn is_zero(&self) -> bool {
+        BigRational::is_zero(self)
+    }
+    fn divide(&self, rhs: &Self) -> Self {
+        self / rhs
+    }
+}
+
+//a dense matrix stored row by row
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigMatrix<T = BigNum> {
+    rows: usize,
+    cols: usize,
+    data: Vec<T>,
+}
+
+impl<T> BigMatrix<T> {
+    pub fn new(rows: usize, cols: usize, data: Vec<T>) -> BigMatrix<T> {
+        assert_eq!(rows * cols, data.len(), "matrix data has the wrong length");
+        BigMatrix { rows, cols, data }
+    }
+
+    pub fn from_rows(rows: Vec<Vec<T>>) -> BigMatrix<T> {
+        let cols = rows.first().map_or(0, |row| row.len());
+        assert!(
+            rows.iter().all(|row| row.len() == cols),
+            "matrix rows have different lengths"
+        );
+        let row_count = rows.len();
+        BigMatrix::new(row_count, cols, rows.into_iter().flatten().collect())
+    }
+
+    pub fn rows(&self) -> usize {
+        self.rows
+    }
+
+    pub fn cols(&self) -> usize {
+        self.cols
+    }
+
+    pub fn is_square(&self) -> bool {
+        self.rows == self.cols
+    }
+
+    pub fn row(&self, i: usize) -> &[T] {
+        &self.data[i * self.cols..(i + 1) * self.cols]
+    }
+
+    pub fn map<U, F: FnMut(&T) -> U>(&self, f: F) -> BigMatrix<U> {
+        BigMatrix::new(self.rows, self.cols, self.data.iter().map(f).collect())
+    }
+
+    fn swap_rows(&mut self, a: usize, b: usize) {
+        for j in 0..self.cols {
+            self.data.swap(a * self.cols + j, b * self.cols + j);
+        }
+    }
+}
+
+impl<T: Clone> BigMatrix<T> {
+    pub fn transpose(&self) -> BigMatrix<T> {
+        let mut data = Vec::with_capacity(self.data.len());
+        for j in 0..self.cols {
+            for i in 0..self.rows {
+                data.push(self[(i, j)].clone());
+            }
+        }
+        BigMatrix::new(self.cols, self.rows, data)
+    }
+}
+
+impl<T> BigMatrix<T>
+where
+    T: Scalar,
+    for<'a> &'a T: Add<&'a T, Output = T> + Sub<&'a T, Output = T> + Mul<&'a T, Output = T>,
+    for<'a> &'a T: Neg<Output = T>,
+{
+    pub fn zeros(rows: usize, cols: usize) -> BigMatrix<T> {
+        BigMatrix::new(rows, cols, vec![T::zero(); rows * cols])
+    }
+
+    pub fn identity(n: usize) -> BigMatrix<T> {
+        let mut result = Self::zeros(n, n);
+        for i in 0..n {
+            result[(i, i)] = T::one();
+        }
+        result
+    }
+
+    //the row at or below `from` with the largest entry in column col, None if they are all zero
+    //picking the largest keeps rounding small for BigNum and does no harm for exact types
+    fn pivot_row(&self, col: usize, from: usize) -> Option<usize> {
+        let abs = |x: &T| if x < &T::zero() { -x } else { x.clone() };
+        (from..self.rows)
+            .filter(|&i| !self[(i, col)].is_zero())
+            .max_by(|&a, &b| abs(&self[(a, col)]).cmp(&abs(&self[(b, col)])))
+    }
+
+    //bareiss' fraction free elimination, every division in it is exact so integer matrices stay
+    //integers all the way through
+    pub fn determinant(&self) -> Result<T, MatrixError> {
+        if !self.is_square() {
+            return Err(MatrixError::NotSquare);
+        }
+        let n = self.rows;
+        if n == 0 {
+            return Ok(T::one());
+        }
+        let mut m = self.clone();
+        let mut negate = false;
+        let mut previous = T::one();
+        for k in 0..n - 1 {
+            let Some(p) = m.pivot_row(k, k) else {
+                return Ok(T::zero());
+            };
+            if p != k {
+                m.swap_rows(p, k);
+                negate = !negate;
+            }
+            for i in k + 1..n {
+                for j in k + 1..n {
+                    let value = &(&m[(k, k)] * &m[(i, j)]) - &(&m[(i, k)] * &m[(k, j)]);
+                    m[(i, j)] = value.divide(&previous);
+                }
+            }
+            previous = m[(k, k)].clone();
+        }
+        let det = m[(n - 1, n - 1)].clone();
+        Ok(if negate { -&det } else { det })
+    }
+
+    //gauss jordan elimination to reduced row echelon form, also returns the pivot columns
+    pub fn rref(&self) -> (BigMatrix<T>, Vec<usize>) {
+        let mut m = self.clone();
+        let mut pivots = Vec::new();
+        let mut row = 0;
+        for col in 0..self.cols {
+            if row == self.rows {
+                break;
+            }
+            let Some(p) = m.pivot_row(col, row) else {
+                continue;
+            };
+            m.swap_rows(p, row);
+            let pivot = m[(row, col)].clone();
+            for j in col..self.cols {
+                m[(row, j)] = m[(row, j)].divide(&pivot);
+            }
+            for i in 0..self.rows {
+                if i == row || m[(i, col)].is_zero() {
+                    continue;
+                }
+                let factor = m[(i, col)].clone();
+                for j in col..self.cols {
+                    let value = &m[(i, j)] - &(&factor * &m[(row, j)]);
+                    m[(i, j)] = value;
+                }
+            }
+            pivots.push(col);
+            row += 1;
+        }
+        (m, pivots)
+    }
+
+    pub fn rank(&self) -> usize {
+        self.rref().1.len()
+    }
+
+    //[self | rhs] side by side
+    fn augment(&self, rhs: &BigMatrix<T>) -> Result<BigMatrix<T>, MatrixError> {
+        if self.rows != rhs.rows {
+            return Err(MatrixError::DimensionMismatch);
+        }
+        let rows = (0..self.rows)
+            .map(|i| {
+                let mut row = self.row(i).to_vec();
+                row.extend_from_slice(rhs.row(i));
+                row
+            })
+            .collect();
+        Ok(BigMatrix::from_rows(rows))
+    }
+
+    //the X with self X = rhs, as long as there is exactly one
+    pub fn solve_matrix(&self, rhs: &BigMatrix<T>) -> Result<BigMatrix<T>, MatrixError> {
+        let (reduced, pivots) = self.augment(rhs)?.rref();
+        if pivots.last().is_some_and(|&col| col >= self.cols) {
+            return Err(MatrixError::Inconsistent);
+        }
+        if pivots.len() < self.cols {
+            return Err(MatrixError::Singular);
+        }
+        let mut result = Self::zeros(self.cols, rhs.cols);
+        for (i, &col) in pivots.iter().enumerate() {
+            for j in 0..rhs.cols {
+                result[(col, j)] = reduced[(i, self.cols + j)].clone();
+            }
+        }
+        Ok(result)
+    }
+
+    //the x with self x = b
+    pub fn solve(&self, b: &[T]) -> Result<Vec<T>, MatrixError> {
+        let rhs = BigMatrix::new(b.len(), 1, b.to_vec());
+        Ok(self.solve_matrix(&rhs)?.data)
+    }
+
+    pub fn inverse(&self) -> Result<BigMatrix<T>, MatrixError> {
+        if !self.is_square() {
+            return Err(MatrixError::NotSquare);
+        }
+        //a singular matrix makes [self | I] inconsistent as well, either way there is no inverse
+        self.solve_matrix(&Self::identity(self.rows))
+            .map_err(|_| MatrixError::Singular)
+    }
+}
+
+impl<T> Index<(usize, usize)> for BigMatrix<T> {
+    type Output = T;
+    fn index(&self, (i, j): (usize, usize)) -> &Self::Output {
+        assert!(i < self.rows && j < self.cols, "matrix index out of bounds");
+        &self.data[i * self.cols + j]
+    }
+}
+
+impl<T> IndexMut<(usize, usize)> for BigMatrix<T> {
+    fn index_mut(&mut self, (i, j): (usize, usize)) -> &mut Self::Output {
+        assert!(i < self.rows && j < self.cols, "matrix index out of bounds");
+        &mut self.data[i * self.cols + j]
+    }
+}
+
+impl<T> Add for &BigMatrix<T>
+where
+    for<'a> &'a T: Add<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn add(self, rhs: Self) -> Self::Output {
+        assert!(
+            self.rows == rhs.rows && self.cols == rhs.cols,
+            "matrix dimensions do not match"
+        );
+        let data = self
+            .data
+            .iter()
+            .zip(&rhs.data)
+            .map(|(a, b)| a + b)
+            .collect();
+        BigMatrix::new(self.rows, self.cols, data)
+    }
+}
+
+impl<T> Add for BigMatrix<T>
+where
+    for<'a> &'a T: Add<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl<T> Add<&BigMatrix<T>> for BigMatrix<T>
+where
+    for<'a> &'a T: Add<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn add(self, rhs: &BigMatrix<T>) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl<T> Add<BigMatrix<T>> for &BigMatrix<T>
+where
+    for<'a> &'a T: Add<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn add(self, rhs: BigMatrix<T>) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl<T> Sub for &BigMatrix<T>
+where
+    for<'a> &'a T: Sub<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        assert!(
+            self.rows == rhs.rows && self.cols == rhs.cols,
+            "matrix dimensions do not match"
+        );
+        let data = self
+            .data
+            .iter()
+            .zip(&rhs.data)
+            .map(|(a, b)| a - b)
+            .collect();
+        BigMatrix::new(self.rows, self.cols, data)
+    }
+}
+
+impl<T> Sub for BigMatrix<T>
+where
+    for<'a> &'a T: Sub<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl<T> Sub<&BigMatrix<T>> for BigMatrix<T>
+where
+    for<'a> &'a T: Sub<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn sub(self, rhs: &BigMatrix<T>) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl<T> Sub<BigMatrix<T>> for &BigMatrix<T>
+where
+    for<'a> &'a T: Sub<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn sub(self, rhs: BigMatrix<T>) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl<T> Mul for &BigMatrix<T>
+where
+    T: Scalar,
+    for<'a> &'a T: Add<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        assert_eq!(self.cols, rhs.rows, "matrix dimensions do not match");
+        let mut data = Vec::with_capacity(self.rows * rhs.cols);
+        for i in 0..self.rows {
+            for j in 0..rhs.cols {
+                let mut sum = T::zero();
+                for k in 0..self.cols {
+                    sum = &sum + &(&self[(i, k)] * &rhs[(k, j)]);
+                }
+                data.push(sum);
+            }
+        }
+        BigMatrix::new(self.rows, rhs.cols, data)
+    }
+}
+
+impl<T> Mul for BigMatrix<T>
+where
+    T: Scalar,
+    for<'a> &'a T: Add<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl<T> Mul<&BigMatrix<T>> for BigMatrix<T>
+where
+    T: Scalar,
+    for<'a> &'a T: Add<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn mul(self, rhs: &BigMatrix<T>) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl<T> Mul<BigMatrix<T>> for &BigMatrix<T>
+where
+    T: Scalar,
+    for<'a> &'a T: Add<&'a T, Output = T> + Mul<&'a T, Output = T>,
+{
+    type Output = BigMatrix<T>;
+    fn mul(self, rhs: BigMatrix<T>) -> Self::Output {
+        self * &rhs
+    }
+}
+
+//one row per line, [a, b, c]
+impl<T: Display> Display for BigMatrix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for i in 0..self.rows {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let row: Vec<String> = self.row(i).iter().map(|x| x.to_string()).collect();
+            write!(f, "[{}]", row.join(", "))?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use std::{
+    cmp::Ordering,
+    error::Error,
+    fmt::{self, Display},
+};
+
+use crate::big_num::BigNum;
+use crate::factor;
+use crate::limbs;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModularError {
+    NotAnInteger,
+    //the modulus has to be a positive integer
+    InvalidModulus,
+    //the jacobi symbol is only defined for odd positive moduli
+    EvenModulus,
+    //legendre symbols and modular square roots need an odd prime modulus
+    NotPrime,
+    //the value shares the factor `gcd` with the modulus
+    NotInvertible { gcd: BigNum },
+    //the congruences contradict each other, or the value is not a quadratic residue
+    NoSolution,
+}
+
+impl Display for ModularError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ModularError::NotAnInteger => write!(f, "modular arithmetic needs integer arguments"),
+            ModularError::InvalidModulus => write!(f, "the modulus must be a positive integer"),
+            ModularError::EvenModulus => write!(f, "the modulus must be odd"),
+            ModularError::NotPrime => write!(f, "the modulus must be an odd prime"),
+            ModularError::NotInvertible { gcd } => {
+                write!(
+                    f,
+                    "not invertible, shares the factor {} with the modulus",
+                    gcd
+                )
+            }
+            ModularError::NoSolution => write!(f, "no solution exists"),
+        }
+    }
+}
+
+impl Error for ModularError {}
+
+fn check_integer(n: &BigNum) -> Result<(), ModularError> {
+    if n.is_integer() {
+        Ok(())
+    } else {
+        Err(ModularError::NotAnInteger)
+    }
+}
+
+//the modulus as limbs, checked to be a positive integer
+fn modulus(m: &BigNum) -> Result<Vec<u64>, ModularError> {
+    check_integer(m)?;
+    if m <= &BigNum::from(0) {
+        return Err(ModularError::InvalidModulus);
+    }
+    Ok(m.magnitude())
+}
+
+//n mod m in [0, m), also for negative n
+fn reduce(n: &BigNum, m: &[u64]) -> Vec<u64> {
+    let r = limbs::rem(&n.magnitude(), m);
+    if n < &BigNum::from(0) && !r.is_empty() {
+        limbs::sub(m, &r)
+    } else {
+        r
+    }
+}
+
+impl BigNum {
+    //returns (g, x, y) with a * x + b * y = g = gcd(a, b), g is never negative
+    pub fn extended_gcd(a: Self, b: Self) -> Result<(Self, Self, Self), ModularError> {
+        check_integer(&a)?;
+        check_integer(&b)?;
+
+        let mut r0 = a.magnitude();
+        let mut r1 = b.magnitude();
+        let (mut x0, mut x1) = (BigNum::from(1), BigNum::from(0));
+        let (mut y0, mut y1) = (BigNum::from(0), BigNum::from(1));
+        while !r1.is_empty() {
+            let (q, r) = limbs::div_rem(&r0, &r1);
+            let q = BigNum::from_magnitude(false, q);
+            let x = &x0 - &q * &x1;
+            let y = &y0 - &q * &y1;
+            (r0, r1) = (r1, r);
+            (x0, x1) = (x1, x);
+            (y0, y1) = (y1, y);
+        }
+
+        if a < BigNum::from(0) {
+            x0 = -x0;
+        }
+        if b < BigNum::from(0) {
+            y0 = -y0;
+        }
+        Ok((BigNum::from_magnitude(false, r0), x0, y0))
+    }
+
+    //least common multiple, never negative
+    pub fn lcm(a: Self, b: Self) -> Result<Self, ModularError> {
+        check_integer(&a)?;
+        check_integer(&b)?;
+        let a = a.magnitude();
+        let b = b.magnitude();
+        if a.is_empty() || b.is_empty() {
+            return Ok(BigNum::from(0));
+        }
+        let (q, _) = limbs::div_rem(&a, &limbs::gcd(&a, &b));
+        Ok(BigNum::from_magnitude(false, limbs::mul(&q, &b)))
+    }
+
+    //x in [0, modulus) with self * x = 1 (mod modulus)
+    pub fn mod_inverse(self, modulus: Self) -> Result<Self, ModularError> {
+        check_integer(&self)?;
+        let m = self::modulus(&modulus)?;
+        match limbs::inv_mod(&reduce(&self, &m), &m) {
+            Ok(inverse) => Ok(BigNum::from_magnitude(false, inverse)),
+            Err(gcd) => Err(ModularError::NotInvertible {
+                gcd: BigNum::from_magnitude(false, gcd),
+            }),
+        }
+    }
+
+    //jacobi symbol (a / n) for odd positive n
+    pub fn jacobi(a: Self, n: Self) -> Result<i32, ModularError> {
+        check_integer(&a)?;
+        let n = modulus(&n)?;
+        if !limbs::bit(&n, 0) {
+            return Err(ModularError::EvenModulus);
+        }
+        Ok(jacobi(reduce(&a, &n), n))
+    }
+
+    //legendre symbol (a / p) for an odd prime p
+    pub fn legendre(a: Self, p: Self) -> Result<i32, ModularError> {
+        check_integer(&a)?;
+        let p = modulus(&p)?;
+        if !limbs::bit(&p, 0) || !factor::is_probable_prime(&p) {
+            return Err(ModularError::NotPrime);
+        }
+        Ok(jacobi(reduce(&a, &p), p))
+    }
+
+    //solves x = residue (mod modulus) for every pair, returning (x, m) where m is the lcm of the
+    //moduli and 0 <= x < m, the moduli do not need to be coprime
+    pub fn chinese_remainder(congruences: &[(Self, Self)]) -> Result<(Self, Self), ModularError> {
+        let mut x: Vec<u64> = Vec::new();
+        let mut m: Vec<u64> = vec![1];
+        for (residue, modulus) in congruences {
+            check_integer(residue)?;
+            let n = self::modulus(modulus)?;
+            let r = reduce(residue, &n);
+
+            //x + m * t = r (mod n) has a solution only if gcd(m, n) divides r - x
+            let g = limbs::gcd(&m, &n);
+            let diff = limbs::sub_mod(&r, &limbs::rem(&x, &n), &n);
+            let (diff, rest) = limbs::div_rem(&diff, &g);
+            if !rest.is_empty() {
+                return Err(ModularError::NoSolution);
+            }
+            let (n_g, _) = limbs::div_rem(&n, &g);
+            let (m_g, _) = limbs::div_rem(&m, &g);
+            let t = match limbs::inv_mod(&m_g, &n_g) {
+                Ok(inverse) => limbs::mul_mod(&diff, &inverse, &n_g),
+                Err(_) => unreachable!("m / g and n / g are coprime"),
+            };
+            x = limbs::add(&x, &limbs::mul(&m, &t));
+            m = limbs::mul(&m, &n_g);
+        }
+        Ok((
+            BigNum::from_magnitude(false, x),
+            BigNum::from_magnitude(false, m),
+        ))
+    }
+
+    //the smaller square root of self modulo the prime p (tonelli-shanks)
+    pub fn sqrt_mod(self, p: Self) -> Result<Self, ModularError> {
+        check_integer(&self)?;
+        let p = modulus(&p)?;
+        if limbs::cmp(&p, &[2]) == Ordering::Equal {
+            return Ok(BigNum::from_magnitude(false, reduce(&self, &p)));
+        }
+        if !limbs::bit(&p, 0) || !factor::is_probable_prime(&p) {
+            return Err(ModularError::NotPrime);
+        }
+        let a = reduce(&self, &p);
+        if a.is_empty() {
+            return Ok(BigNum::from(0));
+        }
+        if jacobi(a.clone(), p.clone()) != 1 {
+            return Err(ModularError::NoSolution);
+        }
+
+        //p - 1 = q * 2^s with q odd
+        let p_minus_one = limbs::sub(&p, &[1]);
+        let s = limbs::trailing_zeros(&p_minus_one);
+        let q = limbs::shr(&p_minus_one, s);
+
+        //any quadratic non residue works as z
+        let mut z = vec![2];
+        while jacobi(z.clone(), p.clone()) != -1 {
+            z = limbs::add(&z, &[1]);
+        }
+
+        let mut m = s;
+        let mut c = limbs::pow_mod(&z, &q, &p);
+        let mut t = limbs::pow_mod(&a, &q, &p);
+        let mut r = limbs::pow_mod(&a, &limbs::shr(&limbs::add(&q, &[1]), 1), &p);
+        while !limbs::is_one(&t) {
+            //find the least i with t^(2^i) = 1
+            let mut i = 0;
+            let mut t2 = t.clone();
+            while !limbs::is_one(&t2) {
+                t2 = limbs::mul_mod(&t2, &t2, &p);
+                i += 1;
+            }
+            let mut b = c;
+            for _ in 0..m - i - 1 {
+                b = limbs::mul_mod(&b, &b, &p);
+            }
+            m = i;
+            c = limbs::mul_mod(&b, &b, &p);
+            t = limbs::mul_mod(&t, &c, &p);
+            r = limbs::mul_mod(&r, &b, &p);
+        }
+
+        let other = limbs::sub(&p, &r);
+        if limbs::cmp(&other, &r) == Ordering::Less {
+            r = other;
+        }
+        Ok(BigNum::from_magnitude(false, r))
+    }
+}
+
+//jacobi symbol for 0 <= a < n with n odd
+fn jacobi(mut a: Vec<u64>, mut n: Vec<u64>) -> i32 {
+    let mut result = 1;
+    while !a.is_empty() {
+        let twos = limbs::trailing_zeros(&a);
+        a = limbs::shr(&a, twos);
+        //(2 / n) = -1 exactly when n = 3 or 5 (mod 8)
+        let n_mod_8 = n[0] & 7;
+        if twos % 2 == 1 && (n_mod_8 == 3 || n_mod_8 == 5) {
+            result = -result;
+        }
+        //quadratic reciprocity, the sign flips when both are 3 (mod 4)
+        if a[0] & 3 == 3 && n[0] & 3 == 3 {
+            result = -result;
+        }
+        std::mem::swap(&mut a, &mut n);
+        a = limbs::rem(&a, &n);
+    }
+    if limbs::is_one(&n) {
+        result
+    } else {
+        0
+    }
+}
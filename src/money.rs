@@ -0,0 +1,363 @@
+use std::cmp::Ordering;
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::big_num::BigNum;
+use crate::decimal::{BigDecimal, RoundingMode};
+use crate::limbs;
+
+//an ISO 4217 code and how many digits its minor unit has (2 for cents, 0 for yen)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Currency {
+    code: &'static str,
+    minor_units: u32,
+}
+
+impl Currency {
+    pub const USD: Currency = Currency::new("USD", 2);
+    pub const EUR: Currency = Currency::new("EUR", 2);
+    pub const GBP: Currency = Currency::new("GBP", 2);
+    pub const CHF: Currency = Currency::new("CHF", 2);
+    pub const CAD: Currency = Currency::new("CAD", 2);
+    pub const AUD: Currency = Currency::new("AUD", 2);
+    pub const CNY: Currency = Currency::new("CNY", 2);
+    pub const INR: Currency = Currency::new("INR", 2);
+    pub const JPY: Currency = Currency::new("JPY", 0);
+    pub const KRW: Currency = Currency::new("KRW", 0);
+    pub const BHD: Currency = Currency::new("BHD", 3);
+    pub const KWD: Currency = Currency::new("KWD", 3);
+
+    const KNOWN: [Currency; 12] = [
+        Currency::USD,
+        Currency::EUR,
+        Currency::GBP,
+        Currency::CHF,
+        Currency::CAD,
+        Currency::AUD,
+        Currency::CNY,
+        Currency::INR,
+        Currency::JPY,
+        Currency::KRW,
+        Currency::BHD,
+        Currency::KWD,
+    ];
+
+    pub const fn new(code: &'static str, minor_units: u32) -> Currency {
+        Currency { code, minor_units }
+    }
+
+    //one of the currencies above by its code
+    pub fn from_code(code: &str) -> Option<Currency> {
+        Currency::KNOWN.into_iter().find(|c| c.code == code)
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.code
+    }
+
+    pub fn minor_units(&self) -> u32 {
+        self.minor_units
+    }
+}
+
+impl Display for Currency {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.code)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoneyError {
+    CurrencyMismatch { expected: Currency, found: Currency },
+    InvalidRatios,
+}
+
+impl Display for MoneyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoneyError::CurrencyMismatch { expected, found } => {
+                write!(
+                    f,
+                    "Currency mismatch: expected {}, found {}",
+                    expected, found
+                )
+            }
+            MoneyError::InvalidRatios => {
+                write!(f, "Ratios must be non negative with a positive sum")
+            }
+        }
+    }
+}
+
+impl Error for MoneyError {}
+
+//an amount of one currency, always held at that currency's minor unit scale
+//anything that would need more digits (multiplying by a rate, say) rounds half to even, and
+//mixing currencies is an error rather than a silent conversion
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Money {
+    amount: BigDecimal,
+    currency: Currency,
+}
+
+impl Money {
+    //amount rounded half to even to the minor unit
+    pub fn new(amount: BigDecimal, currency: Currency) -> Money {
+        Money {
+            amount: amount.with_scale(currency.minor_units as i64, RoundingMode::HalfEven),
+            currency,
+        }
+    }
+
+    //from a count of minor units, so 1999 cents is 19.99
+    pub fn from_minor(minor: BigNum, currency: Currency) -> Money {
+        Money {
+            amount: BigDecimal::new(minor, currency.minor_units as i64),
+            currency,
+        }
+    }
+
+    pub fn zero(currency: Currency) -> Money {
+        Money::from_minor(BigNum::new(), currency)
+    }
+
+    pub fn amount(&self) -> &BigDecimal {
+        &self.amount
+    }
+
+    pub fn currency(&self) -> Currency {
+        self.currency
+    }
+
+    //the amount as a count of minor units
+    pub fn minor(&self) -> &BigNum {
+        self.amount.unscaled()
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.amount.is_zero()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.amount.is_negative()
+    }
+
+    fn check_currency(&self, rhs: &Money) -> Result<(), MoneyError> {
+        if self.currency != rhs.currency {
+            return Err(MoneyError::CurrencyMismatch {
+                expected: self.currency,
+                found: rhs.currency,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn checked_add(&self, rhs: &Money) -> Result<Money, MoneyError> {
+        self.check_currency(rhs)?;
+        Ok(Money::from_minor(self.minor() + rhs.minor(), self.currency))
+    }
+
+    pub fn checked_sub(&self, rhs: &Money) -> Result<Money, MoneyError> {
+        self.check_currency(rhs)?;
+        Ok(Money::from_minor(self.minor() - rhs.minor(), self.currency))
+    }
+
+    //None when the currencies differ
+    pub fn checked_cmp(&self, rhs: &Money) -> Option<Ordering> {
+        self.check_currency(rhs).ok()?;
+        Some(self.minor().cmp(rhs.minor()))
+    }
+
+    //splits self into one part per ratio, in proportion to the ratios
+    //every part is a whole number of minor units and they add up to exactly self: each part
+    //starts out rounded toward zero and the minor units that leaves over go one at a time to
+    //the parts that lost the most, earlier parts first on ties
+    pub fn allocate(&self, ratios: &[BigDecimal]) -> Result<Vec<Money>, MoneyError> {
+        let zero = BigDecimal::zero();
+        if ratios.iter().any(|r| *r < zero) {
+            return Err(MoneyError::InvalidRatios);
+        }
+        let total = ratios.iter().fold(BigDecimal::zero(), |sum, r| sum + r);
+        if total.is_zero() {
+            return Err(MoneyError::InvalidRatios);
+        }
+
+        //bring every ratio to the same scale so they become integers
+        let scale = ratios.iter().map(|r| r.scale()).max().unwrap().max(0);
+        let weights: Vec<Vec<u64>> = ratios
+            .iter()
+            .map(|r| {
+                r.with_scale(scale, RoundingMode::Down)
+                    .unscaled()
+                    .magnitude()
+            })
+            .collect();
+        let total = total
+            .with_scale(scale, RoundingMode::Down)
+            .unscaled()
+            .magnitude();
+        let minor = self.minor().magnitude();
+
+        let mut parts = Vec::with_capacity(weights.len());
+        let mut remainders = Vec::with_capacity(weights.len());
+        let mut left = minor.clone();
+        for w in &weights {
+            let (q, r) = limbs::div_rem(&limbs::mul(&minor, w), &total);
+            left = limbs::sub(&left, &q);
+            parts.push(q);
+            remainders.push(r);
+        }
+        let mut order: Vec<usize> = (0..parts.len()).collect();
+        order.sort_by(|&a, &b| limbs::cmp(&remainders[b], &remainders[a]));
+        //fewer minor units are left over than there are parts
+        let left = left.first().copied().unwrap_or(0) as usize;
+        for &i in order.iter().take(left) {
+            parts[i] = limbs::add(&parts[i], &[1]);
+        }
+
+        let negative = self.is_negative();
+        Ok(parts
+            .into_iter()
+            .map(|p| Money::from_minor(BigNum::from_magnitude(negative, p), self.currency))
+            .collect())
+    }
+
+    //the amount with `group` between every three whole digits and `point` before the minor
+    //units, like 1,234,567.89 or 1.234.567,89
+    pub fn format_with(&self, group: char, point: char) -> String {
+        let digits = self.amount.abs().to_string();
+        let (whole, fraction) = digits.split_once('.').unwrap_or((&digits, ""));
+        let mut result = String::new();
+        if self.is_negative() {
+            result.push('-');
+        }
+        for (i, c) in whole.chars().enumerate() {
+            if i > 0 && (whole.len() - i) % 3 == 0 {
+                result.push(group);
+            }
+            result.push(c);
+        }
+        if !fraction.is_empty() {
+            result.push(point);
+            result.push_str(fraction);
+        }
+        result
+    }
+}
+
+impl Add for Money {
+    type Output = Money;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl Add for &Money {
+    type Output = Money;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl Add<&Money> for Money {
+    type Output = Money;
+    fn add(self, rhs: &Money) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<Money> for &Money {
+    type Output = Money;
+    fn add(self, rhs: Money) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for Money {
+    type Output = Money;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl Sub for &Money {
+    type Output = Money;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl Sub<&Money> for Money {
+    type Output = Money;
+    fn sub(self, rhs: &Money) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<Money> for &Money {
+    type Output = Money;
+    fn sub(self, rhs: Money) -> Self::Output {
+        self - &rhs
+    }
+}
+
+//scaling by a rate or quantity, rounded half to even back to the minor unit
+impl Mul<&BigDecimal> for &Money {
+    type Output = Money;
+    fn mul(self, rhs: &BigDecimal) -> Self::Output {
+        Money::new(&self.amount * rhs, self.currency)
+    }
+}
+
+impl Mul<BigDecimal> for Money {
+    type Output = Money;
+    fn mul(self, rhs: BigDecimal) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul<&BigDecimal> for Money {
+    type Output = Money;
+    fn mul(self, rhs: &BigDecimal) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<BigDecimal> for &Money {
+    type Output = Money;
+    fn mul(self, rhs: BigDecimal) -> Self::Output {
+        self * &rhs
+    }
+}
+
+impl Neg for Money {
+    type Output = Money;
+    fn neg(self) -> Self::Output {
+        Money {
+            amount: -self.amount,
+            currency: self.currency,
+        }
+    }
+}
+
+impl Neg for &Money {
+    type Output = Money;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+//amounts of different currencies are unordered
+impl PartialOrd for Money {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.checked_cmp(other)
+    }
+}
+
+//1,234.50 USD
+impl Display for Money {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.format_with(',', '.'), self.currency)
+    }
+}
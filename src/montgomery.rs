@@ -0,0 +1,333 @@
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display},
+    ops::{Add, Mul, Neg, Sub},
+};
+
+use crate::big_num::BigNum;
+use crate::limbs;
+use crate::modular::ModularError;
+
+//precomputed values for montgomery multiplication modulo an odd n, with R = 2^(64 * limbs of n)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MontgomeryContext {
+    pub(crate) modulus: Vec<u64>,
+    //-n^-1 mod 2^64
+    pub(crate) n_prime: u64,
+    //R mod n, which is 1 in montgomery form
+    pub(crate) one: Vec<u64>,
+    //R^2 mod n, used to move values into montgomery form
+    pub(crate) r_squared: Vec<u64>,
+}
+
+//a value mod n stored in montgomery form (x * R mod n), always exactly as many limbs as n
+#[derive(Debug, Clone)]
+pub struct ModInt<'a> {
+    value: Vec<u64>,
+    ctx: &'a MontgomeryContext,
+}
+
+impl MontgomeryContext {
+    pub fn new(modulus: &BigNum) -> Result<Self, ModularError> {
+        if !modulus.is_integer() {
+            return Err(ModularError::NotAnInteger);
+        }
+        if modulus <= &BigNum::from(1) {
+            return Err(ModularError::InvalidModulus);
+        }
+        let modulus = modulus.magnitude();
+        if modulus[0] & 1 == 0 {
+            return Err(ModularError::EvenModulus);
+        }
+
+        //newton's iteration for the inverse mod 2^64, every step doubles the number of correct bits
+        let mut inverse: u64 = 1;
+        for _ in 0..6 {
+            inverse = inverse.wrapping_mul(2u64.wrapping_sub(modulus[0].wrapping_mul(inverse)));
+        }
+
+        let bits = 64 * modulus.len();
+        let mut ctx = MontgomeryContext {
+            n_prime: inverse.wrapping_neg(),
+            one: limbs::rem(&limbs::shl(&[1], bits), &modulus),
+            r_squared: limbs::rem(&limbs::shl(&[1], 2 * bits), &modulus),
+            modulus,
+        };
+        ctx.one = ctx.pad(ctx.one.clone());
+        ctx.r_squared = ctx.pad(ctx.r_squared.clone());
+        Ok(ctx)
+    }
+
+    pub fn modulus(&self) -> BigNum {
+        BigNum::from_magnitude(false, self.modulus.clone())
+    }
+
+    //value mod n as an element of this context, negative values wrap around
+    pub fn element(&self, value: &BigNum) -> Result<ModInt<'_>, ModularError> {
+        if !value.is_integer() {
+            return Err(ModularError::NotAnInteger);
+        }
+        let mut reduced = limbs::rem(&value.magnitude(), &self.modulus);
+        if value < &BigNum::from(0) && !reduced.is_empty() {
+            reduced = limbs::sub(&self.modulus, &reduced);
+        }
+        let reduced = self.pad(reduced);
+        Ok(ModInt {
+            value: self.mul(&reduced, &self.r_squared),
+            ctx: self,
+        })
+    }
+
+    pub fn zero(&self) -> ModInt<'_> {
+        ModInt {
+            value: vec![0; self.modulus.len()],
+            ctx: self,
+        }
+    }
+
+    pub fn one(&self) -> ModInt<'_> {
+        ModInt {
+            value: self.one.clone(),
+            ctx: self,
+        }
+    }
+
+    fn pad(&self, mut value: Vec<u64>) -> Vec<u64> {
+        value.resize(self.modulus.len(), 0);
+        value
+    }
+
+    //a * b / R mod n (coarsely integrated operand scanning)
+    fn mul(&self, a: &[u64], b: &[u64]) -> Vec<u64> {
+        let n = &self.modulus;
+        let s = n.len();
+        let mut t = vec![0u64; s + 2];
+        for &b_i in b.iter().take(s) {
+            //t += a * b_i
+            let mut carry: u128 = 0;
+            for j in 0..s {
+                let x = t[j] as u128 + (a[j] as u128) * (b_i as u128) + carry;
+                t[j] = x as u64;
+                carry = x >> 64;
+            }
+            let x = t[s] as u128 + carry;
+            t[s] = x as u64;
+            t[s + 1] = (x >> 64) as u64;
+
+            //t = (t + m * n) / 2^64, m is chosen so the lowest limb becomes zero
+            let m = t[0].wrapping_mul(self.n_prime);
+            let x = t[0] as u128 + (m as u128) * (n[0] as u128);
+            let mut carry = x >> 64;
+            for j in 1..s {
+                let x = t[j] as u128 + (m as u128) * (n[j] as u128) + carry;
+                t[j - 1] = x as u64;
+                carry = x >> 64;
+            }
+            let x = t[s] as u128 + carry;
+            t[s - 1] = x as u64;
+            t[s] = t[s + 1] + (x >> 64) as u64;
+            t[s + 1] = 0;
+        }
+        t.truncate(s + 1);
+        if limbs::cmp(&t, n) != Ordering::Less {
+            t = limbs::sub(&t, n);
+        }
+        self.pad(t)
+    }
+}
+
+impl ModInt<'_> {
+    pub fn context(&self) -> &MontgomeryContext {
+        self.ctx
+    }
+
+    pub fn to_bignum(&self) -> BigNum {
+        let mut one = vec![0; self.value.len()];
+        one[0] = 1;
+        BigNum::from_magnitude(false, self.ctx.mul(&self.value, &one))
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.value.iter().all(|&limb| limb == 0)
+    }
+
+    //exp has to be a non negative integer
+    pub fn pow(&self, exp: &BigNum) -> Self {
+        assert!(exp.is_integer());
+        assert!(exp >= &BigNum::from(0));
+        let exp = exp.magnitude();
+        let mut result = self.ctx.one.clone();
+        for i in (0..limbs::bit_len(&exp)).rev() {
+            result = self.ctx.mul(&result, &result);
+            if limbs::bit(&exp, i) {
+                result = self.ctx.mul(&result, &self.value);
+            }
+        }
+        ModInt {
+            value: result,
+            ctx: self.ctx,
+        }
+    }
+
+    pub fn inv(&self) -> Result<Self, ModularError> {
+        //x R -> x^-1 R^-1 -> x^-1 R after two multiplications by R^2
+        match limbs::inv_mod(&self.value, &self.ctx.modulus) {
+            Ok(inverse) => {
+                let inverse = self.ctx.pad(inverse);
+                let inverse = self.ctx.mul(&inverse, &self.ctx.r_squared);
+                Ok(ModInt {
+                    value: self.ctx.mul(&inverse, &self.ctx.r_squared),
+                    ctx: self.ctx,
+                })
+            }
+            Err(gcd) => Err(ModularError::NotInvertible {
+                gcd: BigNum::from_magnitude(false, gcd),
+            }),
+        }
+    }
+
+    fn check_context(&self, rhs: &Self) {
+        assert!(
+            std::ptr::eq(self.ctx, rhs.ctx) || self.ctx.modulus == rhs.ctx.modulus,
+            "ModInt values have different moduli"
+        );
+    }
+}
+
+impl PartialEq for ModInt<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.ctx.modulus == other.ctx.modulus && self.value == other.value
+    }
+}
+
+impl Eq for ModInt<'_> {}
+
+impl<'a> Add for &ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.check_context(rhs);
+        let n = &self.ctx.modulus;
+        ModInt {
+            value: self.ctx.pad(limbs::add_mod(&self.value, &rhs.value, n)),
+            ctx: self.ctx,
+        }
+    }
+}
+
+impl<'a> Add for ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl<'a> Add<&ModInt<'a>> for ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn add(self, rhs: &ModInt<'a>) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl<'a> Add<ModInt<'a>> for &ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn add(self, rhs: ModInt<'a>) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl<'a> Sub for &ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.check_context(rhs);
+        let n = &self.ctx.modulus;
+        ModInt {
+            value: self.ctx.pad(limbs::sub_mod(&self.value, &rhs.value, n)),
+            ctx: self.ctx,
+        }
+    }
+}
+
+impl<'a> Sub for ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl<'a> Sub<&ModInt<'a>> for ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn sub(self, rhs: &ModInt<'a>) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl<'a> Sub<ModInt<'a>> for &ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn sub(self, rhs: ModInt<'a>) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl<'a> Mul for &ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.check_context(rhs);
+        ModInt {
+            value: self.ctx.mul(&self.value, &rhs.value),
+            ctx: self.ctx,
+        }
+    }
+}
+
+impl<'a> Mul for ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl<'a> Mul<&ModInt<'a>> for ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn mul(self, rhs: &ModInt<'a>) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl<'a> Mul<ModInt<'a>> for &ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn mul(self, rhs: ModInt<'a>) -> Self::Output {
+        self * &rhs
+    }
+}
+
+impl<'a> Neg for &ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn neg(self) -> Self::Output {
+        &self.ctx.zero() - self
+    }
+}
+
+impl<'a> Neg for ModInt<'a> {
+    type Output = ModInt<'a>;
+    fn neg(self) -> Self::Output {
+        -&self
+    }
+}
+
+impl From<&ModInt<'_>> for BigNum {
+    fn from(value: &ModInt<'_>) -> Self {
+        value.to_bignum()
+    }
+}
+
+impl From<ModInt<'_>> for BigNum {
+    fn from(value: ModInt<'_>) -> Self {
+        value.to_bignum()
+    }
+}
+
+impl Display for ModInt<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_bignum())
+    }
+}
@@ -0,0 +1,218 @@
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+//limbs kept inside the BigNum itself, enough for any u64 or u128 and the product of two
+//single limbs
+const INLINE: usize = 2;
+
+//the limbs of a BigNum, least significant first, stored inline while there are at most INLINE
+//of them and on the heap beyond that
+//the heap buffer is shared between clones and only copied when one of them is changed, so
+//cloning a BigNum never copies limbs
+//everything reads them as a slice, only the operations that change the length live here
+//the inline length is a u8 so it shares a word with the tag, which keeps Parts at 24 bytes, the
+//size of the Vec it replaces
+pub(crate) enum Parts {
+    Inline { len: u8, limbs: [u64; INLINE] },
+    Heap(Arc<Vec<u64>>),
+}
+
+impl Parts {
+    pub(crate) fn new() -> Parts {
+        Parts::Inline {
+            len: 0,
+            limbs: [0; INLINE],
+        }
+    }
+
+    pub(crate) fn from_slice(slice: &[u64]) -> Parts {
+        if slice.len() <= INLINE {
+            let mut limbs = [0; INLINE];
+            limbs[..slice.len()].copy_from_slice(slice);
+            Parts::Inline {
+                len: slice.len() as u8,
+                limbs,
+            }
+        } else {
+            Parts::Heap(Arc::new(slice.to_vec()))
+        }
+    }
+
+    //room for `capacity` limbs before the first allocation
+    pub(crate) fn with_capacity(capacity: usize) -> Parts {
+        if capacity <= INLINE {
+            Parts::new()
+        } else {
+            Parts::Heap(Arc::new(Vec::with_capacity(capacity)))
+        }
+    }
+
+    //len zero limbs, reusing the heap buffer when nothing else shares it
+    pub(crate) fn reset(&mut self, len: usize) -> &mut [u64] {
+        let reusable = matches!(self, Parts::Heap(heap) if Arc::strong_count(heap) == 1);
+        if !reusable {
+            *self = Parts::new();
+        }
+        self.truncate(0);
+        self.resize(len, 0);
+        self
+    }
+
+    //the limbs as a Vec this value owns alone, moving inline limbs to the heap with room for
+    //at least `capacity` and copying a shared buffer
+    fn spill(&mut self, capacity: usize) -> &mut Vec<u64> {
+        if let Parts::Inline { len, limbs } = *self {
+            let mut heap = Vec::with_capacity(capacity.max(2 * INLINE));
+            heap.extend_from_slice(&limbs[..len as usize]);
+            *self = Parts::Heap(Arc::new(heap));
+        }
+        match self {
+            Parts::Heap(heap) => Arc::make_mut(heap),
+            Parts::Inline { .. } => unreachable!(),
+        }
+    }
+
+    pub(crate) fn push(&mut self, limb: u64) {
+        match self {
+            Parts::Inline { len, limbs } if (*len as usize) < INLINE => {
+                limbs[*len as usize] = limb;
+                *len += 1;
+            }
+            _ => self.spill(INLINE + 1).push(limb),
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<u64> {
+        match self {
+            Parts::Inline { len: 0, .. } => None,
+            Parts::Inline { len, limbs } => {
+                *len -= 1;
+                Some(limbs[*len as usize])
+            }
+            Parts::Heap(heap) => Arc::make_mut(heap).pop(),
+        }
+    }
+
+    pub(crate) fn insert(&mut self, index: usize, limb: u64) {
+        match self {
+            Parts::Inline { len, limbs } if (*len as usize) < INLINE => {
+                assert!(index <= *len as usize, "insertion index out of bounds");
+                limbs.copy_within(index..*len as usize, index + 1);
+                limbs[index] = limb;
+                *len += 1;
+            }
+            _ => self.spill(INLINE + 1).insert(index, limb),
+        }
+    }
+
+    pub(crate) fn remove(&mut self, index: usize) -> u64 {
+        match self {
+            Parts::Inline { len, limbs } => {
+                let limb = limbs[..*len as usize][index];
+                limbs.copy_within(index + 1..*len as usize, index);
+                *len -= 1;
+                limb
+            }
+            Parts::Heap(heap) => Arc::make_mut(heap).remove(index),
+        }
+    }
+
+    //n zero limbs below the current ones
+    pub(crate) fn insert_low(&mut self, n: usize) {
+        match self {
+            Parts::Inline { len, limbs } if *len as usize + n <= INLINE => {
+                limbs.copy_within(..*len as usize, n);
+                limbs[..n].fill(0);
+                *len += n as u8;
+            }
+            _ => {
+                let heap = self.spill(INLINE + n);
+                heap.splice(..0, std::iter::repeat_n(0, n));
+            }
+        }
+    }
+
+    //drops the n least significant limbs
+    pub(crate) fn remove_low(&mut self, n: usize) {
+        match self {
+            Parts::Inline { len, limbs } => {
+                limbs.copy_within(n..*len as usize, 0);
+                *len -= n as u8;
+            }
+            Parts::Heap(heap) => {
+                Arc::make_mut(heap).drain(..n);
+            }
+        }
+    }
+
+    pub(crate) fn truncate(&mut self, n: usize) {
+        match self {
+            Parts::Inline { len, .. } => *len = (*len as usize).min(n) as u8,
+            Parts::Heap(heap) => Arc::make_mut(heap).truncate(n),
+        }
+    }
+
+    pub(crate) fn resize(&mut self, n: usize, value: u64) {
+        match self {
+            Parts::Inline { len, limbs } if n <= INLINE => {
+                limbs[(*len as usize).min(n)..n].fill(value);
+                *len = n as u8;
+            }
+            _ => self.spill(n).resize(n, value),
+        }
+    }
+}
+
+impl Default for Parts {
+    fn default() -> Self {
+        Parts::new()
+    }
+}
+
+//shares the heap buffer, or moves back inline if it has shrunk to INLINE limbs
+impl Clone for Parts {
+    fn clone(&self) -> Self {
+        match self {
+            Parts::Heap(heap) if heap.len() > INLINE => Parts::Heap(Arc::clone(heap)),
+            _ => Parts::from_slice(self),
+        }
+    }
+}
+
+impl From<Vec<u64>> for Parts {
+    fn from(heap: Vec<u64>) -> Self {
+        if heap.len() <= INLINE {
+            Parts::from_slice(&heap)
+        } else {
+            Parts::Heap(Arc::new(heap))
+        }
+    }
+}
+
+impl Deref for Parts {
+    type Target = [u64];
+    fn deref(&self) -> &[u64] {
+        match self {
+            Parts::Inline { len, limbs } => &limbs[..*len as usize],
+            Parts::Heap(heap) => heap,
+        }
+    }
+}
+
+//writing to a shared buffer copies it first
+impl DerefMut for Parts {
+    fn deref_mut(&mut self) -> &mut [u64] {
+        match self {
+            Parts::Inline { len, limbs } => &mut limbs[..*len as usize],
+            Parts::Heap(heap) => Arc::make_mut(heap).as_mut_slice(),
+        }
+    }
+}
+
+//prints like the Vec it replaces
+impl fmt::Debug for Parts {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
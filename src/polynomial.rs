@@ -0,0 +1,517 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::ops::{Add, Mul, Neg, Sub};
+
+use crate::big_num::BigNum;
+use crate::functions::limbs_for;
+use crate::interval::BigInterval;
+use crate::limbs;
+
+//c_0 + c_1 x + c_2 x^2 + ..., never with a zero leading coefficient, the zero polynomial has no
+//coefficients at all
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Polynomial {
+    coeffs: Vec<BigNum>,
+}
+
+fn sign(x: &BigNum) -> Ordering {
+    x.cmp(&BigNum::new())
+}
+
+//a / b for integers where b is known to divide a
+fn div_exact(a: &BigNum, b: &BigNum) -> BigNum {
+    let (quotient, _) = limbs::div_rem(&a.magnitude(), &b.magnitude());
+    BigNum::from_magnitude(sign(a) != sign(b), quotient)
+}
+
+impl Polynomial {
+    //coefficients from the constant term up
+    pub fn new(mut coeffs: Vec<BigNum>) -> Polynomial {
+        while coeffs.last().is_some_and(|c| c.is_zero()) {
+            coeffs.pop();
+        }
+        Polynomial { coeffs }
+    }
+
+    pub fn constant(c: BigNum) -> Polynomial {
+        Polynomial::new(vec![c])
+    }
+
+    //the polynomial x
+    pub fn x() -> Polynomial {
+        Polynomial::new(vec![BigNum::new(), BigNum::from(1)])
+    }
+
+    pub fn coeffs(&self) -> &[BigNum] {
+        &self.coeffs
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.coeffs.is_empty()
+    }
+
+    //None for the zero polynomial
+    pub fn degree(&self) -> Option<usize> {
+        self.coeffs.len().checked_sub(1)
+    }
+
+    pub fn leading_coefficient(&self) -> BigNum {
+        self.coeffs.last().cloned().unwrap_or_default()
+    }
+
+    //horner's rule, exact
+    pub fn eval(&self, x: &BigNum) -> BigNum {
+        self.coeffs
+            .iter()
+            .rev()
+            .fold(BigNum::new(), |acc, c| acc * x + c)
+    }
+
+    pub fn derivative(&self) -> Polynomial {
+        Polynomial::new(
+            self.coeffs
+                .iter()
+                .enumerate()
+                .skip(1)
+                .map(|(i, c)| c * BigNum::from(i as u64))
+                .collect(),
+        )
+    }
+
+    //c x^k
+    fn monomial(c: BigNum, k: usize) -> Polynomial {
+        let mut coeffs = vec![BigNum::new(); k];
+        coeffs.push(c);
+        Polynomial::new(coeffs)
+    }
+
+    fn scale(&self, c: &BigNum) -> Polynomial {
+        Polynomial::new(self.coeffs.iter().map(|x| x * c).collect())
+    }
+
+    //(quotient, remainder) with self = quotient * rhs + remainder and deg remainder < deg rhs
+    //every step divides by the leading coefficient of rhs, which is exact when that is a power
+    //of two (in particular for monic divisors) and otherwise keeps as many limbs as BigNum's
+    //Div does
+    pub fn div_rem(&self, rhs: &Polynomial) -> (Polynomial, Polynomial) {
+        let Some(rhs_degree) = rhs.degree() else {
+            panic!("Divide by zero");
+        };
+        let lead = rhs.leading_coefficient();
+        let mut quotient = Polynomial::default();
+        let mut remainder = self.clone();
+        while let Some(degree) = remainder.degree().filter(|&d| d >= rhs_degree) {
+            let top = remainder.leading_coefficient();
+            let limb_count = top.get_precision() + lead.get_precision();
+            let term =
+                Polynomial::monomial(top.div_to_limbs(&lead, limb_count), degree - rhs_degree);
+            remainder = remainder - &term * rhs;
+            //drop whatever rounding left in the top coefficient so the degree always goes down
+            remainder.coeffs.truncate(degree);
+            remainder = Polynomial::new(remainder.coeffs);
+            quotient = quotient + term;
+        }
+        (quotient, remainder)
+    }
+
+    //the same polynomial times a power of two so every coefficient is an integer
+    fn integer_multiple(&self) -> Polynomial {
+        let mut result = self.clone();
+        while !result.coeffs.iter().all(|c| c.is_integer()) {
+            result.coeffs = result
+                .coeffs
+                .into_iter()
+                .map(|c| c.scale_by_limbs(1))
+                .collect();
+        }
+        result
+    }
+
+    //divided by the gcd of its coefficients, for integer coefficients only
+    fn primitive_part(&self) -> Polynomial {
+        let content = self
+            .coeffs
+            .iter()
+            .fold(Vec::new(), |g, c| limbs::gcd(&g, &c.magnitude()));
+        if content.is_empty() || limbs::is_one(&content) {
+            return self.clone();
+        }
+        let content = BigNum::from_magnitude(false, content);
+        Polynomial::new(self.coeffs.iter().map(|c| div_exact(c, &content)).collect())
+    }
+
+    //a positive multiple of the remainder of self / rhs, for integer coefficients, so no
+    //division is needed: each step computes |lc(rhs)| r - sign(lc(rhs)) lead(r) x^k rhs
+    fn pseudo_remainder(&self, rhs: &Polynomial) -> Polynomial {
+        let rhs_degree = rhs.degree().expect("Divide by zero");
+        let lead = rhs.leading_coefficient();
+        let (lead_abs, lead_negative) = if sign(&lead) == Ordering::Less {
+            (-&lead, true)
+        } else {
+            (lead, false)
+        };
+        let mut remainder = self.clone();
+        while let Some(degree) = remainder.degree().filter(|&d| d >= rhs_degree) {
+            let mut top = remainder.leading_coefficient();
+            if lead_negative {
+                top = -top;
+            }
+            remainder =
+                remainder.scale(&lead_abs) - Polynomial::monomial(top, degree - rhs_degree) * rhs;
+        }
+        remainder
+    }
+
+    //self / rhs for integer coefficients when rhs is primitive and divides self exactly
+    fn div_exact(&self, rhs: &Polynomial) -> Polynomial {
+        let rhs_degree = rhs.degree().expect("Divide by zero");
+        let lead = rhs.leading_coefficient();
+        let mut quotient = Polynomial::default();
+        let mut remainder = self.clone();
+        while let Some(degree) = remainder.degree().filter(|&d| d >= rhs_degree) {
+            let term = Polynomial::monomial(
+                div_exact(&remainder.leading_coefficient(), &lead),
+                degree - rhs_degree,
+            );
+            remainder = remainder - &term * rhs;
+            quotient = quotient + term;
+        }
+        quotient
+    }
+
+    //the greatest common divisor, made primitive with integer coefficients and a positive
+    //leading coefficient since it is only defined up to a constant factor
+    pub fn gcd(a: &Polynomial, b: &Polynomial) -> Polynomial {
+        let mut a = a.integer_multiple().primitive_part();
+        let mut b = b.integer_multiple().primitive_part();
+        while !b.is_zero() {
+            let r = a.pseudo_remainder(&b).primitive_part();
+            a = b;
+            b = r;
+        }
+        if sign(&a.leading_coefficient()) == Ordering::Less {
+            a = -a;
+        }
+        a
+    }
+
+    //the same roots, each with multiplicity one, with integer coefficients
+    fn square_free(&self) -> Polynomial {
+        let p = self.integer_multiple().primitive_part();
+        let g = Polynomial::gcd(&p, &p.derivative());
+        p.div_exact(&g)
+    }
+
+    //p, p', then the negated remainders, each made primitive which keeps every sign
+    fn sturm_sequence(&self) -> Vec<Polynomial> {
+        let mut sequence = vec![self.clone(), self.derivative().primitive_part()];
+        loop {
+            let n = sequence.len();
+            if sequence[n - 1].degree().unwrap_or(0) == 0 {
+                break;
+            }
+            let r = sequence[n - 2].pseudo_remainder(&sequence[n - 1]);
+            if r.is_zero() {
+                break;
+            }
+            sequence.push(-r.primitive_part());
+        }
+        sequence
+    }
+
+    //sign changes along the sturm sequence at x, zeros are skipped
+    fn sign_changes(sequence: &[Polynomial], x: &BigNum) -> usize {
+        let signs: Vec<Ordering> = sequence
+            .iter()
+            .map(|p| sign(&p.eval(x)))
+            .filter(|&s| s != Ordering::Equal)
+            .collect();
+        signs.windows(2).filter(|w| w[0] != w[1]).count()
+    }
+
+    //a power of two above the absolute value of every root (cauchy's bound)
+    fn root_bound(&self) -> BigNum {
+        let lead = self.leading_coefficient().log2_floor().unwrap();
+        let largest = self
+            .coeffs
+            .iter()
+            .filter_map(|c| c.log2_floor())
+            .max()
+            .unwrap();
+        BigNum::from(1).mul_pow2((largest + 1 - lead).max(0) + 1)
+    }
+
+    //the number of distinct real roots
+    pub fn count_real_roots(&self) -> usize {
+        if self.degree().unwrap_or(0) == 0 {
+            return 0;
+        }
+        let p = self.square_free();
+        let bound = p.root_bound();
+        let sequence = p.sturm_sequence();
+        Polynomial::sign_changes(&sequence, &-&bound) - Polynomial::sign_changes(&sequence, &bound)
+    }
+
+    //disjoint intervals in increasing order, each holding exactly one distinct real root
+    //the ends of every interval are never roots themselves unless it is a single point
+    pub fn isolate_real_roots(&self) -> Vec<BigInterval> {
+        if self.degree().unwrap_or(0) == 0 {
+            return Vec::new();
+        }
+        let p = self.square_free();
+        let bound = p.root_bound();
+        let sequence = p.sturm_sequence();
+        let changes = |x: &BigNum| Polynomial::sign_changes(&sequence, x);
+
+        let mut result = Vec::new();
+        let mut pending = vec![(-&bound, changes(&-&bound), bound.clone(), changes(&bound))];
+        while let Some((lo, lo_changes, hi, hi_changes)) = pending.pop() {
+            match lo_changes - hi_changes {
+                0 => continue,
+                1 => {
+                    result.push(BigInterval::new(lo, hi));
+                    continue;
+                }
+                _ => {}
+            }
+            //split somewhere that is not a root, trying the midpoint first
+            let width = &hi - &lo;
+            let mut split = (&lo + &hi).mul_pow2(-1);
+            let mut step = 2;
+            while p.eval(&split).is_zero() {
+                step += 1;
+                split = &lo + width.clone().mul_pow2(-step) * BigNum::from(3);
+            }
+            let split_changes = changes(&split);
+            pending.push((split.clone(), split_changes, hi, hi_changes));
+            pending.push((lo, lo_changes, split, split_changes));
+        }
+        result.sort_by(|a, b| a.lo().cmp(b.lo()));
+        result
+    }
+
+    //the distinct real roots, each enclosed in an interval no wider than 2^-precision
+    //newton steps from inside each isolating interval, falling back to bisection whenever a
+    //step leaves the interval or does not halve it
+    pub fn real_roots(&self, precision: usize) -> Vec<BigInterval> {
+        let p = self.square_free();
+        let isolated = self.isolate_real_roots();
+        if isolated.is_empty() {
+            return isolated;
+        }
+        let d = p.derivative();
+        let target = BigNum::from(1).mul_pow2(-(precision as i64));
+        let bound_bits = p.root_bound().log2_floor().unwrap() as usize;
+        let n = limbs_for(precision + bound_bits + 64);
+
+        isolated
+            .into_iter()
+            .map(|interval| {
+                let mut lo = interval.lo().clone();
+                let mut hi = interval.hi().clone();
+                let lo_sign = sign(&p.eval(&lo));
+                let mut x = interval.midpoint();
+                while &hi - &lo > target {
+                    let width = &hi - &lo;
+                    let fx = p.eval(&x);
+                    if fx.is_zero() {
+                        return BigInterval::point(x);
+                    }
+                    let dfx = d.eval(&x);
+                    let mut candidate = if dfx.is_zero() {
+                        x.clone()
+                    } else {
+                        (&x - fx.div_to_limbs(&dfx, n)).round_to_limbs(n)
+                    };
+                    if candidate <= lo || candidate >= hi {
+                        candidate = (&lo + &hi).mul_pow2(-1);
+                    }
+
+                    //tighten the bracket with the sign at the candidate and just around it
+                    let half_target = target.clone().mul_pow2(-1);
+                    for probe in [
+                        &candidate - &half_target,
+                        candidate.clone(),
+                        &candidate + &half_target,
+                    ] {
+                        if probe <= lo || probe >= hi {
+                            continue;
+                        }
+                        match sign(&p.eval(&probe)) {
+                            Ordering::Equal => return BigInterval::point(probe),
+                            s if s == lo_sign => lo = probe,
+                            _ => hi = probe,
+                        }
+                    }
+                    if (&hi - &lo).mul_pow2(1) > width {
+                        let mid = (&lo + &hi).mul_pow2(-1);
+                        match sign(&p.eval(&mid)) {
+                            Ordering::Equal => return BigInterval::point(mid),
+                            s if s == lo_sign => lo = mid,
+                            _ => hi = mid,
+                        }
+                    }
+                    //keep iterating from the newton step, pulled back into the bracket if the
+                    //probes or a bisection moved past it
+                    x = candidate.clamp(lo.clone(), hi.clone());
+                }
+                BigInterval::new(lo, hi)
+            })
+            .collect()
+    }
+}
+
+impl From<BigNum> for Polynomial {
+    fn from(c: BigNum) -> Self {
+        Polynomial::constant(c)
+    }
+}
+
+impl Add for Polynomial {
+    type Output = Polynomial;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl Add for &Polynomial {
+    type Output = Polynomial;
+    fn add(self, rhs: Self) -> Self::Output {
+        let len = self.coeffs.len().max(rhs.coeffs.len());
+        let zero = BigNum::new();
+        Polynomial::new(
+            (0..len)
+                .map(|i| self.coeffs.get(i).unwrap_or(&zero) + rhs.coeffs.get(i).unwrap_or(&zero))
+                .collect(),
+        )
+    }
+}
+
+impl Add<&Polynomial> for Polynomial {
+    type Output = Polynomial;
+    fn add(self, rhs: &Polynomial) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<Polynomial> for &Polynomial {
+    type Output = Polynomial;
+    fn add(self, rhs: Polynomial) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for Polynomial {
+    type Output = Polynomial;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl Sub for &Polynomial {
+    type Output = Polynomial;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self + &-rhs
+    }
+}
+
+impl Sub<&Polynomial> for Polynomial {
+    type Output = Polynomial;
+    fn sub(self, rhs: &Polynomial) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<Polynomial> for &Polynomial {
+    type Output = Polynomial;
+    fn sub(self, rhs: Polynomial) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl Mul for Polynomial {
+    type Output = Polynomial;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul for &Polynomial {
+    type Output = Polynomial;
+    fn mul(self, rhs: Self) -> Self::Output {
+        if self.is_zero() || rhs.is_zero() {
+            return Polynomial::default();
+        }
+        let mut coeffs = vec![BigNum::new(); self.coeffs.len() + rhs.coeffs.len() - 1];
+        for (i, a) in self.coeffs.iter().enumerate() {
+            if a.is_zero() {
+                continue;
+            }
+            for (j, b) in rhs.coeffs.iter().enumerate() {
+                coeffs[i + j] = &coeffs[i + j] + a * b;
+            }
+        }
+        Polynomial::new(coeffs)
+    }
+}
+
+impl Mul<&Polynomial> for Polynomial {
+    type Output = Polynomial;
+    fn mul(self, rhs: &Polynomial) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<Polynomial> for &Polynomial {
+    type Output = Polynomial;
+    fn mul(self, rhs: Polynomial) -> Self::Output {
+        self * &rhs
+    }
+}
+
+impl Neg for Polynomial {
+    type Output = Polynomial;
+    fn neg(self) -> Self::Output {
+        Polynomial::new(self.coeffs.into_iter().map(|c| -c).collect())
+    }
+}
+
+impl Neg for &Polynomial {
+    type Output = Polynomial;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+//highest power first, like 3x^2 - x + 1
+impl Display for Polynomial {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_zero() {
+            return write!(f, "0");
+        }
+        let mut first = true;
+        for (i, c) in self.coeffs.iter().enumerate().rev() {
+            if c.is_zero() {
+                continue;
+            }
+            let negative = sign(c) == Ordering::Less;
+            match (first, negative) {
+                (true, true) => write!(f, "-")?,
+                (true, false) => {}
+                (false, true) => write!(f, " - ")?,
+                (false, false) => write!(f, " + ")?,
+            }
+            first = false;
+            let magnitude = if negative { -c } else { c.clone() };
+            if i == 0 || magnitude != BigNum::from(1) {
+                write!(f, "{}", magnitude)?;
+            }
+            match i {
+                0 => {}
+                1 => write!(f, "x")?,
+                _ => write!(f, "x^{}", i)?,
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,130 @@
+use rand::distributions::uniform::{SampleBorrow, SampleUniform, UniformSampler};
+use rand::distributions::Distribution;
+use rand::Rng;
+
+use crate::big_num::BigNum;
+use crate::limbs;
+
+//uniform integers in [0, 2^bits)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomBits(pub usize);
+
+//uniform integers in [0, bound), bound has to be a positive integer
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformBelow {
+    bound: Vec<u64>,
+}
+
+//uniform fractions in [0, 1) that are multiples of 2^-bits
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RandomFraction(pub usize);
+
+//uniform integers in [low, high), what rng.gen_range uses for BigNum
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniformBigNum {
+    low: BigNum,
+    range: UniformBelow,
+}
+
+fn random_limbs<R: Rng + ?Sized>(rng: &mut R, bits: usize) -> Vec<u64> {
+    let mut result: Vec<u64> = (0..bits.div_ceil(64)).map(|_| rng.gen()).collect();
+    if !bits.is_multiple_of(64) {
+        *result.last_mut().unwrap() >>= 64 - bits % 64;
+    }
+    result
+}
+
+impl Distribution<BigNum> for RandomBits {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigNum {
+        BigNum::from_magnitude(false, random_limbs(rng, self.0))
+    }
+}
+
+impl UniformBelow {
+    pub fn new(bound: &BigNum) -> UniformBelow {
+        assert!(bound.is_integer(), "bound must be an integer");
+        assert!(bound > &BigNum::from(0), "bound must be positive");
+        UniformBelow {
+            bound: bound.magnitude(),
+        }
+    }
+}
+
+impl Distribution<BigNum> for UniformBelow {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigNum {
+        //rejection sampling, every try succeeds with probability above 1/2
+        let bits = limbs::bit_len(&self.bound);
+        loop {
+            let candidate = random_limbs(rng, bits);
+            if limbs::cmp(&candidate, &self.bound) == std::cmp::Ordering::Less {
+                return BigNum::from_magnitude(false, candidate);
+            }
+        }
+    }
+}
+
+impl Distribution<BigNum> for RandomFraction {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> BigNum {
+        //shift the random bits up against the top of whole limbs and put the point above them
+        let limb_count = self.0.div_ceil(64);
+        let value = limbs::shl(&random_limbs(rng, self.0), limb_count * 64 - self.0);
+        BigNum::from_magnitude(false, value).scale_by_limbs(-(limb_count as i64))
+    }
+}
+
+impl UniformSampler for UniformBigNum {
+    type X = BigNum;
+
+    fn new<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        let low = low.borrow();
+        let high = high.borrow();
+        assert!(
+            low.is_integer() && high.is_integer(),
+            "BigNum ranges must have integer bounds"
+        );
+        assert!(low < high, "UniformBigNum::new called with `low >= high`");
+        UniformBigNum {
+            low: low.clone(),
+            range: UniformBelow::new(&(high - low)),
+        }
+    }
+
+    fn new_inclusive<B1, B2>(low: B1, high: B2) -> Self
+    where
+        B1: SampleBorrow<Self::X> + Sized,
+        B2: SampleBorrow<Self::X> + Sized,
+    {
+        UniformBigNum::new(low, high.borrow() + BigNum::from(1))
+    }
+
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> Self::X {
+        &self.low + self.range.sample(rng)
+    }
+}
+
+impl SampleUniform for BigNum {
+    type Sampler = UniformBigNum;
+}
+
+impl BigNum {
+    pub fn random_bits<R: Rng + ?Sized>(rng: &mut R, bits: usize) -> BigNum {
+        RandomBits(bits).sample(rng)
+    }
+
+    pub fn random_below<R: Rng + ?Sized>(rng: &mut R, bound: &BigNum) -> BigNum {
+        UniformBelow::new(bound).sample(rng)
+    }
+
+    //uniform integer in [low, high)
+    pub fn random_range<R: Rng + ?Sized>(rng: &mut R, low: &BigNum, high: &BigNum) -> BigNum {
+        UniformBigNum::new(low, high).sample(rng)
+    }
+
+    pub fn random_fraction<R: Rng + ?Sized>(rng: &mut R, bits: usize) -> BigNum {
+        RandomFraction(bits).sample(rng)
+    }
+}
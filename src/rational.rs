@@ -0,0 +1,249 @@
+use std::cmp::Ordering;
+use std::fmt::{self, Display};
+use std::ops::{Add, Div, Mul, Neg, Sub};
+
+use crate::big_num::BigNum;
+use crate::functions::limbs_for;
+use crate::limbs;
+
+//an exact fraction num / den, always in lowest terms with den > 0
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BigRational {
+    num: BigNum,
+    den: BigNum,
+}
+
+impl BigRational {
+    pub fn new(num: BigNum, den: BigNum) -> BigRational {
+        assert!(
+            num.is_integer() && den.is_integer(),
+            "numerator and denominator must be integers"
+        );
+        assert!(!den.is_zero(), "Divide by zero");
+        let neg = (num < BigNum::from(0)) ^ (den < BigNum::from(0));
+        let num = num.magnitude();
+        let den = den.magnitude();
+        let g = limbs::gcd(&num, &den);
+        BigRational {
+            num: BigNum::from_magnitude(neg, limbs::div_rem(&num, &g).0),
+            den: BigNum::from_magnitude(false, limbs::div_rem(&den, &g).0),
+        }
+    }
+
+    pub fn from_integer(n: BigNum) -> BigRational {
+        BigRational::new(n, BigNum::from(1))
+    }
+
+    pub fn zero() -> BigRational {
+        BigRational::from_integer(BigNum::new())
+    }
+
+    pub fn numer(&self) -> &BigNum {
+        &self.num
+    }
+
+    pub fn denom(&self) -> &BigNum {
+        &self.den
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.num.is_zero()
+    }
+
+    pub fn is_integer(&self) -> bool {
+        self.den == BigNum::from(1)
+    }
+
+    pub fn abs(&self) -> BigRational {
+        BigRational {
+            num: BigNum::from_magnitude(false, self.num.magnitude()),
+            den: self.den.clone(),
+        }
+    }
+
+    pub fn recip(&self) -> BigRational {
+        BigRational::new(self.den.clone(), self.num.clone())
+    }
+
+    //the closest BigNum with a relative error below 2^-precision, rounding toward zero
+    pub fn to_bignum(&self, precision: usize) -> BigNum {
+        self.num.div_to_limbs(&self.den, limbs_for(precision))
+    }
+}
+
+//every BigNum is a fraction with a power of two as the denominator
+impl From<BigNum> for BigRational {
+    fn from(value: BigNum) -> Self {
+        let mut num = value;
+        let mut fraction_limbs = 0;
+        while !num.is_integer() {
+            num = num.scale_by_limbs(1);
+            fraction_limbs += 1;
+        }
+        BigRational::new(num, BigNum::from(1).scale_by_limbs(fraction_limbs))
+    }
+}
+
+impl From<i64> for BigRational {
+    fn from(value: i64) -> Self {
+        BigRational::from_integer(BigNum::from(value))
+    }
+}
+
+impl Add for BigRational {
+    type Output = BigRational;
+    fn add(self, rhs: Self) -> Self::Output {
+        &self + &rhs
+    }
+}
+
+impl Add for &BigRational {
+    type Output = BigRational;
+    fn add(self, rhs: Self) -> Self::Output {
+        BigRational::new(
+            &self.num * &rhs.den + &rhs.num * &self.den,
+            &self.den * &rhs.den,
+        )
+    }
+}
+
+impl Add<&BigRational> for BigRational {
+    type Output = BigRational;
+    fn add(self, rhs: &BigRational) -> Self::Output {
+        &self + rhs
+    }
+}
+
+impl Add<BigRational> for &BigRational {
+    type Output = BigRational;
+    fn add(self, rhs: BigRational) -> Self::Output {
+        self + &rhs
+    }
+}
+
+impl Sub for BigRational {
+    type Output = BigRational;
+    fn sub(self, rhs: Self) -> Self::Output {
+        &self - &rhs
+    }
+}
+
+impl Sub for &BigRational {
+    type Output = BigRational;
+    fn sub(self, rhs: Self) -> Self::Output {
+        BigRational::new(
+            &self.num * &rhs.den - &rhs.num * &self.den,
+            &self.den * &rhs.den,
+        )
+    }
+}
+
+impl Sub<&BigRational> for BigRational {
+    type Output = BigRational;
+    fn sub(self, rhs: &BigRational) -> Self::Output {
+        &self - rhs
+    }
+}
+
+impl Sub<BigRational> for &BigRational {
+    type Output = BigRational;
+    fn sub(self, rhs: BigRational) -> Self::Output {
+        self - &rhs
+    }
+}
+
+impl Mul for BigRational {
+    type Output = BigRational;
+    fn mul(self, rhs: Self) -> Self::Output {
+        &self * &rhs
+    }
+}
+
+impl Mul for &BigRational {
+    type Output = BigRational;
+    fn mul(self, rhs: Self) -> Self::Output {
+        BigRational::new(&self.num * &rhs.num, &self.den * &rhs.den)
+    }
+}
+
+impl Mul<&BigRational> for BigRational {
+    type Output = BigRational;
+    fn mul(self, rhs: &BigRational) -> Self::Output {
+        &self * rhs
+    }
+}
+
+impl Mul<BigRational> for &BigRational {
+    type Output = BigRational;
+    fn mul(self, rhs: BigRational) -> Self::Output {
+        self * &rhs
+    }
+}
+
+impl Div for BigRational {
+    type Output = BigRational;
+    fn div(self, rhs: Self) -> Self::Output {
+        &self / &rhs
+    }
+}
+
+impl Div for &BigRational {
+    type Output = BigRational;
+    fn div(self, rhs: Self) -> Self::Output {
+        BigRational::new(&self.num * &rhs.den, &self.den * &rhs.num)
+    }
+}
+
+impl Div<&BigRational> for BigRational {
+    type Output = BigRational;
+    fn div(self, rhs: &BigRational) -> Self::Output {
+        &self / rhs
+    }
+}
+
+impl Div<BigRational> for &BigRational {
+    type Output = BigRational;
+    fn div(self, rhs: BigRational) -> Self::Output {
+        self / &rhs
+    }
+}
+
+impl Neg for BigRational {
+    type Output = BigRational;
+    fn neg(self) -> Self::Output {
+        BigRational {
+            num: -self.num,
+            den: self.den,
+        }
+    }
+}
+
+impl Neg for &BigRational {
+    type Output = BigRational;
+    fn neg(self) -> Self::Output {
+        -self.clone()
+    }
+}
+
+impl PartialOrd for BigRational {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for BigRational {
+    fn cmp(&self, other: &Self) -> Ordering {
+        //both denominators are positive so cross multiplying keeps the order
+        (&self.num * &other.den).cmp(&(&other.num * &self.den))
+    }
+}
+
+impl Display for BigRational {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_integer() {
+            write!(f, "{}", self.num)
+        } else {
+            write!(f, "{}/{}", self.num, self.den)
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use std::{
+    fmt,
+    ops::{BitAnd, BitOr, Not},
+    sync::atomic::{compiler_fence, Ordering},
+};
+
+use crate::big_num::BigNum;
+use crate::modular::ModularError;
+use crate::montgomery::MontgomeryContext;
+
+//a non negative integer held in a fixed number of limbs for use as key material
+//every operation runs the same instructions and touches the same limbs no matter what the
+//values are, only the widths are treated as public, and the limbs are wiped when dropped
+pub struct SecretBigNum {
+    limbs: Vec<u64>,
+}
+
+//the result of a constant time comparison, stored as a mask of all zeros or all ones so it can
+//be used without branching
+#[derive(Debug, Clone, Copy)]
+pub struct Choice(u64);
+
+impl Choice {
+    fn from_bit(bit: u64) -> Choice {
+        Choice(bit.wrapping_neg())
+    }
+
+    //turning a choice into a bool is where the secret stops being secret
+    pub fn declassify(self) -> bool {
+        self.0 != 0
+    }
+}
+
+impl Not for Choice {
+    type Output = Choice;
+    fn not(self) -> Self::Output {
+        Choice(!self.0)
+    }
+}
+
+impl BitAnd for Choice {
+    type Output = Choice;
+    fn bitand(self, rhs: Self) -> Self::Output {
+        Choice(self.0 & rhs.0)
+    }
+}
+
+impl BitOr for Choice {
+    type Output = Choice;
+    fn bitor(self, rhs: Self) -> Self::Output {
+        Choice(self.0 | rhs.0)
+    }
+}
+
+//the tests record every limb primitive that runs so they can check the sequence does not
+//depend on the operands, outside of tests this compiles to nothing
+#[cfg(test)]
+thread_local! {
+    pub(crate) static TRACE: std::cell::RefCell<Vec<&'static str>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+#[inline(always)]
+fn trace(_op: &'static str) {
+    #[cfg(test)]
+    TRACE.with(|trace| trace.borrow_mut().push(_op));
+}
+
+#[inline(always)]
+fn adc(a: u64, b: u64, carry: u64) -> (u64, u64) {
+    trace("adc");
+    let sum = a as u128 + b as u128 + carry as u128;
+    (sum as u64, (sum >> 64) as u64)
+}
+
+#[inline(always)]
+fn sbb(a: u64, b: u64, borrow: u64) -> (u64, u64) {
+    trace("sbb");
+    let diff = (a as u128).wrapping_sub(b as u128 + borrow as u128);
+    (diff as u64, ((diff >> 64) as u64) & 1)
+}
+
+//a + b * c + carry, which never overflows 128 bits
+#[inline(always)]
+fn mac(a: u64, b: u64, c: u64, carry: u64) -> (u64, u64) {
+    trace("mac");
+    let t = a as u128 + (b as u128) * (c as u128) + carry as u128;
+    (t as u64, (t >> 64) as u64)
+}
+
+#[inline(always)]
+fn select_limb(a: u64, b: u64, choice: Choice) -> u64 {
+    trace("select");
+    a ^ ((a ^ b) & choice.0)
+}
+
+fn zeroize(limbs: &mut [u64]) {
+    for limb in limbs.iter_mut() {
+        //volatile so the compiler cannot drop the writes to memory that is about to be freed
+        unsafe { std::ptr::write_volatile(limb, 0) };
+    }
+    compiler_fence(Ordering::SeqCst);
+}
+
+impl SecretBigNum {
+    pub fn zero(width: usize) -> SecretBigNum {
+        SecretBigNum {
+            limbs: vec![0; width],
+        }
+    }
+
+    //None if value is not a non negative integer that fits in width limbs
+    pub fn from_bignum(value: &BigNum, width: usize) -> Option<SecretBigNum> {
+        if !value.is_integer() || value < &BigNum::from(0) {
+            return None;
+        }
+        let mut magnitude = value.magnitude();
+        if magnitude.len() > width {
+            zeroize(&mut magnitude);
+            return None;
+        }
+        //copied into a buffer of the full width, growing magnitude could move it and leave the
+        //old limbs behind unwiped
+        let mut limbs = Vec::with_capacity(width);
+        limbs.extend_from_slice(&magnitude);
+        limbs.resize(width, 0);
+        zeroize(&mut magnitude);
+        Some(SecretBigNum { limbs })
+    }
+
+    pub fn width(&self) -> usize {
+        self.limbs.len()
+    }
+
+    //copies the value out into a normal BigNum, which is no longer protected
+    pub fn expose(&self) -> BigNum {
+        BigNum::from_magnitude(false, self.limbs.clone())
+    }
+
+    fn check_width(&self, rhs: &SecretBigNum) {
+        assert_eq!(self.width(), rhs.width(), "SecretBigNum widths differ");
+    }
+
+    //(self + rhs) mod 2^(64 * width) and the carry out
+    pub fn wrapping_add(&self, rhs: &SecretBigNum) -> (SecretBigNum, Choice) {
+        self.check_width(rhs);
+        let mut result = SecretBigNum::zero(self.width());
+        let mut carry = 0;
+        for i in 0..self.width() {
+            (result.limbs[i], carry) = adc(self.limbs[i], rhs.limbs[i], carry);
+        }
+        (result, Choice::from_bit(carry))
+    }
+
+    //(self - rhs) mod 2^(64 * width) and the borrow out
+    pub fn wrapping_sub(&self, rhs: &SecretBigNum) -> (SecretBigNum, Choice) {
+        self.check_width(rhs);
+        let mut result = SecretBigNum::zero(self.width());
+        let mut borrow = 0;
+        for i in 0..self.width() {
+            (result.limbs[i], borrow) = sbb(self.limbs[i], rhs.limbs[i], borrow);
+        }
+        (result, Choice::from_bit(borrow))
+    }
+
+    //the full product, self.width() + rhs.width() limbs wide
+    pub fn widening_mul(&self, rhs: &SecretBigNum) -> SecretBigNum {
+        let mut result = SecretBigNum::zero(self.width() + rhs.width());
+        for i in 0..self.width() {
+            let mut carry = 0;
+            for j in 0..rhs.width() {
+                (result.limbs[i + j], carry) =
+                    mac(result.limbs[i + j], self.limbs[i], rhs.limbs[j], carry);
+            }
+            result.limbs[i + rhs.width()] = carry;
+        }
+        result
+    }
+
+    pub fn ct_eq(&self, rhs: &SecretBigNum) -> Choice {
+        self.check_width(rhs);
+        let mut diff = 0;
+        for i in 0..self.width() {
+            trace("xor");
+            diff |= self.limbs[i] ^ rhs.limbs[i];
+        }
+        //(diff | -diff) has its top bit set exactly when diff is not zero
+        !Choice::from_bit((diff | diff.wrapping_neg()) >> 63)
+    }
+
+    pub fn ct_lt(&self, rhs: &SecretBigNum) -> Choice {
+        let (_, borrow) = self.wrapping_sub(rhs);
+        borrow
+    }
+
+    pub fn ct_gt(&self, rhs: &SecretBigNum) -> Choice {
+        rhs.ct_lt(self)
+    }
+
+    //b when choice is set, a otherwise
+    pub fn select(a: &SecretBigNum, b: &SecretBigNum, choice: Choice) -> SecretBigNum {
+        a.check_width(b);
+        let mut result = SecretBigNum::zero(a.width());
+        for i in 0..a.width() {
+            result.limbs[i] = select_limb(a.limbs[i], b.limbs[i], choice);
+        }
+        result
+    }
+
+    //self^exp mod modulus, the modulus is public and has to be odd with exactly as many limbs
+    //as self, every bit of exp is processed with a square and a multiply whatever its value
+    pub fn mod_pow(
+        &self,
+        exp: &SecretBigNum,
+        modulus: &BigNum,
+    ) -> Result<SecretBigNum, ModularError> {
+        let ctx = MontgomeryContext::new(modulus)?;
+        assert_eq!(
+            self.width(),
+            ctx.modulus.len(),
+            "SecretBigNum width does not match the modulus"
+        );
+        let n = SecretBigNum {
+            limbs: ctx.modulus.clone(),
+        };
+        let r_squared = SecretBigNum {
+            limbs: ctx.r_squared.clone(),
+        };
+
+        //anything below R can go in, the product with R^2 mod n stays below n * R
+        let base = mont_mul(self, &r_squared, &n, ctx.n_prime);
+        let mut result = SecretBigNum {
+            limbs: ctx.one.clone(),
+        };
+        for i in (0..exp.width() * 64).rev() {
+            result = mont_mul(&result, &result, &n, ctx.n_prime);
+            let product = mont_mul(&result, &base, &n, ctx.n_prime);
+            let bit = (exp.limbs[i / 64] >> (i % 64)) & 1;
+            result = SecretBigNum::select(&result, &product, Choice::from_bit(bit));
+        }
+
+        let mut one = SecretBigNum::zero(self.width());
+        one.limbs[0] = 1;
+        Ok(mont_mul(&result, &one, &n, ctx.n_prime))
+    }
+}
+
+//a * b / R mod n with the final subtraction done by select instead of a branch
+fn mont_mul(a: &SecretBigNum, b: &SecretBigNum, n: &SecretBigNum, n_prime: u64) -> SecretBigNum {
+    let s = n.width();
+    let mut t = SecretBigNum::zero(s + 2);
+    for i in 0..s {
+        let mut carry = 0;
+        for j in 0..s {
+            (t.limbs[j], carry) = mac(t.limbs[j], a.limbs[j], b.limbs[i], carry);
+        }
+        (t.limbs[s], carry) = adc(t.limbs[s], carry, 0);
+        t.limbs[s + 1] = carry;
+
+        let m = t.limbs[0].wrapping_mul(n_prime);
+        let (_, mut carry) = mac(t.limbs[0], m, n.limbs[0], 0);
+        for j in 1..s {
+            (t.limbs[j - 1], carry) = mac(t.limbs[j], m, n.limbs[j], carry);
+        }
+        (t.limbs[s - 1], carry) = adc(t.limbs[s], carry, 0);
+        t.limbs[s] = t.limbs[s + 1] + carry;
+        t.limbs[s + 1] = 0;
+    }
+
+    //t < 2n, subtract n unless that borrows
+    let mut reduced = SecretBigNum::zero(s);
+    let mut borrow = 0;
+    for i in 0..s {
+        (reduced.limbs[i], borrow) = sbb(t.limbs[i], n.limbs[i], borrow);
+    }
+    let (_, borrow) = sbb(t.limbs[s], 0, borrow);
+    let mut result = SecretBigNum::zero(s);
+    for i in 0..s {
+        result.limbs[i] = select_limb(reduced.limbs[i], t.limbs[i], Choice::from_bit(borrow));
+    }
+    result
+}
+
+impl Clone for SecretBigNum {
+    fn clone(&self) -> Self {
+        SecretBigNum {
+            limbs: self.limbs.clone(),
+        }
+    }
+}
+
+impl Drop for SecretBigNum {
+    fn drop(&mut self) {
+        zeroize(&mut self.limbs);
+    }
+}
+
+//never print the value itself
+impl fmt::Debug for SecretBigNum {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SecretBigNum {{ width: {} }}", self.width())
+    }
+}
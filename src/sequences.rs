@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use crate::big_num::BigNum;
+use crate::limbs;
+
+//(F(n), F(n + 1)) by fast doubling:
+//F(2k) = F(k) (2 F(k + 1) - F(k))
+//F(2k + 1) = F(k)^2 + F(k + 1)^2
+fn fibonacci_pair(n: u64) -> (Vec<u64>, Vec<u64>) {
+    let mut a: Vec<u64> = Vec::new();
+    let mut b: Vec<u64> = vec![1];
+    for i in (0..64 - n.leading_zeros()).rev() {
+        let c = limbs::mul(&a, &limbs::sub(&limbs::shl(&b, 1), &a));
+        let d = limbs::add(&limbs::mul(&a, &a), &limbs::mul(&b, &b));
+        if (n >> i) & 1 == 1 {
+            a = d.clone();
+            b = limbs::add(&c, &d);
+        } else {
+            a = c;
+            b = d;
+        }
+    }
+    (a, b)
+}
+
+impl BigNum {
+    //F(0) = 0, F(1) = 1, F(n) = F(n - 1) + F(n - 2)
+    pub fn fibonacci(n: u64) -> BigNum {
+        BigNum::from_magnitude(false, fibonacci_pair(n).0)
+    }
+
+    //L(0) = 2, L(1) = 1, L(n) = L(n - 1) + L(n - 2)
+    pub fn lucas(n: u64) -> BigNum {
+        //L(n) = 2 F(n + 1) - F(n)
+        let (f, f_next) = fibonacci_pair(n);
+        BigNum::from_magnitude(false, limbs::sub(&limbs::shl(&f_next, 1), &f))
+    }
+}
+
+//a(n) = coeffs[0] a(n - 1) + coeffs[1] a(n - 2) + ... + coeffs[k - 1] a(n - k)
+//iterating yields a(0), a(1), ... and nth jumps ahead with matrix exponentiation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearRecurrence {
+    coeffs: Vec<BigNum>,
+    //the next k terms, starting with the one the iterator returns next
+    window: VecDeque<BigNum>,
+}
+
+type Matrix = Vec<Vec<BigNum>>;
+
+fn mat_mul(a: &Matrix, b: &Matrix) -> Matrix {
+    let k = a.len();
+    let mut result = vec![vec![BigNum::new(); k]; k];
+    for i in 0..k {
+        for l in 0..k {
+            if a[i][l].is_zero() {
+                continue;
+            }
+            for j in 0..k {
+                result[i][j] = &result[i][j] + &a[i][l] * &b[l][j];
+            }
+        }
+    }
+    result
+}
+
+impl LinearRecurrence {
+    //initial holds a(0) .. a(k - 1), there has to be one per coefficient
+    pub fn new(coeffs: Vec<BigNum>, initial: Vec<BigNum>) -> LinearRecurrence {
+        assert!(
+            !coeffs.is_empty(),
+            "a linear recurrence needs at least one coefficient"
+        );
+        assert_eq!(
+            coeffs.len(),
+            initial.len(),
+            "a linear recurrence needs one initial term per coefficient"
+        );
+        LinearRecurrence {
+            coeffs,
+            window: initial.into(),
+        }
+    }
+
+    pub fn order(&self) -> usize {
+        self.coeffs.len()
+    }
+
+    //the n-th term counted from the current position, without advancing
+    pub fn nth_term(&self, n: u64) -> BigNum {
+        let k = self.order();
+        if n < k as u64 {
+            return self.window[n as usize].clone();
+        }
+        self.jump(n)[0].clone()
+    }
+
+    //the window moved n steps ahead: the state vector (a(n), ..., a(n + k - 1)) is M^n times
+    //the current one, M being the companion matrix
+    fn jump(&self, n: u64) -> Vec<BigNum> {
+        let k = self.order();
+        let mut companion = vec![vec![BigNum::new(); k]; k];
+        for (i, row) in companion.iter_mut().enumerate().take(k - 1) {
+            row[i + 1] = BigNum::from(1);
+        }
+        for (j, c) in self.coeffs.iter().enumerate() {
+            companion[k - 1][k - 1 - j] = c.clone();
+        }
+
+        let mut power: Matrix = (0..k)
+            .map(|i| (0..k).map(|j| BigNum::from((i == j) as u64)).collect())
+            .collect();
+        for i in (0..64 - n.leading_zeros()).rev() {
+            power = mat_mul(&power, &power);
+            if (n >> i) & 1 == 1 {
+                power = mat_mul(&power, &companion);
+            }
+        }
+
+        power
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .zip(self.window.iter())
+                    .fold(BigNum::new(), |acc, (m, a)| acc + m * a)
+            })
+            .collect()
+    }
+}
+
+impl Iterator for LinearRecurrence {
+    type Item = BigNum;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = self
+            .coeffs
+            .iter()
+            .zip(self.window.iter().rev())
+            .fold(BigNum::new(), |acc, (c, a)| acc + c * a);
+        self.window.push_back(next);
+        self.window.pop_front()
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        if n >= self.order() {
+            self.window = self.jump(n as u64).into();
+        } else {
+            for _ in 0..n {
+                self.next();
+            }
+        }
+        self.next()
+    }
+}
@@ -0,0 +1,412 @@
+use crate::big_num::BigNum;
+use crate::functions::{limbs_for, ln2, negligible, trunc_to_i64, DomainError};
+use crate::limbs;
+use crate::rational::BigRational;
+
+//special functions at a requested number of bits, built on exp, ln, sin and pi from functions.rs
+//the error bound of each public function is written above it, precision is always in bits
+
+fn half() -> BigNum {
+    BigNum::from(1).mul_pow2(-1)
+}
+
+fn abs(x: &BigNum) -> BigNum {
+    if x < &BigNum::from(0) {
+        -x
+    } else {
+        x.clone()
+    }
+}
+
+//bits in the integer part of |x|, at least 1
+fn int_bits(x: &BigNum) -> usize {
+    x.log2_floor().unwrap_or(0).max(0) as usize + 1
+}
+
+//x^y for x > 0
+fn powf(x: &BigNum, y: &BigNum, bits: usize) -> BigNum {
+    (y * x.ln(bits).unwrap()).exp(bits)
+}
+
+//x^n for a non negative integer n, keeping n limbs
+fn pow_int(x: &BigNum, n: u64, limb_count: usize) -> BigNum {
+    let mut result = BigNum::from(1);
+    for i in (0..64 - n.leading_zeros()).rev() {
+        result = (&result * &result).round_to_limbs(limb_count);
+        if (n >> i) & 1 == 1 {
+            result = (&result * x).round_to_limbs(limb_count);
+        }
+    }
+    result
+}
+
+//B_0 .. B_n by the akiyama tanigawa algorithm, with B_1 = -1/2
+fn bernoulli_list(n: usize) -> Vec<BigRational> {
+    let mut a: Vec<BigRational> = Vec::with_capacity(n + 1);
+    let mut result = Vec::with_capacity(n + 1);
+    for m in 0..=n {
+        a.push(BigRational::new(
+            BigNum::from(1),
+            BigNum::from(m as u64 + 1),
+        ));
+        for j in (1..=m).rev() {
+            a[j - 1] = BigRational::from(j as i64) * (&a[j - 1] - &a[j]);
+        }
+        result.push(a[0].clone());
+    }
+    //the algorithm itself gives B_1 = +1/2
+    if n >= 1 {
+        result[1] = -&result[1];
+    }
+    result
+}
+
+//ln(gamma(x)) for x > 0 with an absolute error of a few units of 2^-bits
+fn ln_gamma_positive(x: &BigNum, bits: usize) -> BigNum {
+    let n = limbs_for(bits);
+    //gamma(x) = gamma(x + s) / (x (x + 1) ... (x + s - 1)), shift until the stirling series
+    //converges fast enough
+    let z_min = BigNum::from((bits / 2 + 10) as u64);
+    let mut z = x.clone();
+    let mut shift_product = BigNum::from(1);
+    while z < z_min {
+        shift_product = (&shift_product * &z).round_to_limbs(n);
+        z = z + BigNum::from(1);
+    }
+
+    //ln gamma(z) = (z - 1/2) ln z - z + ln(2 pi) / 2 + sum of B_2k / (2k (2k - 1) z^(2k - 1))
+    //the series is asymptotic and for real z > 0 the error is below the first term left out,
+    //with z >= bits / 2 the terms have fallen below 2^-bits well before k = bits / 8
+    let ln_z = z.ln(bits).unwrap();
+    let ln_2pi = BigNum::pi(bits).mul_pow2(1).ln(bits).unwrap();
+    let mut result = (&z - half()) * &ln_z - &z + ln_2pi.mul_pow2(-1);
+    let z_inv = BigNum::from(1).div_to_limbs(&z, n);
+    let z_inv2 = (&z_inv * &z_inv).round_to_limbs(n);
+    let mut power = z_inv;
+    let bernoulli = bernoulli_list(2 * (bits / 8 + 8));
+    for k in 1..bernoulli.len() / 2 {
+        let coeff = &bernoulli[2 * k] / BigRational::from((2 * k * (2 * k - 1)) as i64);
+        let term = (coeff.numer() * &power).div_to_limbs(coeff.denom(), n);
+        if negligible(&term, bits) {
+            break;
+        }
+        result = (result + term).round_to_limbs(n + 1);
+        power = (&power * &z_inv2).round_to_limbs(n);
+    }
+    result - shift_product.ln(bits).unwrap()
+}
+
+//sin(pi x) with about `bits` correct bits relative to its own size, for x not an integer
+fn sin_pi(x: &BigNum, bits: usize) -> BigNum {
+    //sin(pi x) is about pi times the distance from x to the closest integer, which decides how
+    //many leading bits cancel
+    let frac = abs(&(x - x.get_integer_part_ref()));
+    let distance = if frac > half() {
+        BigNum::from(1) - frac
+    } else {
+        frac
+    };
+    let guard = (-distance.log2_floor().unwrap()).max(0) as usize;
+    let bits = bits + guard + 8;
+    (BigNum::pi(bits + int_bits(x)) * x).sin(bits)
+}
+
+fn pole_check(x: &BigNum) -> Result<(), DomainError> {
+    if x.is_integer() && x <= &BigNum::from(0) {
+        return Err(DomainError::new(
+            "gamma has a pole at non positive integers",
+        ));
+    }
+    Ok(())
+}
+
+//exact integer exponents up to this size are evaluated exactly for gamma
+const EXACT_GAMMA_LIMIT: u64 = 1000;
+
+//borwein's algorithm for 0 < s, s != 1:
+//zeta(s) = sum of (-1)^k (d_n - d_k) / (k + 1)^s over k < n, divided by d_n (1 - 2^(1 - s))
+//with d_k = n * sum over i <= k of (n + i - 1)! 4^i / ((n - i)! (2i)!)
+//the truncation error is below 3 / ((3 + sqrt(8))^n |1 - 2^(1 - s)|), about 2^(-2.54 n)
+fn zeta_borwein(s: &BigNum, bits: usize) -> BigNum {
+    //2^(1 - s) is close to 1 near s = 1 and the difference loses that many bits
+    let one_minus_s = BigNum::from(1) - s;
+    let near_one = (-one_minus_s.log2_floor().unwrap()).max(0) as usize;
+    let bits = bits + near_one + 16;
+    let terms = bits * 2 / 5 + 2;
+    let bits = bits + 64 - (terms as u64).leading_zeros() as usize;
+    let n = limbs_for(bits);
+
+    //the d_k are integers, each summand follows from the previous one by
+    //t_(i + 1) = t_i * 2 (n + i) (n - i) / ((2i + 1) (i + 1))
+    let count = terms as u64;
+    let mut d: Vec<Vec<u64>> = vec![vec![1]];
+    let mut t: Vec<u64> = vec![1];
+    for i in 0..count {
+        t = limbs::mul_small(&limbs::mul_small(&t, 2 * (count + i)), count - i);
+        t = limbs::div_rem_small(&t, 2 * i + 1).0;
+        t = limbs::div_rem_small(&t, i + 1).0;
+        let next = limbs::add(d.last().unwrap(), &t);
+        d.push(next);
+    }
+    let d_n = &d[terms];
+
+    let integer_s = if s.is_integer() {
+        Some(trunc_to_i64(s) as u64)
+    } else {
+        None
+    };
+    let mut sum = BigNum::new();
+    for (k, d_k) in d.iter().take(terms).enumerate() {
+        let base = BigNum::from(k as u64 + 1);
+        let power = match integer_s {
+            Some(s) => BigNum::from(1).div_to_limbs(&pow_int(&base, s, n), n),
+            None => powf(&base, &-s, bits),
+        };
+        let term = (BigNum::from_magnitude(false, limbs::sub(d_n, d_k)) * power).round_to_limbs(n);
+        if k % 2 == 0 {
+            sum = sum + term;
+        } else {
+            sum = sum - term;
+        }
+    }
+    let two_pow = match integer_s {
+        Some(s) => BigNum::from(1).mul_pow2(1 - s as i64),
+        None => (&one_minus_s * ln2(bits)).exp(bits),
+    };
+    let denominator = BigNum::from_magnitude(false, d_n.clone()) * (BigNum::from(1) - two_pow);
+    sum.div_to_limbs(&denominator, n)
+}
+
+impl BigNum {
+    //the n-th bernoulli number, exactly, using the convention B_1 = -1/2
+    pub fn bernoulli(n: u64) -> BigRational {
+        if n > 1 && n % 2 == 1 {
+            return BigRational::zero();
+        }
+        bernoulli_list(n as usize).swap_remove(n as usize)
+    }
+
+    //ln|gamma(x)| with an absolute error below 2^-precision
+    pub fn ln_gamma(&self, precision: usize) -> Result<BigNum, DomainError> {
+        pole_check(self)?;
+        let bits = precision + 64 + 2 * int_bits(self);
+        let result = if self > &BigNum::from(0) {
+            ln_gamma_positive(self, bits)
+        } else {
+            //reflection: |gamma(x)| = pi / (|sin(pi x)| gamma(1 - x))
+            let sin = abs(&sin_pi(self, bits));
+            BigNum::pi(bits).ln(bits).unwrap()
+                - sin.ln(bits).unwrap()
+                - ln_gamma_positive(&(BigNum::from(1) - self), bits)
+        };
+        Ok(result.round_to_limbs(limbs_for(precision)))
+    }
+
+    //gamma(x) with a relative error below 2^-precision, exact for integers up to 1000
+    pub fn gamma(&self, precision: usize) -> Result<BigNum, DomainError> {
+        pole_check(self)?;
+        if self.is_integer() && self <= &BigNum::from(EXACT_GAMMA_LIMIT) {
+            return Ok((self - BigNum::from(1)).factorial().unwrap());
+        }
+        //an absolute error e in ln gamma is a relative error of about e in gamma
+        let bits = precision + 64 + 2 * int_bits(self);
+        if self > &BigNum::from(0) {
+            return Ok(ln_gamma_positive(self, bits).exp(precision));
+        }
+        //reflection: gamma(x) = pi / (sin(pi x) gamma(1 - x))
+        let sin = sin_pi(self, bits);
+        let reflected = ln_gamma_positive(&(BigNum::from(1) - self), bits).exp(bits);
+        Ok(BigNum::pi(bits).div_to_limbs(&(sin * reflected), limbs_for(precision)))
+    }
+
+    //the beta function gamma(a) gamma(b) / gamma(a + b) for a, b > 0, with a relative error
+    //below 2^-precision
+    pub fn beta(&self, b: &BigNum, precision: usize) -> Result<BigNum, DomainError> {
+        if self <= &BigNum::from(0) || b <= &BigNum::from(0) {
+            return Err(DomainError::new("beta needs positive arguments"));
+        }
+        let bits = precision + 64 + 2 * int_bits(&(self + b));
+        let ln_beta = ln_gamma_positive(self, bits) + ln_gamma_positive(b, bits)
+            - ln_gamma_positive(&(self + b), bits);
+        Ok(ln_beta.exp(precision))
+    }
+
+    //riemann zeta with a relative error below 2^-precision
+    //integers are exact through bernoulli numbers where possible:
+    //zeta(2k) = (-1)^(k + 1) B_2k (2 pi)^2k / (2 (2k)!), zeta(-m) = (-1)^m B_(m + 1) / (m + 1)
+    //other s > 0 use borwein's algorithm and s < 0 the functional equation
+    pub fn zeta(&self, precision: usize) -> Result<BigNum, DomainError> {
+        if self == &BigNum::from(1) {
+            return Err(DomainError::new("zeta has a pole at 1"));
+        }
+        //zeta(s) - 1 < 2^(1 - s) for s >= 2
+        if self > &BigNum::from(precision as u64 + 2) {
+            return Ok(BigNum::from(1));
+        }
+        let bits = precision + 64;
+        if self.is_integer() {
+            let s = trunc_to_i64(self);
+            if s == 0 {
+                return Ok(-half());
+            }
+            if s < 0 {
+                let m = s.unsigned_abs();
+                if m.is_multiple_of(2) {
+                    return Ok(BigNum::new());
+                }
+                let value = -BigNum::bernoulli(m + 1) / BigRational::from(m as i64 + 1);
+                return Ok(value.to_bignum(precision));
+            }
+            if s % 2 == 0 && s <= 100 {
+                let n = limbs_for(bits);
+                let s = s as u64;
+                let factor = BigNum::bernoulli(s).abs()
+                    / BigRational::from_integer(BigNum::from(s).factorial().unwrap())
+                    / BigRational::from(2);
+                let two_pi = BigNum::pi(bits + 8).mul_pow2(1);
+                let power = pow_int(&two_pi, s, n);
+                return Ok(
+                    (factor.numer() * power).div_to_limbs(factor.denom(), limbs_for(precision))
+                );
+            }
+        }
+        if self > &BigNum::from(0) {
+            return Ok(zeta_borwein(self, bits).round_to_limbs(limbs_for(precision)));
+        }
+
+        //zeta(s) = 2^s pi^(s - 1) sin(pi s / 2) gamma(1 - s) zeta(1 - s)
+        let bits = bits + 2 * int_bits(self);
+        let one_minus_s = BigNum::from(1) - self;
+        let pi = BigNum::pi(bits);
+        let power = (self * ln2(bits) - &one_minus_s * pi.ln(bits).unwrap()).exp(bits);
+        let sin = sin_pi(&self.clone().mul_pow2(-1), bits);
+        let gamma = ln_gamma_positive(&one_minus_s, bits).exp(bits);
+        let result = power * sin * gamma * zeta_borwein(&one_minus_s, bits);
+        Ok(result.round_to_limbs(limbs_for(precision)))
+    }
+
+    //the error function with a relative error below 2^-precision
+    pub fn erf(&self, precision: usize) -> BigNum {
+        if self.is_zero() {
+            return BigNum::new();
+        }
+        let sign = if self < &BigNum::from(0) {
+            BigNum::from(-1)
+        } else {
+            BigNum::from(1)
+        };
+        //1 - erf(x) < e^(-x^2), so past x^2 > (precision + 2) ln 2 the answer rounds to 1
+        let x2 = self * self;
+        if x2 > BigNum::from((precision as u64 + 2) * 7 / 10) {
+            return sign;
+        }
+
+        //erf(x) = 2 / sqrt(pi) e^(-x^2) sum of 2^n x^(2n + 1) / (1 * 3 * ... * (2n + 1))
+        //the terms are positive and peak around e^(x^2), which is why x^2 / ln 2 guard bits
+        //are carried along
+        let guard = trunc_to_i64(&(&x2 * BigNum::from(3)).mul_pow2(-1)) as usize;
+        let bits = precision + guard + 64;
+        let n = limbs_for(bits);
+        let two_x2 = x2.clone().mul_pow2(1);
+        let mut term = self.clone();
+        let mut sum = self.clone();
+        for i in 1u64.. {
+            term = (&term * &two_x2)
+                .round_to_limbs(n)
+                .div_to_limbs(&BigNum::from(2 * i + 1), n);
+            if term.log2_floor().unwrap() < sum.log2_floor().unwrap() - bits as i64 {
+                break;
+            }
+            sum = (sum + &term).round_to_limbs(n + 1);
+        }
+        let scale = (-x2)
+            .exp(bits)
+            .mul_pow2(1)
+            .div_to_limbs(&BigNum::pi(bits).sqrt(bits).unwrap(), n);
+        (scale * sum).round_to_limbs(limbs_for(precision))
+    }
+
+    //the complementary error function 1 - erf(x) with a relative error below 2^-precision
+    //it is computed as 1 - erf(x), so for large positive x the cost grows like x^2
+    pub fn erfc(&self, precision: usize) -> BigNum {
+        let guard = if self > &BigNum::from(0) {
+            trunc_to_i64(&(self * self * BigNum::from(3)).mul_pow2(-1)) as usize
+        } else {
+            0
+        };
+        let result = BigNum::from(1) - self.erf(precision + guard + 8);
+        result.round_to_limbs(limbs_for(precision))
+    }
+
+    //the regularized incomplete beta function I_x(a, b) for 0 <= x <= 1 and a, b > 0, with an
+    //absolute error below 2^-precision
+    //the continued fraction is stopped once a step changes it by less than 2^-(precision + 64)
+    pub fn incomplete_beta(
+        &self,
+        a: &BigNum,
+        b: &BigNum,
+        precision: usize,
+    ) -> Result<BigNum, DomainError> {
+        if a <= &BigNum::from(0) || b <= &BigNum::from(0) {
+            return Err(DomainError::new("incomplete beta needs positive a and b"));
+        }
+        if self < &BigNum::from(0) || self > &BigNum::from(1) {
+            return Err(DomainError::new("incomplete beta needs 0 <= x <= 1"));
+        }
+        if self.is_zero() || self == &BigNum::from(1) {
+            return Ok(self.clone());
+        }
+        //the continued fraction converges quickly for x < (a + 1) / (a + b + 2), otherwise use
+        //I_x(a, b) = 1 - I_(1 - x)(b, a)
+        if self * (a + b + BigNum::from(2)) > a + BigNum::from(1) {
+            let flipped = (BigNum::from(1) - self).incomplete_beta(b, a, precision + 8)?;
+            return Ok((BigNum::from(1) - flipped).round_to_limbs(limbs_for(precision)));
+        }
+
+        let x = self;
+        let bits = precision + 64 + 2 * int_bits(&(a + b));
+        let n = limbs_for(bits);
+        let one = BigNum::from(1);
+        let tiny = BigNum::from(1).mul_pow2(-2 * bits as i64);
+        let not_tiny = |v: BigNum| if abs(&v) < tiny { tiny.clone() } else { v };
+
+        //modified lentz evaluation of
+        //1 / (1 + d_1 / (1 + d_2 / (1 + ...))) with
+        //d_(2m + 1) = -(a + m) (a + b + m) x / ((a + 2m) (a + 2m + 1))
+        //d_(2m) = m (b - m) x / ((a + 2m - 1) (a + 2m))
+        let qab = a + b;
+        let qap = a + &one;
+        let qam = a - &one;
+        let mut c = one.clone();
+        let mut d = not_tiny(&one - (&qab * x).div_to_limbs(&qap, n));
+        d = one.div_to_limbs(&d, n);
+        let mut h = d.clone();
+        for m in 1u64.. {
+            let m = BigNum::from(m);
+            let m2 = m.clone().mul_pow2(1);
+            let aa = (&m * (b - &m) * x).div_to_limbs(&((&qam + &m2) * (a + &m2)), n);
+            d = not_tiny(&one + &aa * &d);
+            c = not_tiny(&one + aa.div_to_limbs(&c, n));
+            d = one.div_to_limbs(&d, n);
+            h = (&h * &d * &c).round_to_limbs(n);
+
+            let aa = -((a + &m) * (&qab + &m) * x).div_to_limbs(&((a + &m2) * (&qap + &m2)), n);
+            d = not_tiny(&one + &aa * &d);
+            c = not_tiny(&one + aa.div_to_limbs(&c, n));
+            d = one.div_to_limbs(&d, n);
+            let delta = (&d * &c).round_to_limbs(n);
+            h = (&h * &delta).round_to_limbs(n);
+            if negligible(&(delta - &one), precision + 64) {
+                break;
+            }
+        }
+
+        //I_x(a, b) = x^a (1 - x)^b / (a B(a, b)) times the continued fraction
+        let ln_front = a * x.ln(bits).unwrap() + b * (&one - x).ln(bits).unwrap()
+            - ln_gamma_positive(a, bits)
+            - ln_gamma_positive(b, bits)
+            + ln_gamma_positive(&qab, bits);
+        let front = ln_front.exp(bits).div_to_limbs(a, n);
+        Ok((front * h).round_to_limbs(limbs_for(precision)))
+    }
+}
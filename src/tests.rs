@@ -25,7 +25,7 @@ lazy_static! {
     };
 }
 
-#[expect(dead_code)] // this warning is from being used in the lazy_static block only
+#[cfg_attr(not(test), expect(dead_code))] // this warning is from being used in the lazy_static block only
 fn generate_random_bignum<R: Rng>(rng: &mut R, digits: usize, exp: i32) -> BigNum {
     let mut result = BigNum::new();
     let a = BigNum::from(1) + BigNum::from(u64::MAX);
@@ -244,6 +244,7 @@ fn equality() {
 }
 
 #[cfg(test)]
+#[allow(clippy::eq_op)]
 fn test_equality(a: &BigNum) {
     assert_eq!(*a, *a, "a: {:?}", a);
 }
@@ -258,6 +259,7 @@ fn anti_reflexive_property_of_inequality() {
 }
 
 #[cfg(test)]
+#[allow(clippy::eq_op, clippy::nonminimal_bool)]
 fn test_anti_reflexive_property_of_inequality(a: &BigNum) {
     assert!(
         !(a < a),
@@ -488,3 +490,2288 @@ fn test_inequality_sanity_test(a: &BigNum, b: &BigNum) {
         );
     }
 }
+
+#[test]
+fn limb_division() {
+    // a = q * b + r with r < b
+    let mut rng = StdRng::seed_from_u64(7);
+    for a_len in 1..6 {
+        for b_len in 1..=a_len {
+            let a: Vec<u64> = (0..a_len).map(|_| rng.gen()).collect();
+            let mut b: Vec<u64> = (0..b_len).map(|_| rng.gen()).collect();
+            //exercise the small top limb path of the normalization
+            b[b_len - 1] >>= rng.gen_range(0..64);
+            if crate::limbs::bit_len(&b) == 0 {
+                continue;
+            }
+            let (q, r) = crate::limbs::div_rem(&a, &b);
+            assert_eq!(
+                crate::limbs::cmp(&r, &b),
+                std::cmp::Ordering::Less,
+                "remainder too large: {:?} / {:?}",
+                a,
+                b
+            );
+            let back = crate::limbs::add(&crate::limbs::mul(&q, &b), &r);
+            assert_eq!(
+                crate::limbs::cmp(&back, &a),
+                std::cmp::Ordering::Equal,
+                "{:?} / {:?} gave {:?} rem {:?}",
+                a,
+                b,
+                q,
+                r
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+fn test_factorize(n: &str, expected: &[(&str, u32)]) {
+    let factors = crate::parse(n, crate::Base::Decimal).factorize().unwrap();
+    let expected: Vec<(BigNum, u32)> = expected
+        .iter()
+        .map(|(p, k)| (crate::parse(p, crate::Base::Decimal), *k))
+        .collect();
+    assert_eq!(factors, expected, "factorization of {} failed", n);
+}
+
+#[test]
+fn factorize() {
+    test_factorize("1", &[]);
+    test_factorize("2", &[("2", 1)]);
+    test_factorize("360", &[("2", 3), ("3", 2), ("5", 1)]);
+    test_factorize("-12", &[("-1", 1), ("2", 2), ("3", 1)]);
+    test_factorize(
+        "18446744073709551617",
+        &[("274177", 1), ("67280421310721", 1)],
+    );
+    // 2^61 - 1 is prime
+    test_factorize("2305843009213693951", &[("2305843009213693951", 1)]);
+    // two 10 digit primes, out of reach of trial division
+    test_factorize(
+        "1000000016000000063",
+        &[("1000000007", 1), ("1000000009", 1)],
+    );
+    // square of a prime larger than the trial division limit
+    test_factorize("10000001400000049", &[("100000007", 2)]);
+    // (2^64 + 1)(2^61 - 1)(2^89 - 1), 65 digits, where a single p-1 gcd catches all of them
+    test_factorize(
+        "26328072917139296664488773029440593046579494127567196856973787137",
+        &[
+            ("274177", 1),
+            ("67280421310721", 1),
+            ("2305843009213693951", 1),
+            ("618970019642690137449562111", 1),
+        ],
+    );
+}
+
+#[test]
+fn prime_sieve() {
+    use crate::factor::primes_below;
+
+    assert_eq!(primes_below(0).count(), 0);
+    assert_eq!(primes_below(2).count(), 0);
+    assert_eq!(primes_below(3).collect::<Vec<_>>(), vec![2]);
+    // several segments
+    assert_eq!(primes_below(1_000_000).count(), 78498);
+    assert_eq!(primes_below(1_000_000).last(), Some(999983));
+    // a huge bound allocates no more than one segment up front
+    assert_eq!(
+        primes_below(1 << 60).nth(100000),
+        primes_below(2_000_000).nth(100000)
+    );
+}
+
+#[test]
+fn factorize_errors() {
+    use crate::{FactorError, FactorOptions};
+
+    assert_eq!(BigNum::from(0).factorize(), Err(FactorError::Zero));
+    assert_eq!(
+        crate::parse("1.5", crate::Base::Decimal).factorize(),
+        Err(FactorError::NotAnInteger)
+    );
+
+    let no_effort = FactorOptions {
+        trial_limit: 100,
+        pm1_bound: 0,
+        rho_iterations: 0,
+        ecm_curves: 0,
+        ecm_bound: 0,
+    };
+    let n = crate::parse("2000000032000000126", crate::Base::Decimal);
+    assert_eq!(
+        n.factorize_with(&no_effort),
+        Err(FactorError::BudgetExhausted {
+            found: vec![(BigNum::from(2), 1)],
+            remaining: vec![crate::parse("1000000016000000063", crate::Base::Decimal)],
+        })
+    );
+}
+
+#[test]
+fn is_probable_prime() {
+    let primes = [
+        "2",
+        "3",
+        "97",
+        "2305843009213693951",
+        "170141183460469231731687303715884105727",
+    ];
+    for p in primes {
+        assert!(
+            crate::parse(p, crate::Base::Decimal).is_probable_prime(),
+            "{} is prime",
+            p
+        );
+    }
+    let composites = [
+        "0",
+        "1",
+        "-7",
+        "91",
+        "3215031751",
+        "170141183460469231731687303715884105729",
+    ];
+    for n in composites {
+        assert!(
+            !crate::parse(n, crate::Base::Decimal).is_probable_prime(),
+            "{} is composite",
+            n
+        );
+    }
+}
+
+#[test]
+fn extended_gcd() {
+    let values = [
+        0i64,
+        1,
+        -1,
+        12,
+        -18,
+        240,
+        46,
+        -1071,
+        462,
+        i64::MAX,
+        i64::MIN,
+    ];
+    for &a in values.iter() {
+        for &b in values.iter() {
+            let (g, x, y) = BigNum::extended_gcd(BigNum::from(a), BigNum::from(b)).unwrap();
+            assert!(g >= BigNum::from(0));
+            assert_eq!(
+                BigNum::from(a) * &x + BigNum::from(b) * &y,
+                g,
+                "bezout identity failed for {} and {}",
+                a,
+                b
+            );
+            if a != 0 || b != 0 {
+                assert_eq!(reduce(&BigNum::from(a), &g), BigNum::from(0));
+            }
+        }
+    }
+    assert_eq!(
+        BigNum::extended_gcd(BigNum::from(1) / BigNum::from(2), BigNum::from(3)),
+        Err(crate::ModularError::NotAnInteger)
+    );
+}
+
+#[test]
+fn lcm() {
+    assert_eq!(
+        BigNum::lcm(BigNum::from(4), BigNum::from(6)),
+        Ok(BigNum::from(12))
+    );
+    assert_eq!(
+        BigNum::lcm(BigNum::from(-4), BigNum::from(6)),
+        Ok(BigNum::from(12))
+    );
+    assert_eq!(
+        BigNum::lcm(BigNum::from(0), BigNum::from(6)),
+        Ok(BigNum::from(0))
+    );
+}
+
+// n mod m in [0, m), the % operator only subtracts in a loop
+#[cfg(test)]
+fn reduce(n: &BigNum, m: &BigNum) -> BigNum {
+    BigNum::chinese_remainder(&[(n.clone(), m.clone())])
+        .unwrap()
+        .0
+}
+
+#[test]
+fn mod_inverse() {
+    use crate::ModularError;
+
+    assert_eq!(
+        BigNum::from(3).mod_inverse(BigNum::from(11)),
+        Ok(BigNum::from(4))
+    );
+    assert_eq!(
+        BigNum::from(-3).mod_inverse(BigNum::from(11)),
+        Ok(BigNum::from(7))
+    );
+    let p = crate::parse(
+        "170141183460469231731687303715884105727",
+        crate::Base::Decimal,
+    );
+    let a = crate::parse("123456789012345678901234567890", crate::Base::Decimal);
+    let inverse = a.clone().mod_inverse(p.clone()).unwrap();
+    assert_eq!(reduce(&(a * inverse), &p), BigNum::from(1));
+
+    assert_eq!(
+        BigNum::from(6).mod_inverse(BigNum::from(9)),
+        Err(ModularError::NotInvertible {
+            gcd: BigNum::from(3)
+        })
+    );
+    assert_eq!(
+        BigNum::from(6).mod_inverse(BigNum::from(0)),
+        Err(ModularError::InvalidModulus)
+    );
+}
+
+#[test]
+fn jacobi_and_legendre() {
+    use crate::ModularError;
+
+    // rows of the table at https://en.wikipedia.org/wiki/Jacobi_symbol
+    let row_15 = [0, 1, 1, 0, 1, 0, 0, -1, 1, 0, 0, -1, 0, -1, -1];
+    for (a, expected) in row_15.iter().enumerate() {
+        assert_eq!(
+            BigNum::jacobi(BigNum::from(a as u64), BigNum::from(15)),
+            Ok(*expected)
+        );
+    }
+    assert_eq!(BigNum::jacobi(BigNum::from(-1), BigNum::from(7)), Ok(-1));
+    assert_eq!(
+        BigNum::jacobi(BigNum::from(2), BigNum::from(8)),
+        Err(ModularError::EvenModulus)
+    );
+
+    assert_eq!(BigNum::legendre(BigNum::from(2), BigNum::from(7)), Ok(1));
+    assert_eq!(BigNum::legendre(BigNum::from(3), BigNum::from(7)), Ok(-1));
+    assert_eq!(BigNum::legendre(BigNum::from(14), BigNum::from(7)), Ok(0));
+    assert_eq!(
+        BigNum::legendre(BigNum::from(2), BigNum::from(15)),
+        Err(ModularError::NotPrime)
+    );
+}
+
+#[test]
+fn chinese_remainder() {
+    let congruences = [
+        (BigNum::from(2), BigNum::from(3)),
+        (BigNum::from(3), BigNum::from(5)),
+        (BigNum::from(2), BigNum::from(7)),
+    ];
+    assert_eq!(
+        BigNum::chinese_remainder(&congruences),
+        Ok((BigNum::from(23), BigNum::from(105)))
+    );
+
+    // moduli that are not coprime
+    let congruences = [
+        (BigNum::from(3), BigNum::from(4)),
+        (BigNum::from(-1), BigNum::from(6)),
+    ];
+    assert_eq!(
+        BigNum::chinese_remainder(&congruences),
+        Ok((BigNum::from(11), BigNum::from(12)))
+    );
+    let congruences = [
+        (BigNum::from(1), BigNum::from(4)),
+        (BigNum::from(2), BigNum::from(6)),
+    ];
+    assert_eq!(
+        BigNum::chinese_remainder(&congruences),
+        Err(crate::ModularError::NoSolution)
+    );
+    assert_eq!(
+        BigNum::chinese_remainder(&[]),
+        Ok((BigNum::from(0), BigNum::from(1)))
+    );
+}
+
+#[test]
+fn sqrt_mod() {
+    // every residue of a prime where p - 1 has several factors of two
+    let p = BigNum::from(113);
+    for a in 0..113u64 {
+        match BigNum::from(a).sqrt_mod(p.clone()) {
+            Ok(r) => assert_eq!(reduce(&(&r * &r), &p), BigNum::from(a)),
+            Err(e) => {
+                assert_eq!(e, crate::ModularError::NoSolution);
+                assert_eq!(BigNum::legendre(BigNum::from(a), p.clone()), Ok(-1));
+            }
+        }
+    }
+    assert_eq!(
+        BigNum::from(2).sqrt_mod(BigNum::from(7)),
+        Ok(BigNum::from(3))
+    );
+    assert_eq!(
+        BigNum::from(2).sqrt_mod(BigNum::from(9)),
+        Err(crate::ModularError::NotPrime)
+    );
+}
+
+#[test]
+fn montgomery_arithmetic() {
+    use crate::MontgomeryContext;
+
+    let moduli = [
+        "3",
+        "1000000007",
+        "18446744073709551557",
+        "170141183460469231731687303715884105727",
+        "340282366920938463463374607431768211457",
+    ];
+    let values = [0i64, 1, 2, -1, 12345, -987654321, i64::MAX, i64::MIN];
+    for m in moduli {
+        let m = crate::parse(m, crate::Base::Decimal);
+        let ctx = MontgomeryContext::new(&m).unwrap();
+        for &a in values.iter() {
+            let x = ctx.element(&BigNum::from(a)).unwrap();
+            assert_eq!(x.to_bignum(), reduce(&BigNum::from(a), &m));
+            for &b in values.iter() {
+                let y = ctx.element(&BigNum::from(b)).unwrap();
+                let (a, b) = (BigNum::from(a), BigNum::from(b));
+                assert_eq!((&x + &y).to_bignum(), reduce(&(&a + &b), &m));
+                assert_eq!((&x - &y).to_bignum(), reduce(&(&a - &b), &m));
+                assert_eq!((&x * &y).to_bignum(), reduce(&(&a * &b), &m));
+            }
+            assert_eq!((-&x).to_bignum(), reduce(&-BigNum::from(a), &m));
+        }
+    }
+}
+
+#[test]
+fn montgomery_pow_and_inverse() {
+    use crate::{ModularError, MontgomeryContext};
+
+    // fermat's little theorem on the mersenne prime 2^127 - 1
+    let p = crate::parse(
+        "170141183460469231731687303715884105727",
+        crate::Base::Decimal,
+    );
+    let ctx = MontgomeryContext::new(&p).unwrap();
+    let x = ctx.element(&BigNum::from(123456789)).unwrap();
+    assert_eq!(x.pow(&(&p - BigNum::from(1))), ctx.one());
+    assert_eq!(x.pow(&BigNum::from(0)), ctx.one());
+    assert_eq!(x.pow(&BigNum::from(3)), &x * &x * &x);
+    assert_eq!(&x.inv().unwrap() * &x, ctx.one());
+    assert_eq!(
+        x.inv().unwrap().to_bignum(),
+        BigNum::from(123456789).mod_inverse(p).unwrap()
+    );
+
+    let ctx = MontgomeryContext::new(&BigNum::from(15)).unwrap();
+    assert_eq!(
+        ctx.element(&BigNum::from(6)).unwrap().inv(),
+        Err(ModularError::NotInvertible {
+            gcd: BigNum::from(3)
+        })
+    );
+    assert_eq!(
+        MontgomeryContext::new(&BigNum::from(16)),
+        Err(ModularError::EvenModulus)
+    );
+    assert_eq!(
+        MontgomeryContext::new(&BigNum::from(1)),
+        Err(ModularError::InvalidModulus)
+    );
+}
+
+// runs f and returns every limb primitive the secret operations executed
+#[cfg(test)]
+fn secret_trace<F: FnOnce()>(f: F) -> Vec<&'static str> {
+    crate::secret::TRACE.with(|trace| trace.borrow_mut().clear());
+    f();
+    crate::secret::TRACE.with(|trace| trace.take())
+}
+
+#[cfg(test)]
+fn secret(n: &str, width: usize) -> crate::SecretBigNum {
+    crate::SecretBigNum::from_bignum(&crate::parse(n, crate::Base::Decimal), width).unwrap()
+}
+
+#[test]
+fn secret_arithmetic() {
+    use crate::SecretBigNum;
+
+    let a = secret("340282366920938463463374607431768211455", 2);
+    let b = secret("1", 2);
+    let (sum, carry) = a.wrapping_add(&b);
+    assert_eq!(sum.expose(), BigNum::from(0));
+    assert!(carry.declassify());
+    let (diff, borrow) = sum.wrapping_sub(&b);
+    assert_eq!(diff.expose(), a.expose());
+    assert!(borrow.declassify());
+
+    let product = a.widening_mul(&a);
+    assert_eq!(product.width(), 4);
+    assert_eq!(product.expose(), a.expose() * a.expose());
+
+    assert!(a.ct_eq(&a.clone()).declassify());
+    assert!(!a.ct_eq(&b).declassify());
+    assert!(b.ct_lt(&a).declassify());
+    assert!(!a.ct_lt(&b).declassify());
+    assert!(a.ct_gt(&b).declassify());
+    assert_eq!(
+        SecretBigNum::select(&a, &b, a.ct_eq(&b)).expose(),
+        a.expose()
+    );
+    assert_eq!(
+        SecretBigNum::select(&a, &b, b.ct_lt(&a)).expose(),
+        b.expose()
+    );
+
+    assert!(SecretBigNum::from_bignum(&BigNum::from(-1), 1).is_none());
+    assert!(SecretBigNum::from_bignum(&a.expose(), 1).is_none());
+    assert_eq!(format!("{:?}", a), "SecretBigNum { width: 2 }");
+}
+
+#[test]
+fn secret_mod_pow() {
+    let p = crate::parse(
+        "170141183460469231731687303715884105727",
+        crate::Base::Decimal,
+    );
+    let x = secret("123456789", 2);
+    // fermat's little theorem
+    let exp = crate::SecretBigNum::from_bignum(&(&p - BigNum::from(1)), 2).unwrap();
+    assert_eq!(x.mod_pow(&exp, &p).unwrap().expose(), BigNum::from(1));
+
+    let m = crate::MontgomeryContext::new(&p).unwrap();
+    let expected = m
+        .element(&BigNum::from(123456789))
+        .unwrap()
+        .pow(&BigNum::from(65537));
+    let exp = secret("65537", 1);
+    assert_eq!(x.mod_pow(&exp, &p).unwrap().expose(), expected.to_bignum());
+
+    assert_eq!(
+        x.mod_pow(&exp, &BigNum::from(10)).unwrap_err(),
+        crate::ModularError::EvenModulus
+    );
+}
+
+#[test]
+fn secret_operations_do_not_depend_on_values() {
+    use crate::SecretBigNum;
+
+    let small = [secret("0", 4), secret("1", 4), secret("2", 4)];
+    let large = [
+        secret(
+            "115792089237316195423570985008687907853269984665640564039457584007913129639935",
+            4,
+        ),
+        secret(
+            "57896044618658097711785492504343953926634992332820282019728792003956564819968",
+            4,
+        ),
+        secret("1", 4),
+    ];
+    let modulus = crate::parse(
+        "115792089237316195423570985008687907853269984665640564039457584007913129639747",
+        crate::Base::Decimal,
+    );
+
+    let run = |values: &[SecretBigNum; 3]| {
+        let [a, b, c] = values;
+        secret_trace(|| {
+            let _ = a.wrapping_add(b);
+            let _ = a.wrapping_sub(b);
+            let _ = a.widening_mul(b);
+            let _ = a.ct_eq(b);
+            let _ = a.ct_lt(b);
+            let _ = SecretBigNum::select(a, b, c.ct_eq(a));
+            let _ = a.mod_pow(b, &modulus).unwrap();
+            let _ = c.mod_pow(a, &modulus).unwrap();
+        })
+    };
+    let trace = run(&small);
+    assert!(!trace.is_empty());
+    assert_eq!(trace, run(&large));
+}
+
+#[test]
+fn random_below() {
+    use crate::UniformBelow;
+    use rand::distributions::Distribution;
+
+    let mut rng = StdRng::seed_from_u64(1);
+    let bound = crate::parse("100000000000000000000000000000", crate::Base::Decimal);
+    let dist = UniformBelow::new(&bound);
+    for _ in 0..200 {
+        let x = dist.sample(&mut rng);
+        assert!(
+            x >= BigNum::from(0) && x < bound && x.is_integer(),
+            "{:?}",
+            x
+        );
+    }
+
+    // every value of a small bound shows up
+    let mut seen = [0; 7];
+    for _ in 0..700 {
+        seen[BigNum::random_below(&mut rng, &BigNum::from(7)).to_u64() as usize] += 1;
+    }
+    assert!(seen.iter().all(|&count| count > 50), "{:?}", seen);
+}
+
+#[test]
+fn random_bits() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let two = BigNum::from(2);
+    for bits in [0, 1, 63, 64, 65, 200] {
+        let x = BigNum::random_bits(&mut rng, bits);
+        assert!(x >= BigNum::from(0) && x < two.clone().pow(BigNum::from(bits as u64)));
+        assert!(x.is_integer());
+    }
+    // the top bit is as likely as any other, so the full width gets used
+    let half = two.clone().pow(BigNum::from(199));
+    let high = (0..100)
+        .filter(|_| BigNum::random_bits(&mut rng, 200) >= half)
+        .count();
+    assert!((25..75).contains(&high), "{}", high);
+    assert_eq!(BigNum::random_bits(&mut rng, 0), BigNum::from(0));
+}
+
+#[test]
+fn random_fraction() {
+    let mut rng = StdRng::seed_from_u64(2);
+    let two = BigNum::from(2);
+    for bits in [0, 1, 63, 64, 65, 200] {
+        let f = BigNum::random_fraction(&mut rng, bits);
+        assert!(f >= BigNum::from(0) && f < BigNum::from(1), "{:?}", f);
+        // f is a multiple of 2^-bits
+        assert!((f * two.clone().pow(BigNum::from(bits as u64))).is_integer());
+    }
+    let f = BigNum::random_fraction(&mut rng, 1);
+    assert!(f == BigNum::from(0) || f == BigNum::from(1) / BigNum::from(2));
+}
+
+#[test]
+fn random_gen_range() {
+    let mut rng = StdRng::seed_from_u64(3);
+    let low = BigNum::from(-5);
+    let high = BigNum::from(5);
+    let mut seen_low = false;
+    let mut seen_high = false;
+    for _ in 0..500 {
+        let x = rng.gen_range(low.clone()..high.clone());
+        assert!(x >= low && x < high);
+        let y = rng.gen_range(low.clone()..=high.clone());
+        assert!(y >= low && y <= high);
+        seen_low |= y == low;
+        seen_high |= y == high;
+    }
+    assert!(seen_low && seen_high);
+
+    let big = crate::parse(
+        "1000000000000000000000000000000000000000",
+        crate::Base::Decimal,
+    );
+    let x = BigNum::random_range(&mut rng, &big, &(&big + BigNum::from(10)));
+    assert!(x >= big && x < &big + BigNum::from(10));
+}
+
+#[cfg(test)]
+fn decimal(n: &str) -> BigNum {
+    crate::parse(n, crate::Base::Decimal)
+}
+
+#[test]
+fn factorial() {
+    assert_eq!(BigNum::from(0).factorial(), Ok(BigNum::from(1)));
+    assert_eq!(BigNum::from(1).factorial(), Ok(BigNum::from(1)));
+    assert_eq!(
+        BigNum::from(20).factorial(),
+        Ok(BigNum::from(2432902008176640000u64))
+    );
+    assert_eq!(
+        BigNum::from(50).factorial(),
+        Ok(decimal(
+            "30414093201713378043612608166064768844377641568960512000000000000"
+        ))
+    );
+
+    // n! = n * (n - 1)!
+    let f999 = BigNum::from(999).factorial().unwrap();
+    let f1000 = BigNum::from(1000).factorial().unwrap();
+    assert_eq!(f1000, f999 * BigNum::from(1000));
+
+    assert_eq!(
+        BigNum::from(25).double_factorial(),
+        Ok(BigNum::from(7905853580625u64))
+    );
+    assert_eq!(
+        BigNum::from(26).double_factorial(),
+        Ok(BigNum::from(51011754393600u64))
+    );
+    assert_eq!(BigNum::from(0).double_factorial(), Ok(BigNum::from(1)));
+
+    use crate::CombinatoricsError;
+    assert_eq!(
+        BigNum::from(-1).factorial(),
+        Err(CombinatoricsError::Negative)
+    );
+    assert_eq!(
+        decimal("2.5").factorial(),
+        Err(CombinatoricsError::NotAnInteger)
+    );
+    assert_eq!(
+        decimal("100000000000000000000").factorial(),
+        Err(CombinatoricsError::TooLarge)
+    );
+    //in range of a u64 but far too big to build
+    let huge = BigNum::from(10000000000000u64);
+    assert_eq!(huge.factorial(), Err(CombinatoricsError::TooLarge));
+    assert_eq!(huge.double_factorial(), Err(CombinatoricsError::TooLarge));
+    assert_eq!(
+        BigNum::from(1000000).factorial(),
+        Err(CombinatoricsError::TooLarge)
+    );
+}
+
+#[test]
+fn binomial() {
+    assert_eq!(
+        BigNum::from(5).binomial(&BigNum::from(2)),
+        Ok(BigNum::from(10))
+    );
+    assert_eq!(
+        BigNum::from(5).binomial(&BigNum::from(0)),
+        Ok(BigNum::from(1))
+    );
+    assert_eq!(
+        BigNum::from(5).binomial(&BigNum::from(6)),
+        Ok(BigNum::from(0))
+    );
+    assert_eq!(
+        BigNum::from(200).binomial(&BigNum::from(100)),
+        Ok(decimal(
+            "90548514656103281165404177077484163874504589675413336841320"
+        ))
+    );
+
+    // C(5000, 2500) * 2500! * 2500! = 5000!
+    let c = BigNum::from(5000).binomial(&BigNum::from(2500)).unwrap();
+    let f2500 = BigNum::from(2500).factorial().unwrap();
+    assert_eq!(c * &f2500 * &f2500, BigNum::from(5000).factorial().unwrap());
+
+    assert_eq!(
+        BigNum::multinomial(&[BigNum::from(2), BigNum::from(3), BigNum::from(5)]),
+        Ok(BigNum::from(2520))
+    );
+    assert_eq!(BigNum::multinomial(&[]), Ok(BigNum::from(1)));
+
+    //the whole u64 range, and large n with small k without sieving up to n
+    let max = BigNum::from(u64::MAX);
+    assert_eq!(max.binomial(&BigNum::from(1)), Ok(max.clone()));
+    assert_eq!(max.binomial(&(&max - BigNum::from(1))), Ok(max.clone()));
+    assert_eq!(
+        max.binomial(&BigNum::from(3)),
+        Ok(decimal(
+            "1046183622564446793632349203613672605920836997447371718655"
+        ))
+    );
+    let ten_billion = BigNum::from(10000000000u64);
+    assert_eq!(
+        ten_billion.binomial(&BigNum::from(3)),
+        Ok(decimal("166666666616666666670000000000"))
+    );
+    assert_eq!(
+        ten_billion.binomial(&BigNum::from(5000000000u64)),
+        Err(crate::CombinatoricsError::TooLarge)
+    );
+    assert_eq!(max.catalan(), Err(crate::CombinatoricsError::TooLarge));
+}
+
+#[test]
+fn catalan_stirling_partitions() {
+    let catalan = [1u64, 1, 2, 5, 14, 42, 132, 429, 1430, 4862];
+    for (n, expected) in catalan.iter().enumerate() {
+        assert_eq!(
+            BigNum::from(n as u64).catalan(),
+            Ok(BigNum::from(*expected))
+        );
+    }
+    assert_eq!(
+        BigNum::from(100).catalan(),
+        Ok(decimal(
+            "896519947090131496687170070074100632420837521538745909320"
+        ))
+    );
+
+    // S(n, k) for n = 0..6
+    let stirling2 = [
+        vec![1u64],
+        vec![0, 1],
+        vec![0, 1, 1],
+        vec![0, 1, 3, 1],
+        vec![0, 1, 7, 6, 1],
+        vec![0, 1, 15, 25, 10, 1],
+        vec![0, 1, 31, 90, 65, 15, 1],
+    ];
+    for (n, row) in stirling2.iter().enumerate() {
+        for (k, expected) in row.iter().enumerate() {
+            assert_eq!(
+                BigNum::from(n as u64).stirling2(&BigNum::from(k as u64)),
+                Ok(BigNum::from(*expected)),
+                "S({}, {})",
+                n,
+                k
+            );
+        }
+    }
+    assert_eq!(
+        BigNum::from(3).stirling2(&BigNum::from(4)),
+        Ok(BigNum::from(0))
+    );
+    // S(20, 10) = 5917584964655, with a binomial row past one limb
+    assert_eq!(
+        BigNum::from(20).stirling2(&BigNum::from(10)),
+        Ok(BigNum::from(5917584964655u64))
+    );
+    let million = BigNum::from(1000000);
+    assert_eq!(million.stirling2(&million), Ok(BigNum::from(1)));
+    assert_eq!(million.stirling2(&BigNum::from(1)), Ok(BigNum::from(1)));
+    assert_eq!(
+        (&million + BigNum::from(1)).stirling2(&million),
+        Err(crate::CombinatoricsError::TooLarge)
+    );
+
+    let partitions = [1u64, 1, 2, 3, 5, 7, 11, 15, 22, 30, 42];
+    for (n, expected) in partitions.iter().enumerate() {
+        assert_eq!(
+            BigNum::from(n as u64).partitions(),
+            Ok(BigNum::from(*expected))
+        );
+    }
+    assert_eq!(
+        BigNum::from(1000).partitions(),
+        Ok(decimal("24061467864032622473692149727991"))
+    );
+    assert_eq!(
+        BigNum::from(100000000).partitions(),
+        Err(crate::CombinatoricsError::TooLarge)
+    );
+}
+
+#[test]
+fn fibonacci_and_lucas() {
+    let mut f = (BigNum::from(0), BigNum::from(1));
+    let mut l = (BigNum::from(2), BigNum::from(1));
+    for n in 0..200 {
+        assert_eq!(BigNum::fibonacci(n), f.0, "F({})", n);
+        assert_eq!(BigNum::lucas(n), l.0, "L({})", n);
+        f = (f.1.clone(), f.0 + f.1);
+        l = (l.1.clone(), l.0 + l.1);
+    }
+    assert_eq!(BigNum::fibonacci(100), decimal("354224848179261915075"));
+
+    // F(2n) = F(n) L(n)
+    let n = 50000;
+    assert_eq!(
+        BigNum::fibonacci(2 * n),
+        BigNum::fibonacci(n) * BigNum::lucas(n)
+    );
+    // the last 30 digits of F(100000)
+    let last_digits = BigNum::chinese_remainder(&[(
+        BigNum::fibonacci(100000),
+        decimal("1000000000000000000000000000000"),
+    )])
+    .unwrap()
+    .0;
+    assert_eq!(last_digits, decimal("289236362349895374653428746875"));
+}
+
+#[test]
+fn linear_recurrence() {
+    use crate::LinearRecurrence;
+
+    let fib = LinearRecurrence::new(
+        vec![BigNum::from(1), BigNum::from(1)],
+        vec![BigNum::from(0), BigNum::from(1)],
+    );
+    let terms: Vec<BigNum> = fib.clone().take(10).collect();
+    let expected: Vec<BigNum> = [0u64, 1, 1, 2, 3, 5, 8, 13, 21, 34]
+        .iter()
+        .map(|&x| BigNum::from(x))
+        .collect();
+    assert_eq!(terms, expected);
+    assert_eq!(fib.nth_term(1000), BigNum::fibonacci(1000));
+
+    // a(n) = 2 a(n - 1) - a(n - 2) + 3 a(n - 3) with a negative coefficient
+    let rec = LinearRecurrence::new(
+        vec![BigNum::from(2), BigNum::from(-1), BigNum::from(3)],
+        vec![BigNum::from(1), BigNum::from(-2), BigNum::from(5)],
+    );
+    let by_iteration: Vec<BigNum> = rec.clone().take(60).collect();
+    for (n, term) in by_iteration.iter().enumerate() {
+        assert_eq!(&rec.nth_term(n as u64), term, "a({})", n);
+    }
+
+    // nth jumps ahead and the iterator keeps going from there
+    let mut iter = rec.clone();
+    assert_eq!(iter.next().as_ref(), Some(&by_iteration[0]));
+    assert_eq!(iter.nth(40).as_ref(), Some(&by_iteration[41]));
+    assert_eq!(iter.next().as_ref(), Some(&by_iteration[42]));
+    assert_eq!(iter.nth(1).as_ref(), Some(&by_iteration[44]));
+}
+
+//checks that value * 2^128 rounds to expected, give or take a few units
+#[cfg(test)]
+fn assert_close(value: BigNum, expected: &str) {
+    let diff = value.mul_pow2(128) - decimal(expected);
+    assert!(
+        diff <= BigNum::from(4) && diff >= BigNum::from(-4),
+        "off by {} units of 2^-128",
+        diff
+    );
+}
+
+#[test]
+fn elementary_functions() {
+    let half = BigNum::from(1).mul_pow2(-1);
+    assert_close(BigNum::pi(160), "1069028584064966747859680373161870783301");
+    assert_close(
+        BigNum::from(10).exp(160),
+        "7495217915559919573679589385952004519405958",
+    );
+    assert_close(
+        BigNum::from(-7).mul_pow2(-1).exp(160),
+        "10275637105765648526871610556406864783",
+    );
+    assert_close(
+        BigNum::from(10).ln(160).unwrap(),
+        "783529105480883066805338482703447369892",
+    );
+    assert_close(
+        BigNum::from(1).mul_pow2(-10).ln(160).unwrap(),
+        "-2358657632255132941379441427641544843993",
+    );
+    assert_close(
+        BigNum::from(2).sqrt(160).unwrap(),
+        "481231938336009023090067544955250113854",
+    );
+    assert_close(
+        BigNum::from(100).sin(160),
+        "-172307298884267183469027072999536562403",
+    );
+    assert_close(
+        BigNum::from(100).cos(160),
+        "293431906902647539016485327742659857132",
+    );
+    assert_close(half.sin(160), "163140057038583953344548562349566324122");
+    assert!(BigNum::from(0).ln(64).is_err());
+    assert!(BigNum::from(-4).sqrt(64).is_err());
+}
+
+#[test]
+fn gamma_and_ln_gamma() {
+    let half = BigNum::from(1).mul_pow2(-1);
+    assert_eq!(BigNum::from(5).gamma(64), Ok(BigNum::from(24)));
+    assert_close(
+        half.gamma(160).unwrap(),
+        "603134791644261162232608214451700185152",
+    );
+    assert_close(
+        BigNum::from(-5).mul_pow2(-1).gamma(160).unwrap(),
+        "-321671888876939286524057714374240098748",
+    );
+    assert_close(
+        BigNum::from(201).mul_pow2(-1).ln_gamma(160).unwrap(),
+        "122990141199724007104123196077474013827744",
+    );
+    assert!(BigNum::from(0).gamma(64).is_err());
+    assert!(BigNum::from(-3).ln_gamma(64).is_err());
+}
+
+#[test]
+fn zeta_and_bernoulli() {
+    use crate::BigRational;
+
+    assert_eq!(BigNum::bernoulli(0), BigRational::from(1));
+    assert_eq!(BigNum::bernoulli(1).to_string(), "-1/2");
+    assert_eq!(BigNum::bernoulli(3), BigRational::zero());
+    assert_eq!(BigNum::bernoulli(12).to_string(), "-691/2730");
+    assert_eq!(BigNum::bernoulli(20).to_string(), "-174611/330");
+
+    assert_close(
+        BigNum::from(2).zeta(160).unwrap(),
+        "559742057695999706728347712798405795005",
+    );
+    assert_close(
+        BigNum::from(3).zeta(160).unwrap(),
+        "409038768180800056427241407891448561435",
+    );
+    assert_close(
+        BigNum::from(10).zeta(160).unwrap(),
+        "340620803299513096449500218927115666899",
+    );
+    assert_close(
+        BigNum::from(5).mul_pow2(-1).zeta(160).unwrap(),
+        "456484459091619967046037299838617598217",
+    );
+    assert_close(
+        BigNum::from(1).mul_pow2(-1).zeta(160).unwrap(),
+        "-496932888801390681627350104531279378850",
+    );
+    assert_close(
+        BigNum::from(-3).mul_pow2(-1).zeta(160).unwrap(),
+        "-8672164820530359487350271079698245801",
+    );
+    assert_eq!(BigNum::from(0).zeta(64), Ok(BigNum::from(-1).mul_pow2(-1)));
+    assert_eq!(BigNum::from(-2).zeta(64), Ok(BigNum::from(0)));
+    assert_close(
+        BigNum::from(-1).zeta(160).unwrap(),
+        "-28356863910078205288614550619314017621",
+    );
+    assert!(BigNum::from(1).zeta(64).is_err());
+}
+
+#[test]
+fn erf_and_incomplete_beta() {
+    assert_close(
+        BigNum::from(1).erf(160),
+        "286756220431080668183289216208796350931",
+    );
+    assert_close(
+        BigNum::from(-1).mul_pow2(-2).erf(160),
+        "-94028998089166402780659537876880198487",
+    );
+    assert_close(
+        BigNum::from(3).erfc(160),
+        "7517006605138541006502114289307812",
+    );
+    assert_close(
+        BigNum::from(-1).erfc(160),
+        "627038587352019131646663823640564562387",
+    );
+    assert_eq!(BigNum::from(100).erf(64), BigNum::from(1));
+
+    assert_close(
+        BigNum::from(3)
+            .mul_pow2(-3)
+            .incomplete_beta(&BigNum::from(5).mul_pow2(-1), &BigNum::from(3), 160)
+            .unwrap(),
+        "125168900218073865038811243259346465674",
+    );
+    assert_close(
+        BigNum::from(7)
+            .mul_pow2(-3)
+            .incomplete_beta(&BigNum::from(2), &BigNum::from(1).mul_pow2(-1), 160)
+            .unwrap(),
+        "167339639081435220790381583463475201790",
+    );
+    assert!(BigNum::from(2)
+        .incomplete_beta(&BigNum::from(1), &BigNum::from(1), 64)
+        .is_err());
+}
+
+#[test]
+fn interval_arithmetic() {
+    use crate::BigInterval;
+
+    let a = BigInterval::new(BigNum::from(1), BigNum::from(2));
+    let b = BigInterval::new(BigNum::from(-3), BigNum::from(4));
+    assert_eq!(&a + &b, BigInterval::new(BigNum::from(-2), BigNum::from(6)));
+    assert_eq!(&a - &b, BigInterval::new(BigNum::from(-3), BigNum::from(5)));
+    assert_eq!(&a * &b, BigInterval::new(BigNum::from(-6), BigNum::from(8)));
+    assert_eq!(-&a, BigInterval::new(BigNum::from(-2), BigNum::from(-1)));
+    assert_eq!(a.width(), BigNum::from(1));
+    assert_eq!(a.midpoint(), BigNum::from(3).mul_pow2(-1));
+    assert!(b.contains(&BigNum::from(0)));
+    assert!(!a.contains(&BigNum::from(0)));
+    assert!(b.encloses(&BigInterval::point(BigNum::from(4))));
+    assert_eq!(
+        a.intersect(&b),
+        Some(BigInterval::new(BigNum::from(1), BigNum::from(2)))
+    );
+    assert_eq!(a.to_string(), "[1, 2]");
+    //printed bounds are rounded outward, not truncated
+    let tiny = BigInterval::point(BigNum::from(1).mul_pow2(-64));
+    assert_eq!(
+        tiny.to_string(),
+        "[0.0000000000000000000, 0.0000000000000000001]"
+    );
+    assert_eq!(
+        (-tiny).to_string(),
+        "[-0.0000000000000000001, 0.0000000000000000000]"
+    );
+    let exact = BigInterval::new(decimal("-2.5"), decimal("0.125"));
+    assert_eq!(exact.to_string(), "[-2.5, 0.125]");
+
+    //1/3 is not a BigNum, the interval has to straddle it tightly
+    let third = BigInterval::point(BigNum::from(1)) / BigInterval::point(BigNum::from(3));
+    assert!(third.lo() * BigNum::from(3) < BigNum::from(1));
+    assert!(third.hi() * BigNum::from(3) > BigNum::from(1));
+    assert!(third.width() < BigNum::from(1).mul_pow2(-64));
+    let printed = third.to_string();
+    assert!(
+        printed.starts_with("[0.3333333333333333333")
+            && printed.contains(", 0.3333333333333333333"),
+        "{}",
+        printed
+    );
+    assert!(a.checked_div(&b, 64).is_none());
+    assert_eq!(
+        a.checked_div(&BigInterval::point(BigNum::from(2)), 64),
+        Some(BigInterval::new(
+            BigNum::from(1).mul_pow2(-1),
+            BigNum::from(1)
+        ))
+    );
+}
+
+#[test]
+fn interval_sqrt_and_pow() {
+    use crate::BigInterval;
+
+    let two = BigInterval::point(BigNum::from(2));
+    let root = two.sqrt(256).unwrap();
+    assert!(root.lo() * root.lo() < BigNum::from(2));
+    assert!(root.hi() * root.hi() > BigNum::from(2));
+    assert!(root.width() < BigNum::from(1).mul_pow2(-250));
+    assert_eq!(
+        BigInterval::new(BigNum::from(-1), BigNum::from(9))
+            .sqrt(64)
+            .unwrap(),
+        BigInterval::new(BigNum::from(0), BigNum::from(3))
+    );
+    assert!(BigInterval::point(BigNum::from(-1)).sqrt(64).is_err());
+
+    let b = BigInterval::new(BigNum::from(-3), BigNum::from(2));
+    assert_eq!(
+        b.pow(&BigNum::from(2), 64).unwrap(),
+        BigInterval::new(BigNum::from(0), BigNum::from(9))
+    );
+    assert_eq!(
+        b.pow(&BigNum::from(3), 64).unwrap(),
+        BigInterval::new(BigNum::from(-27), BigNum::from(8))
+    );
+    assert!(b.pow(&BigNum::from(-1), 64).is_err());
+    assert_eq!(
+        b.pow(&BigNum::from(0), 64).unwrap(),
+        BigInterval::point(BigNum::from(1))
+    );
+
+    //2^(1/2) through exp and ln has to enclose the exact square root
+    let fractional = two.pow(&BigNum::from(1).mul_pow2(-1), 128).unwrap();
+    assert!(fractional.lo() * fractional.lo() < BigNum::from(2));
+    assert!(fractional.hi() * fractional.hi() > BigNum::from(2));
+}
+
+#[test]
+fn atan() {
+    assert_close(
+        BigNum::from(1).mul_pow2(-1).atan(160),
+        "157771105808028117754327952606648682114",
+    );
+    assert_close(
+        BigNum::from(-3).atan(160),
+        "-425028251824269804719248045897116377939",
+    );
+    assert_close(
+        BigNum::from(-1).atan2(&BigNum::from(-1), 160),
+        "-801771438048725060894760279871403087475",
+    );
+    assert_eq!(
+        BigNum::from(0).atan2(&BigNum::from(-1), 128),
+        BigNum::pi(128)
+    );
+}
+
+#[test]
+fn complex_arithmetic() {
+    use crate::BigComplex;
+
+    let a = BigComplex::new(BigNum::from(3), BigNum::from(4));
+    let b = BigComplex::new(BigNum::from(1), BigNum::from(-2));
+    assert_eq!(&a + &b, BigComplex::new(BigNum::from(4), BigNum::from(2)));
+    assert_eq!(&a - &b, BigComplex::new(BigNum::from(2), BigNum::from(6)));
+    assert_eq!(&a * &b, BigComplex::new(BigNum::from(11), BigNum::from(-2)));
+    assert_eq!((&a * &b) / &b, a);
+    assert_eq!(
+        BigComplex::i() * BigComplex::i(),
+        BigComplex::from(BigNum::from(-1))
+    );
+    assert_eq!(a.conj(), BigComplex::new(BigNum::from(3), BigNum::from(-4)));
+    assert_eq!(a.norm_sqr(), BigNum::from(25));
+    assert_eq!(a.abs(128), BigNum::from(5));
+    assert_eq!(a.to_string(), "3+4i");
+    assert_eq!(b.to_string(), "1-2i");
+
+    assert_eq!(
+        a.sqrt(128),
+        BigComplex::new(BigNum::from(2), BigNum::from(1))
+    );
+    assert_eq!(
+        BigComplex::from(BigNum::from(-4)).sqrt(128),
+        BigComplex::new(BigNum::from(0), BigNum::from(2))
+    );
+    assert_eq!(
+        BigComplex::new(BigNum::from(3), BigNum::from(-4)).sqrt(128),
+        BigComplex::new(BigNum::from(2), BigNum::from(-1))
+    );
+}
+
+#[test]
+fn complex_functions() {
+    use crate::BigComplex;
+
+    let a = BigComplex::new(BigNum::from(3), BigNum::from(4));
+    assert_close(a.arg(160), "315542211616056235508655905213297364227");
+    let log = a.ln(160).unwrap();
+    assert_close(log.re, "547663342255369772667394339939292885492");
+    assert_close(log.im, "315542211616056235508655905213297364227");
+    assert!(BigComplex::default().ln(64).is_err());
+
+    let e = BigComplex::new(BigNum::from(1), BigNum::from(2)).exp(160);
+    assert_close(e.re, "-384928905176110140546489571897874505690");
+    assert_close(e.im, "841085002331413911508630434227963017393");
+
+    //i^i = e^(-pi / 2)
+    let i_to_i = BigComplex::i().pow(&BigComplex::i(), 160).unwrap();
+    assert_close(i_to_i.re, "70737754275159205772921618435239563690");
+    assert_close(i_to_i.im, "0");
+    let p = BigComplex::new(BigNum::from(1), BigNum::from(1))
+        .pow(
+            &BigComplex::new(BigNum::from(1).mul_pow2(-1), BigNum::from(1)),
+            160,
+        )
+        .unwrap();
+    assert_close(p.re, "136339932929682231535245860291596831286");
+    assert_close(p.im, "124308846395181884656463732847495144351");
+
+    let (r, theta) = a.to_polar(256);
+    let back = BigComplex::from_polar(&r, &theta, 160);
+    assert_close(back.re, "1020847100762815390390123822295304634368");
+    assert_close(back.im, "1361129467683753853853498429727072845824");
+}
+
+#[cfg(test)]
+fn int_matrix(rows: &[&[i64]]) -> crate::BigMatrix {
+    crate::BigMatrix::from_rows(
+        rows.iter()
+            .map(|row| row.iter().map(|&x| BigNum::from(x)).collect())
+            .collect(),
+    )
+}
+
+#[test]
+fn matrix_arithmetic() {
+    let a = int_matrix(&[&[1, 2, 3], &[4, 5, 6]]);
+    let b = int_matrix(&[&[7, 8], &[9, 10], &[11, 12]]);
+    assert_eq!(&a * &b, int_matrix(&[&[58, 64], &[139, 154]]));
+    assert_eq!(a.transpose(), int_matrix(&[&[1, 4], &[2, 5], &[3, 6]]));
+    assert_eq!(&a + &a, int_matrix(&[&[2, 4, 6], &[8, 10, 12]]));
+    assert_eq!(&a - &a, int_matrix(&[&[0, 0, 0], &[0, 0, 0]]));
+    assert_eq!(a[(1, 2)], BigNum::from(6));
+    assert_eq!(a.to_string(), "[1, 2, 3]\n[4, 5, 6]");
+}
+
+#[test]
+fn matrix_determinant() {
+    use crate::MatrixError;
+
+    assert_eq!(
+        int_matrix(&[&[2, -3, 1], &[2, 0, -1], &[1, 4, 5]]).determinant(),
+        Ok(BigNum::from(49))
+    );
+    assert_eq!(
+        int_matrix(&[&[0, 2], &[3, 4]]).determinant(),
+        Ok(BigNum::from(-6))
+    );
+    assert_eq!(
+        int_matrix(&[&[1, 2, 3], &[2, 4, 6], &[1, 0, 1]]).determinant(),
+        Ok(BigNum::from(0))
+    );
+    //vandermonde on 1..6, the product of all differences
+    let vandermonde: Vec<Vec<BigNum>> = (1..=6i64)
+        .map(|x| (0..6u32).map(|k| BigNum::from(x.pow(k))).collect())
+        .collect();
+    assert_eq!(
+        crate::BigMatrix::from_rows(vandermonde).determinant(),
+        Ok(BigNum::from(34560))
+    );
+    assert_eq!(
+        int_matrix(&[&[1, 2]]).determinant(),
+        Err(MatrixError::NotSquare)
+    );
+}
+
+#[test]
+fn matrix_elimination() {
+    use crate::{BigMatrix, BigRational, MatrixError};
+
+    let m = int_matrix(&[&[1, 2, 3], &[2, 4, 6], &[1, 0, 1]]);
+    let (reduced, pivots) = m.rref();
+    assert_eq!(reduced, int_matrix(&[&[1, 0, 1], &[0, 1, 1], &[0, 0, 0]]));
+    assert_eq!(pivots, vec![0, 1]);
+    assert_eq!(m.rank(), 2);
+    assert_eq!(m.inverse(), Err(MatrixError::Singular));
+
+    let a = int_matrix(&[&[2, 1], &[1, 3]]);
+    assert_eq!(
+        a.solve(&[BigNum::from(5), BigNum::from(10)]),
+        Ok(vec![BigNum::from(1), BigNum::from(3)])
+    );
+    let flat = int_matrix(&[&[1, 1], &[1, 1]]);
+    assert_eq!(
+        flat.solve(&[BigNum::from(1), BigNum::from(2)]),
+        Err(MatrixError::Inconsistent)
+    );
+    assert_eq!(
+        flat.solve(&[BigNum::from(1), BigNum::from(1)]),
+        Err(MatrixError::Singular)
+    );
+
+    //the inverse of the 3x3 hilbert matrix has integer entries
+    let hilbert: BigMatrix<BigRational> = BigMatrix::from_rows(
+        (1..=3i64)
+            .map(|i| {
+                (1..=3i64)
+                    .map(|j| BigRational::new(BigNum::from(1), BigNum::from(i + j - 1)))
+                    .collect()
+            })
+            .collect(),
+    );
+    let expected = int_matrix(&[&[9, -36, 30], &[-36, 192, -180], &[30, -180, 180]])
+        .map(|x| BigRational::from_integer(x.clone()));
+    assert_eq!(hilbert.inverse(), Ok(expected.clone()));
+    assert_eq!(&hilbert * &expected, BigMatrix::<BigRational>::identity(3));
+    assert_eq!(
+        hilbert.determinant(),
+        Ok(BigRational::new(BigNum::from(1), BigNum::from(2160)))
+    );
+}
+
+#[cfg(test)]
+fn int_poly(coeffs: &[i64]) -> crate::Polynomial {
+    crate::Polynomial::new(coeffs.iter().map(|&c| BigNum::from(c)).collect())
+}
+
+#[test]
+fn polynomial_arithmetic() {
+    use crate::Polynomial;
+
+    let p = int_poly(&[1, -3, 0, 2]);
+    assert_eq!(p.degree(), Some(3));
+    assert_eq!(p.to_string(), "2x^3 - 3x + 1");
+    assert_eq!(p.eval(&BigNum::from(3)), BigNum::from(46));
+    assert_eq!(p.eval(&decimal("0.5")), decimal("-0.25"));
+    assert_eq!(p.derivative(), int_poly(&[-3, 0, 6]));
+    assert_eq!(&p - &p, Polynomial::default());
+    assert_eq!(
+        int_poly(&[1, 1]) * int_poly(&[-1, 1]),
+        int_poly(&[-1, 0, 1])
+    );
+
+    let (q, r) = int_poly(&[-1, 0, 0, 1]).div_rem(&int_poly(&[-1, 1]));
+    assert_eq!(q, int_poly(&[1, 1, 1]));
+    assert!(r.is_zero());
+    let (q, r) = p.div_rem(&int_poly(&[1, 0, 2]));
+    assert_eq!((q, r), (int_poly(&[0, 1]), int_poly(&[1, -4])));
+
+    //(x - 1)(x - 2) and (x - 1)(x + 3), scaled by constants the gcd drops
+    let a = int_poly(&[6, -9, 3]);
+    let b = int_poly(&[-3, 2, 1]) * Polynomial::constant(decimal("0.5"));
+    assert_eq!(Polynomial::gcd(&a, &b), int_poly(&[-1, 1]));
+    assert_eq!(Polynomial::gcd(&a, &int_poly(&[1, 1])), int_poly(&[1]));
+}
+
+#[test]
+fn polynomial_real_roots() {
+    assert_eq!(int_poly(&[1, 0, 1]).count_real_roots(), 0);
+    assert!(int_poly(&[1, 0, 1]).real_roots(64).is_empty());
+    //a double root at 1 is counted once
+    assert_eq!(int_poly(&[2, -3, 0, 1]).count_real_roots(), 2);
+
+    let roots = int_poly(&[-2, 0, 1]).real_roots(200);
+    assert_eq!(roots.len(), 2);
+    let two = BigNum::from(2);
+    let limit = BigNum::from(1).mul_pow2(-200);
+    assert!(roots.iter().all(|root| root.width() <= limit));
+    assert!(roots[1].lo() * roots[1].lo() <= two && two <= roots[1].hi() * roots[1].hi());
+    assert!(roots[0].hi() < roots[1].lo());
+    assert_close(
+        roots[1].midpoint(),
+        "481231938336009023090067544955250113854",
+    );
+
+    //wilkinson's polynomial, hopeless in f64 but exact here
+    let wilkinson = (1..=20).fold(int_poly(&[1]), |p, k| p * int_poly(&[-k, 1]));
+    let roots = wilkinson.real_roots(100);
+    assert_eq!(roots.len(), 20);
+    for (k, root) in (1..=20).zip(&roots) {
+        assert!(root.contains(&BigNum::from(k)));
+    }
+    //moving one coefficient by 2^-23 sends ten of those roots off the real line
+    let mut coeffs = wilkinson.coeffs().to_vec();
+    coeffs[19] = &coeffs[19] - BigNum::from(1).mul_pow2(-23);
+    let perturbed = crate::Polynomial::new(coeffs);
+    assert_eq!(perturbed.count_real_roots(), 10);
+    for root in perturbed.real_roots(80) {
+        assert!(perturbed.eval(root.lo()) * perturbed.eval(root.hi()) <= BigNum::new());
+    }
+}
+
+#[cfg(test)]
+fn dec(s: &str) -> crate::BigDecimal {
+    s.parse().unwrap()
+}
+
+#[test]
+fn decimal_arithmetic() {
+    let sum = dec("0.1") + dec("0.2");
+    assert_eq!(sum, dec("0.3"));
+    assert_eq!(sum.to_string(), "0.3");
+    assert_eq!((dec("1.50") * dec("2")).to_string(), "3.00");
+    assert_eq!(dec("1.50"), dec("1.5"));
+    assert!(dec("-0.01") < dec("0.001"));
+    assert_eq!((dec("10") - dec("0.001")).to_string(), "9.999");
+    assert_eq!((-dec("0.5")).to_string(), "-0.5");
+
+    assert_eq!(dec("1.25e-3").to_string(), "0.00125");
+    assert_eq!(dec("-12E2").to_string(), "-1200");
+    assert_eq!(dec("+.5").to_string(), "0.5");
+    let long = "-123456789012345678901234567890.000000000000000000000000000001";
+    assert_eq!(dec(long).to_string(), long);
+    assert!("1.2.3".parse::<crate::BigDecimal>().is_err());
+    assert!("".parse::<crate::BigDecimal>().is_err());
+    assert!("1e".parse::<crate::BigDecimal>().is_err());
+
+    assert_eq!((dec("1") / dec("8")).to_string(), "0.125");
+    assert_eq!((dec("1") / dec("0.04")).to_string(), "25");
+    assert_eq!(
+        (dec("2") / dec("3")).to_string(),
+        format!("0.{}7", "6".repeat(31))
+    );
+}
+
+#[test]
+fn decimal_rounding() {
+    use crate::RoundingMode::*;
+
+    let cases = [
+        ("2.5", ["2", "3", "2", "3", "3", "2", "2"]),
+        ("-2.5", ["-2", "-3", "-3", "-2", "-3", "-2", "-2"]),
+        ("3.5", ["3", "4", "3", "4", "4", "3", "4"]),
+        ("1.01", ["1", "2", "1", "2", "1", "1", "1"]),
+        ("-1.7", ["-1", "-2", "-2", "-1", "-2", "-2", "-2"]),
+    ];
+    for (value, expected) in cases {
+        for (mode, expected) in [Down, Up, Floor, Ceiling, HalfUp, HalfDown, HalfEven]
+            .into_iter()
+            .zip(expected)
+        {
+            assert_eq!(dec(value).with_scale(0, mode).to_string(), expected);
+        }
+    }
+    let mut x = dec("1.005");
+    x.rescale(2, HalfEven);
+    assert_eq!(x.to_string(), "1.00");
+    x.rescale(4, HalfEven);
+    assert_eq!(x.to_string(), "1.0000");
+    assert_eq!(dec("1234.5").with_scale(-2, HalfUp).to_string(), "1200");
+    assert_eq!(dec("2").div_to(&dec("3"), 4, HalfUp).to_string(), "0.6667");
+    assert_eq!(dec("12.3400").normalized().to_string(), "12.34");
+}
+
+#[test]
+fn decimal_conversion() {
+    use crate::{BigDecimal, BigRational};
+
+    assert_eq!(dec("0.375").to_bignum(), Some(decimal("0.375")));
+    assert_eq!(dec("-4e3").to_bignum(), Some(BigNum::from(-4000)));
+    assert_eq!(dec("0.1").to_bignum(), None);
+    assert_eq!(
+        dec("0.1").to_rational(),
+        BigRational::new(BigNum::from(1), BigNum::from(10))
+    );
+
+    let tiny = BigNum::from(-3).mul_pow2(-70);
+    let exact = BigDecimal::from(tiny.clone());
+    assert_eq!(exact.scale(), 70);
+    assert_eq!(
+        exact.to_string(),
+        "-0.0000000000000000000025410988417629010172049675020389258861541748046875"
+    );
+    assert_eq!(exact.to_bignum(), Some(tiny));
+    assert_eq!(BigDecimal::from(BigNum::from(5)).to_string(), "5");
+    assert_eq!(BigDecimal::from(decimal("2.5")).scale(), 1);
+
+    //scales a million apart are compared and converted without a million digit power of ten
+    let far = dec("1e-1000000");
+    assert!(far < dec("1"));
+    assert!(-&far > dec("-1"));
+    assert!(far > BigDecimal::zero());
+    assert_eq!(far.to_bignum(), None);
+    assert_eq!(
+        dec("-1e1000000").cmp(&dec("1e-1000000")),
+        std::cmp::Ordering::Less
+    );
+    assert_eq!(
+        crate::decimal::pow10(40),
+        decimal(&format!("1{}", "0".repeat(40)))
+    );
+}
+
+#[test]
+fn money_arithmetic() {
+    use crate::{Currency, Money, MoneyError};
+
+    let price = Money::new(dec("19.99"), Currency::USD);
+    let total = &price + Money::new(dec("0.01"), Currency::USD);
+    assert_eq!(total.to_string(), "20.00 USD");
+    assert_eq!(total.minor(), &BigNum::from(2000));
+    assert_eq!(
+        price.checked_add(&Money::new(dec("5"), Currency::EUR)),
+        Err(MoneyError::CurrencyMismatch {
+            expected: Currency::USD,
+            found: Currency::EUR
+        })
+    );
+    assert_eq!(price.partial_cmp(&Money::zero(Currency::EUR)), None);
+    assert!(price > Money::zero(Currency::USD));
+
+    //banker's rounding on the way in and when scaling
+    assert_eq!(
+        Money::new(dec("2.345"), Currency::USD).to_string(),
+        "2.34 USD"
+    );
+    assert_eq!(
+        Money::new(dec("2.355"), Currency::USD).to_string(),
+        "2.36 USD"
+    );
+    assert_eq!(Money::new(dec("2.5"), Currency::JPY).to_string(), "2 JPY");
+    assert_eq!((&price * dec("0.075")).to_string(), "1.50 USD");
+    assert_eq!(Currency::from_code("KWD"), Some(Currency::KWD));
+    assert_eq!(
+        Money::from_minor(BigNum::from(1234), Currency::KWD).to_string(),
+        "1.234 KWD"
+    );
+
+    let big = Money::new(dec("-1234567.8"), Currency::EUR);
+    assert_eq!(big.to_string(), "-1,234,567.80 EUR");
+    assert_eq!(big.format_with('.', ','), "-1.234.567,80");
+    assert_eq!(Money::new(dec("999"), Currency::JPY).to_string(), "999 JPY");
+}
+
+#[test]
+fn money_allocation() {
+    use crate::{Currency, Money, MoneyError};
+
+    let amounts = |parts: Vec<Money>| -> Vec<String> {
+        parts.iter().map(|m| m.amount().to_string()).collect()
+    };
+    let hundred = Money::new(dec("100"), Currency::USD);
+    let thirds = hundred.allocate(&[dec("1"), dec("1"), dec("1")]).unwrap();
+    assert_eq!(amounts(thirds), ["33.34", "33.33", "33.33"]);
+
+    let cents = Money::new(dec("0.05"), Currency::USD);
+    let split = cents.allocate(&[dec("0.3"), dec("0.7")]).unwrap();
+    assert_eq!(amounts(split), ["0.02", "0.03"]);
+
+    let refund = Money::new(dec("-10"), Currency::USD);
+    let parts = refund
+        .allocate(&[dec("1"), dec("2"), dec("0"), dec("3")])
+        .unwrap();
+    assert_eq!(amounts(parts.clone()), ["-1.67", "-3.33", "0.00", "-5.00"]);
+    let sum = parts
+        .iter()
+        .fold(Money::zero(Currency::USD), |sum, m| sum + m);
+    assert_eq!(sum, refund);
+
+    assert_eq!(
+        hundred.allocate(&[dec("0")]),
+        Err(MoneyError::InvalidRatios)
+    );
+    assert_eq!(
+        hundred.allocate(&[dec("1"), dec("-1")]),
+        Err(MoneyError::InvalidRatios)
+    );
+}
+
+#[test]
+fn exact_real_digits() {
+    use crate::ExactReal;
+
+    let third = ExactReal::from(1) / ExactReal::from(3);
+    assert_eq!(third.to_string(30), format!("0.{}", "3".repeat(30)));
+    let tenth = ExactReal::from(1) / ExactReal::from(10);
+    let sum = &tenth + &tenth * ExactReal::from(2);
+    assert_eq!(sum.to_string(5), "0.30000");
+    assert_eq!(ExactReal::from(decimal("0.125")).to_string(2), "0.12");
+    assert_eq!((-&third).to_string(3), "-0.333");
+
+    let pi = ExactReal::pi();
+    assert_eq!(
+        pi.to_string(50),
+        "3.14159265358979323846264338327950288419716939937510"
+    );
+    //no cancellation however large the terms around it
+    let huge = ExactReal::from(BigNum::from(10).pow(BigNum::from(40)));
+    assert_eq!(
+        (&huge + &pi - &huge).to_string(40),
+        "3.1415926535897932384626433832795028841971"
+    );
+    assert_eq!(
+        (ExactReal::from(4) * ExactReal::from(1).atan()).to_string(40),
+        "3.1415926535897932384626433832795028841971"
+    );
+    assert_eq!(pi.sin().to_string(20), "0.00000000000000000000");
+}
+
+#[test]
+fn exact_real_functions() {
+    use crate::ExactReal;
+
+    assert_eq!(
+        ExactReal::from(1).exp().to_string(40),
+        "2.7182818284590452353602874713526624977572"
+    );
+    assert_eq!(
+        ExactReal::from(2).ln().to_string(40),
+        "0.6931471805599453094172321214581765680755"
+    );
+    let root = ExactReal::from(2).sqrt();
+    assert_eq!(root.to_string(30), "1.414213562373095048801688724209");
+    assert_eq!((&root * &root).to_string(20), "2.00000000000000000000");
+    assert_eq!(
+        ExactReal::from(10).exp().ln().to_string(30),
+        format!("10.{}", "0".repeat(30))
+    );
+
+    let approximation = root.approximate(300);
+    let error = &approximation * &approximation - BigNum::from(2);
+    assert!(error.log2_floor().unwrap() < -298);
+
+    let zero = ExactReal::from(1) - ExactReal::from(3) * (ExactReal::from(1) / ExactReal::from(3));
+    let result = std::panic::catch_unwind(|| zero.recip().to_string(5));
+    assert!(result.is_err());
+
+    //e^1000 has 435 digits before the point, e^-1000 435 zeros after it
+    let big = ExactReal::from(1000).exp().to_string(5);
+    assert!(big.starts_with("197007111401704699388887935224332312531693798532384578995"));
+    assert!(big.ends_with("959705844189509050047074217568.22675"));
+    assert_eq!(ExactReal::from(-1000).exp().to_string(5), "0.00000");
+    assert_eq!(ExactReal::from(-1000000).exp().to_string(5), "0.00000");
+    assert_eq!(
+        ExactReal::from(decimal("-1000000000000000000000000000000"))
+            .exp()
+            .to_string(5),
+        "0.00000"
+    );
+}
+
+#[test]
+#[should_panic(expected = "exp of a number too large to compute")]
+fn exact_real_exp_of_a_large_number_panics() {
+    crate::ExactReal::from(100000).exp().to_string(5);
+}
+
+#[test]
+fn approx_error_tracking() {
+    use crate::BigApprox;
+
+    let three = BigApprox::exact(BigNum::from(3));
+    assert!((&three * &three + BigApprox::exact(BigNum::from(4))).is_exact());
+
+    //exact operands only pick up the rounding of the division itself
+    let third = BigApprox::exact(BigNum::from(1)) / &three;
+    let bits = third.significant_bits().unwrap();
+    assert!((120..=130).contains(&bits));
+    let text = third.to_string();
+    assert!(text.len() > 30 && text[2..].chars().all(|c| c == '3'));
+    assert!((-&third).to_string().starts_with("-0.333"));
+
+    //1 + 2^-20 known to 53 bits loses 20 of them to cancellation
+    let x = BigApprox::with_significant_bits(BigNum::from(1) + BigNum::from(1).mul_pow2(-20), 53);
+    let difference = &x - BigApprox::exact(BigNum::from(1));
+    let bits = difference.significant_bits().unwrap();
+    assert!((30..=34).contains(&bits));
+    assert_eq!(difference.to_string(), "0.000000953674316");
+    assert!(difference
+        .to_interval()
+        .contains(&BigNum::from(1).mul_pow2(-20)));
+
+    let rough = BigApprox::new(BigNum::from(12345), BigNum::from(100));
+    assert_eq!(rough.to_string(), "12*10^3");
+    assert_eq!(rough.encode(crate::Base::Hexadecimal), "30*16^2");
+    assert_eq!(
+        BigApprox::exact(BigNum::from(1))
+            .checked_div(&BigApprox::new(BigNum::new(), BigNum::from(1))),
+        None
+    );
+    let divided =
+        BigApprox::exact(BigNum::from(1)) / BigApprox::with_significant_bits(BigNum::from(3), 20);
+    assert!((17..=21).contains(&divided.significant_bits().unwrap()));
+}
+
+#[test]
+fn approx_roots_and_powers() {
+    use crate::BigApprox;
+
+    let two = BigApprox::exact(BigNum::from(2));
+    let root = two.sqrt().unwrap();
+    let interval = root.to_interval();
+    assert!(interval.lo() * interval.lo() <= BigNum::from(2));
+    assert!(interval.hi() * interval.hi() >= BigNum::from(2));
+    assert!(root
+        .to_string()
+        .starts_with("1.414213562373095048801688724209"));
+
+    let half_power = two.pow(&decimal("0.5")).unwrap();
+    assert!(half_power.to_interval().intersect(&interval).is_some());
+    let cube_root = BigApprox::exact(BigNum::from(27)).root(3).unwrap();
+    assert!(cube_root.to_interval().contains(&BigNum::from(3)));
+    assert!(cube_root.significant_bits().unwrap() > 100);
+
+    //a value good to 30 bits stays about that good through a fractional power
+    let rough = BigApprox::with_significant_bits(BigNum::from(10), 30);
+    let powered = rough.pow(&decimal("2.5")).unwrap();
+    assert!((26..=30).contains(&powered.significant_bits().unwrap()));
+    assert!(powered.to_string().starts_with("316.227"));
+    let inverse_square = rough.pow(&BigNum::from(-2)).unwrap();
+    assert!((&inverse_square * BigApprox::exact(BigNum::from(100)))
+        .to_interval()
+        .contains(&BigNum::from(1)));
+    assert_eq!(inverse_square.to_string(), "0.0100000000");
+    assert!(BigApprox::exact(BigNum::from(-4)).sqrt().is_err());
+    assert!(BigApprox::new(BigNum::from(1), BigNum::from(2))
+        .root(2)
+        .is_err());
+}
+
+#[test]
+fn exponent_extremes() {
+    use crate::ExponentError;
+
+    //2^(64 i32::MAX) and its square, far past what an i32 exponent could hold
+    let huge = BigNum::from(1).scale_by_limbs(i32::MAX as i64);
+    let squared = &huge * &huge;
+    assert_eq!(squared.log2_floor(), Some(128 * i32::MAX as i64));
+    assert!(squared > huge && huge > BigNum::from(1));
+    assert_eq!(huge.clone().pow(BigNum::from(3)), &squared * &huge);
+    let tiny = BigNum::from(1).scale_by_limbs(-(i32::MAX as i64));
+    assert_eq!(&huge * &tiny, BigNum::from(1));
+    assert!(-&tiny < BigNum::new() && tiny < BigNum::from(1));
+    assert_eq!(squared.div_to_limbs(&tiny, 1), &squared * &huge);
+
+    let top = BigNum::from(3).scale_by_limbs(BigNum::MAX_EXP);
+    let bottom = BigNum::from(3).scale_by_limbs(BigNum::MIN_EXP);
+    assert_eq!(
+        top.checked_mul(&BigNum::from(u64::MAX)),
+        Ok(&top * BigNum::from(u64::MAX))
+    );
+    assert_eq!(top.checked_mul(&huge), Err(ExponentError::Overflow));
+    assert_eq!(bottom.checked_mul(&tiny), Err(ExponentError::Underflow));
+    assert_eq!(top.checked_mul(&bottom), Ok(BigNum::from(9)));
+    assert_eq!(top.checked_mul(&BigNum::new()), Ok(BigNum::new()));
+    assert!(bottom > BigNum::new() && top > squared);
+
+    //division probes past the top of the range on the way to an in-range quotient
+    let unit_top = BigNum::from(1).scale_by_limbs(BigNum::MAX_EXP);
+    let unit_bottom = BigNum::from(1).scale_by_limbs(BigNum::MIN_EXP);
+    assert_eq!(&top / &top, BigNum::from(1));
+    assert_eq!(&top / &unit_top, BigNum::from(3));
+    assert_eq!(&bottom / &bottom, BigNum::from(1));
+    assert_eq!(&unit_bottom / &bottom, BigNum::from(1) / BigNum::from(3));
+
+    //sums at the ends of the range, exact while the operands are close together
+    assert_eq!(&top + &top, &top * BigNum::from(2));
+    assert_eq!(&top - &top, BigNum::new());
+    assert_eq!(&bottom + &bottom, &bottom * BigNum::from(2));
+    let below_top = BigNum::from(5).scale_by_limbs(BigNum::MAX_EXP - 1);
+    assert_eq!(
+        top.checked_add(&below_top),
+        Ok(BigNum::from_parts(false, vec![5, 3], BigNum::MAX_EXP - 1))
+    );
+    assert_eq!(
+        top.checked_sub(&below_top),
+        Ok(BigNum::from_parts(
+            false,
+            vec![5u64.wrapping_neg(), 2],
+            BigNum::MAX_EXP - 1
+        ))
+    );
+    //and an error rather than gigabytes of zero limbs when they are far apart
+    let one = BigNum::from(1);
+    assert_eq!(unit_top.checked_add(&one), Err(ExponentError::TooWide));
+    assert_eq!(one.checked_sub(&unit_top), Err(ExponentError::TooWide));
+    assert_eq!(top.checked_add(&bottom), Err(ExponentError::TooWide));
+    assert_eq!(unit_top.checked_add(&BigNum::new()), Ok(unit_top.clone()));
+    //one limb past MAX_SPAN from the lowest limb of one to the top of the other
+    let edge = BigNum::from(1).scale_by_limbs(BigNum::MAX_SPAN);
+    assert_eq!(one.checked_add(&edge), Err(ExponentError::TooWide));
+    let result = std::panic::catch_unwind(|| {
+        let mut out = BigNum::new();
+        BigNum::add_into(&unit_top, &BigNum::from(1), &mut out);
+    });
+    assert!(result.is_err());
+    //division lines its operands up the same way
+    assert!(std::panic::catch_unwind(|| &top / BigNum::from(3)).is_err());
+}
+
+#[test]
+#[should_panic(expected = "Operands too far apart")]
+fn far_apart_addition_panics() {
+    let top = BigNum::from(1).scale_by_limbs(BigNum::MAX_EXP);
+    let _ = top + BigNum::from(1);
+}
+
+#[test]
+#[should_panic(expected = "Exponent overflow")]
+fn exponent_overflow_panics() {
+    let top = BigNum::from(1).scale_by_limbs(BigNum::MAX_EXP);
+    let _ = &top * &top;
+}
+
+#[test]
+#[should_panic(expected = "Exponent overflow")]
+fn exponent_overflow_in_addition_panics() {
+    //the carry out of the top limb lands one limb past MAX_EXP
+    let top = BigNum::from(u64::MAX).scale_by_limbs(BigNum::MAX_EXP);
+    let _ = &top + BigNum::from(1).scale_by_limbs(BigNum::MAX_EXP);
+}
+
+#[test]
+#[should_panic(expected = "Exponent overflow")]
+fn exponent_overflow_in_from_parts_panics() {
+    let _ = BigNum::from_parts(false, vec![0, 1], i64::MAX);
+}
+
+#[test]
+fn float_special_values() {
+    use crate::BigFloat;
+
+    let one = BigFloat::from(BigNum::from(1));
+    let zero = BigFloat::zero();
+    let neg_zero = BigFloat::neg_zero();
+    let inf = BigFloat::infinity();
+
+    assert_eq!(&one / &zero, inf);
+    assert_eq!(&one / &neg_zero, BigFloat::neg_infinity());
+    assert!((&zero / &zero).is_nan());
+    assert!((&inf - &inf).is_nan());
+    assert!((&inf * &zero).is_nan());
+    assert_eq!(&one / &inf, zero);
+    assert!((-&one / &inf).is_sign_negative());
+    assert!((&neg_zero + &neg_zero).is_sign_negative());
+    assert!(!(&neg_zero + &zero).is_sign_negative());
+    assert!(!(&one - &one).is_sign_negative());
+    assert_eq!(
+        (BigFloat::from(BigNum::from(3)) / BigFloat::from(BigNum::from(4))).to_bignum(),
+        Some(decimal("0.75"))
+    );
+
+    assert_eq!(zero.ln(64), BigFloat::neg_infinity());
+    assert!(BigFloat::from(BigNum::from(-1)).sqrt(64).is_nan());
+    assert!(BigFloat::from(BigNum::from(-1)).ln(64).is_nan());
+    assert!(neg_zero.sqrt(64).is_sign_negative());
+    assert_eq!(BigFloat::neg_infinity().exp(64), zero);
+    assert_eq!(one.ln(64), zero);
+
+    //past the exponent range products and exp saturate instead of panicking
+    let top = BigFloat::from(BigNum::from(1).scale_by_limbs(BigNum::MAX_EXP));
+    let bottom = BigFloat::from(BigNum::from(1).scale_by_limbs(BigNum::MIN_EXP));
+    assert_eq!(&top * &top, inf);
+    assert_eq!(-&top * &top, BigFloat::neg_infinity());
+    assert!((-&bottom * &bottom).is_zero());
+    assert!((-&bottom * &bottom).is_sign_negative());
+    assert_eq!(&top / &bottom, inf);
+    let big = BigFloat::from(BigNum::from(1).scale_by_limbs(1));
+    assert_eq!(big.exp(64), inf);
+    assert_eq!((-&big).exp(64), zero);
+
+    //sums past the top of the range saturate like products
+    let top = BigFloat::from(BigNum::from(u64::MAX).scale_by_limbs(BigNum::MAX_EXP));
+    let unit = BigFloat::from(BigNum::from(1).scale_by_limbs(BigNum::MAX_EXP));
+    assert_eq!(&top + &unit, inf);
+    assert_eq!(-&top - &unit, BigFloat::neg_infinity());
+    assert_eq!(
+        (&top - &unit).to_bignum(),
+        Some(BigNum::from(u64::MAX - 1).scale_by_limbs(BigNum::MAX_EXP))
+    );
+
+    //operands too far apart for an exact sum round to the larger one
+    let far = BigNum::from(1).scale_by_limbs(1 << 40);
+    let one = BigFloat::from(BigNum::from(1));
+    assert_eq!(
+        (BigFloat::from(far.clone()) + &one).to_bignum(),
+        Some(far.clone())
+    );
+    assert_eq!(
+        (&one - BigFloat::from(far.clone())).to_bignum(),
+        Some(-&far)
+    );
+    let bottom = BigFloat::from(BigNum::from(1).scale_by_limbs(BigNum::MIN_EXP));
+    assert_eq!(
+        (&bottom - &top).to_bignum(),
+        Some(-BigNum::from(u64::MAX).scale_by_limbs(BigNum::MAX_EXP))
+    );
+}
+
+#[test]
+fn float_ordering() {
+    use crate::BigFloat;
+    use std::cmp::Ordering;
+
+    let nan = BigFloat::nan();
+    let one = BigFloat::from(BigNum::from(1));
+    assert_ne!(nan, nan);
+    assert_eq!(nan.partial_cmp(&one), None);
+    assert!(!nan.le(&one) && !nan.ge(&one));
+    assert_eq!(BigFloat::zero(), BigFloat::neg_zero());
+    assert!(BigFloat::neg_infinity() < -&one && one < BigFloat::infinity());
+
+    let mut values = [
+        nan.clone(),
+        BigFloat::infinity(),
+        one.clone(),
+        BigFloat::zero(),
+        BigFloat::neg_zero(),
+        -&one,
+        BigFloat::neg_infinity(),
+    ];
+    values.sort_by(|a, b| a.total_cmp(b));
+    let printed: Vec<String> = values.iter().map(|v| v.to_string()).collect();
+    assert_eq!(printed, ["-inf", "-1", "-0", "0", "1", "inf", "NaN"]);
+    assert_eq!(nan.total_cmp(&nan), Ordering::Equal);
+    assert_eq!(
+        BigFloat::neg_zero().total_cmp(&BigFloat::zero()),
+        Ordering::Less
+    );
+}
+
+#[test]
+fn inline_parts() {
+    use crate::parts::Parts;
+
+    //the inline limbs take no more room than a Vec, so BigNum is as big as a Vec, the i64
+    //exponent and the sign
+    assert_eq!(
+        std::mem::size_of::<Parts>(),
+        std::mem::size_of::<Vec<u64>>()
+    );
+    assert_eq!(std::mem::size_of::<BigNum>(), 40);
+    let mut parts = Parts::from_slice(&[1]);
+    parts.insert(0, 0);
+    parts.push(2);
+    assert!(matches!(parts, Parts::Heap(_)));
+    assert_eq!(&*parts, &[0, 1, 2]);
+    parts.remove_low(1);
+    assert_eq!(parts.remove(0), 1);
+    assert!(matches!(parts.clone(), Parts::Inline { len: 1, .. }));
+    parts.resize(2, 7);
+    parts.truncate(5);
+    assert_eq!(
+        (parts.pop(), parts.pop(), parts.pop()),
+        (Some(7), Some(2), None)
+    );
+
+    //values moving between one, two and more limbs keep their value
+    let max = BigNum::from(u64::MAX);
+    let cube = &max * &max * &max;
+    assert_eq!(cube.get_precision(), 3);
+    let square = cube.div_to_limbs(&max, 2);
+    assert_eq!(&square + &max - &max * &max, max);
+    assert_eq!(BigNum::from(3) - BigNum::from(5), BigNum::from(-2));
+    assert_eq!(
+        max.clone() + BigNum::from(1),
+        BigNum::from(1).scale_by_limbs(1)
+    );
+    assert!(BigNum::from(-3) < BigNum::from(2) && BigNum::from(0) == -BigNum::from(0));
+}
+
+#[test]
+fn shared_parts() {
+    use crate::parts::Parts;
+    use std::sync::Arc;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<BigNum>();
+
+    let original = Parts::from(vec![1, 2, 3]);
+    let mut copy = original.clone();
+    assert!(matches!((&original, &copy), (Parts::Heap(a), Parts::Heap(b)) if Arc::ptr_eq(a, b)));
+    copy[0] = 9;
+    copy.push(4);
+    assert_eq!(&*original, &[1, 2, 3]);
+    assert_eq!(&*copy, &[9, 2, 3, 4]);
+
+    let a = BigNum::from(u64::MAX).pow(BigNum::from(5));
+    let b = a.clone();
+    let sum = b + BigNum::from(1);
+    assert_eq!(&sum - BigNum::from(1), a);
+    let handle = std::thread::spawn({
+        let a = a.clone();
+        move || &a * &a
+    });
+    assert_eq!(handle.join().unwrap(), a.pow(BigNum::from(2)));
+}
+
+#[test]
+fn into_buffers() {
+    let a = decimal("-12345678901234567890123456789.75");
+    let b = BigNum::from(u64::MAX).pow(BigNum::from(3)) + decimal("0.5");
+    let mut out = BigNum::with_capacity(8);
+    assert_eq!(out, BigNum::new());
+
+    for (x, y) in [(&a, &b), (&b, &a), (&a, &a), (&b, &-&b)] {
+        BigNum::add_into(x, y, &mut out);
+        assert_eq!(out, x + y);
+        BigNum::sub_into(x, y, &mut out);
+        assert_eq!(out, x - y);
+        BigNum::mul_into(x, y, &mut out);
+        assert_eq!(out, x * y);
+        BigNum::square_into(x, &mut out);
+        assert_eq!(out, x * x);
+        BigNum::div_into(x, y, &mut out);
+        let precision = x.get_precision() + y.get_precision();
+        assert_eq!(out, x.div_to_limbs(y, precision));
+    }
+    BigNum::div_into(&BigNum::from(-7), &BigNum::from(2), &mut out);
+    assert_eq!(out, decimal("-3.5"));
+    BigNum::sub_into(&a, &a, &mut out);
+    assert_eq!(out, BigNum::new());
+
+    //a clone sharing the buffer keeps its value when the buffer is written to
+    BigNum::mul_into(&b, &b, &mut out);
+    let kept = out.clone();
+    BigNum::mul_into(&a, &a, &mut out);
+    assert_eq!(kept, &b * &b);
+    assert_eq!(out, &a * &a);
+}
+
+#[test]
+fn limb_access() {
+    let value = BigNum::from_parts(true, vec![0, 5, 7, 0, 0], -2);
+    assert_eq!(
+        value,
+        -(BigNum::from(7) + BigNum::from(5).scale_by_limbs(-1))
+    );
+    assert_eq!(value.as_limbs(), &[5, 7]);
+    assert_eq!(value.exponent(), -1);
+    assert!(value.is_negative());
+    assert_eq!(value.limbs().collect::<Vec<_>>(), [5, 7]);
+    assert_eq!(value.limbs_rev().collect::<Vec<_>>(), [7, 5]);
+    assert_eq!(value.clone().into_parts(), (true, vec![5, 7], -1));
+    let (neg, limbs, exp) = value.clone().into_parts();
+    assert_eq!(BigNum::from_parts(neg, limbs, exp), value);
+
+    let zero = BigNum::from_parts(true, vec![0, 0], 9);
+    assert!(!zero.is_negative());
+    assert_eq!(zero.exponent(), 0);
+    assert_eq!(zero.into_parts(), (false, vec![], 0));
+    assert!(!(-BigNum::from(0)).is_negative());
+
+    //frexp and the scales put a value back together
+    let (mantissa, exp) = value.frexp();
+    assert_eq!(exp, 3);
+    assert!(mantissa <= decimal("-0.5") && mantissa > BigNum::from(-1));
+    assert_eq!(mantissa.scale_by_bits(exp), value);
+    assert_eq!(BigNum::new().frexp(), (BigNum::new(), 0));
+    assert_eq!(
+        BigNum::from(3).scale_by_limbs(-1).scale_by_bits(64),
+        BigNum::from(3)
+    );
+}
+
+#[cfg(test)]
+fn hex(s: &str) -> Vec<u8> {
+    (0..s.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&s[i..i + 2], 16).unwrap())
+        .collect()
+}
+
+#[test]
+fn byte_forms() {
+    use crate::BytesError;
+
+    let two_64 = BigNum::from(1).scale_by_limbs(1);
+    //minimal two's complement, checked against python's int.to_bytes
+    let signed = [
+        (BigNum::from(0), "00"),
+        (BigNum::from(127), "7f"),
+        (BigNum::from(128), "0080"),
+        (BigNum::from(-1), "ff"),
+        (BigNum::from(-128), "80"),
+        (BigNum::from(-129), "ff7f"),
+        (BigNum::from(-256), "ff00"),
+        (BigNum::from(-32769), "ff7fff"),
+        (two_64.clone(), "010000000000000000"),
+        (-&two_64, "ff0000000000000000"),
+    ];
+    for (value, expected) in signed {
+        let be = value.to_signed_bytes_be().unwrap();
+        assert_eq!(be, hex(expected), "{}", value);
+        assert_eq!(BigNum::from_signed_bytes_be(&be), value);
+        let mut le = value.to_signed_bytes_le().unwrap();
+        assert_eq!(BigNum::from_signed_bytes_le(&le), value);
+        le.reverse();
+        assert_eq!(le, be);
+    }
+
+    let big = BigNum::from(2).pow(BigNum::from(200)) + BigNum::from(12345);
+    let be = big.to_bytes_be().unwrap();
+    assert_eq!(
+        be,
+        hex("0100000000000000000000000000000000000000000000003039")
+    );
+    assert_eq!(BigNum::from_bytes_be(&be), big);
+    assert_eq!(BigNum::from_bytes_le(&big.to_bytes_le().unwrap()), big);
+    assert_eq!(BigNum::from(0).to_bytes_le().unwrap(), [0]);
+    assert_eq!(BigNum::from_bytes_be(&[]), BigNum::new());
+    assert_eq!(BigNum::from_signed_bytes_le(&[]), BigNum::new());
+
+    assert_eq!(
+        BigNum::from(0x1234).to_bytes_be_padded(4).unwrap(),
+        [0, 0, 0x12, 0x34]
+    );
+    assert_eq!(
+        big.to_bytes_be_padded(25),
+        Err(BytesError::TooLong { len: 26, max: 25 })
+    );
+    assert_eq!(BigNum::from(-5).to_bytes_be(), Err(BytesError::Negative));
+    assert_eq!(
+        decimal("2.5").to_signed_bytes_be(),
+        Err(BytesError::NotAnInteger)
+    );
+}
+
+#[test]
+fn wire_format() {
+    use crate::WireError;
+
+    let write = |x: &BigNum| {
+        let mut out = Vec::new();
+        x.write_to(&mut out).unwrap();
+        out
+    };
+    let read = |bytes: &[u8]| BigNum::read_from(&mut &bytes[..]);
+
+    assert_eq!(write(&BigNum::new()), [1, 0, 0, 0]);
+    assert_eq!(write(&BigNum::from(5)), [1, 0, 0, 1, 5]);
+    assert_eq!(write(&BigNum::from(0x1234)), [1, 2, 0, 1, 0x34, 0x12]);
+    assert_eq!(
+        write(&decimal("-0.5")),
+        [1, 0x0f, 0x7f, 1, 0, 0, 0, 0, 0, 0, 0, 0x80]
+    );
+
+    let values = [
+        BigNum::new(),
+        BigNum::from(-1),
+        decimal("3.75"),
+        BigNum::from(3).pow(BigNum::from(500)),
+        -BigNum::from(1) / BigNum::from(3),
+        BigNum::from_parts(false, vec![7, 0, 9], BigNum::MAX_EXP),
+        BigNum::from_parts(true, vec![1 << 63], BigNum::MIN_EXP),
+    ];
+    //several values back to back in one stream
+    let mut stream = Vec::new();
+    for value in &values {
+        value.write_to(&mut stream).unwrap();
+    }
+    let mut reader = &stream[..];
+    for value in &values {
+        assert_eq!(&BigNum::read_from(&mut reader).unwrap(), value);
+    }
+    assert!(reader.is_empty());
+
+    let rejects = |bytes: &[u8]| read(bytes).unwrap_err();
+    assert!(matches!(
+        rejects(&[2, 0, 0, 0]),
+        WireError::UnsupportedVersion(2)
+    ));
+    assert!(matches!(rejects(&[1, 0x10, 0, 1, 5]), WireError::Malformed));
+    //padded varints, negative zero, a zero top byte and a zero low limb
+    assert!(matches!(
+        rejects(&[1, 0, 0, 0x81, 0, 5]),
+        WireError::NonCanonical
+    ));
+    assert!(matches!(
+        rejects(&[1, 0, 0x40, 0, 1, 5]),
+        WireError::NonCanonical
+    ));
+    assert!(matches!(rejects(&[1, 1, 0, 0]), WireError::NonCanonical));
+    assert!(matches!(
+        rejects(&[1, 2, 0, 1, 5, 0]),
+        WireError::NonCanonical
+    ));
+    let mut low_zero = vec![1, 0, 0, 2];
+    low_zero.extend_from_slice(&[0; 8]);
+    low_zero.push(1);
+    assert!(matches!(rejects(&low_zero), WireError::NonCanonical));
+    //an exponent past MAX_EXP and a count past 64 bits
+    let mut far = vec![1, 0];
+    far.extend_from_slice(&[0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x3f]);
+    far.extend_from_slice(&[1, 1]);
+    assert!(matches!(rejects(&far), WireError::ExponentOutOfRange));
+    assert!(matches!(
+        rejects(&[1, 0, 0, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01]),
+        WireError::Malformed
+    ));
+
+    //truncated anywhere, and a huge count claim fails on the missing limbs without allocating them
+    let encoded = write(&values[3]);
+    for len in 0..encoded.len() {
+        assert!(matches!(rejects(&encoded[..len]), WireError::Truncated));
+    }
+    assert!(matches!(
+        rejects(&[1, 0, 0, 0x80, 0x80, 0x80, 0x80, 0x80, 0x40, 1, 2]),
+        WireError::Truncated
+    ));
+    assert!(matches!(
+        BigNum::read_from_limited(&mut &encoded[..], 4),
+        Err(WireError::TooLong { max: 4, .. })
+    ));
+    assert_eq!(
+        BigNum::read_from_limited(&mut &encoded[..], 100).unwrap(),
+        values[3]
+    );
+}
+
+#[test]
+fn cbor_numbers() {
+    use crate::{BigDecimal, CborError};
+
+    let two_64 = BigNum::from(1).scale_by_limbs(1);
+    //RFC 8949 appendix A and section 3.4.4
+    let vectors = [
+        (BigNum::from(0), "00"),
+        (BigNum::from(23), "17"),
+        (BigNum::from(24), "1818"),
+        (BigNum::from(1000), "1903e8"),
+        (BigNum::from(1000000000000i64), "1b000000e8d4a51000"),
+        (&two_64 - BigNum::from(1), "1bffffffffffffffff"),
+        (two_64.clone(), "c249010000000000000000"),
+        (BigNum::from(-1), "20"),
+        (BigNum::from(-1000), "3903e7"),
+        (-&two_64, "3bffffffffffffffff"),
+        (-&two_64 - BigNum::from(1), "c349010000000000000000"),
+        (decimal("1.5"), "c5822003"),
+    ];
+    for (value, expected) in vectors {
+        assert_eq!(value.to_cbor(), hex(expected), "{}", value);
+        assert_eq!(BigNum::from_cbor(&hex(expected)).unwrap(), value);
+    }
+    let kelvin = hex("c48221196ab3");
+    let decoded = BigDecimal::from_cbor(&kelvin).unwrap();
+    assert_eq!(decoded.to_string(), "273.15");
+    assert_eq!(decoded.to_cbor(), kelvin);
+    assert_eq!(BigNum::from_cbor(&kelvin), Err(CborError::NotDyadic));
+    //a dyadic decimal fraction, 1.25 = 125e-2
+    assert_eq!(
+        BigNum::from_cbor(&hex("c48221187d")).unwrap(),
+        decimal("1.25")
+    );
+
+    for value in [
+        decimal("-3.0625"),
+        BigNum::from(3).pow(BigNum::from(100)) / BigNum::from(1024),
+        BigNum::from_parts(true, vec![5, 7], BigNum::MIN_EXP),
+        BigNum::from_parts(false, vec![1 << 40], BigNum::MAX_EXP),
+    ] {
+        assert_eq!(BigNum::from_cbor(&value.to_cbor()).unwrap(), value);
+    }
+
+    //non-preferred heads, leading zero bytes and chunked strings still decode
+    assert_eq!(
+        BigNum::from_cbor(&hex("1b0000000000000005")).unwrap(),
+        BigNum::from(5)
+    );
+    assert_eq!(
+        BigNum::from_cbor(&hex("c243000105")).unwrap(),
+        BigNum::from(261)
+    );
+    assert_eq!(
+        BigNum::from_cbor(&hex("c25f41014100ff")).unwrap(),
+        BigNum::from(256)
+    );
+    assert_eq!(
+        BigNum::from_cbor(&hex("c59f2003ff")).unwrap(),
+        decimal("1.5")
+    );
+
+    assert_eq!(BigNum::from_cbor(&hex("")), Err(CborError::Truncated));
+    assert_eq!(
+        BigNum::from_cbor(&hex("c249010000")),
+        Err(CborError::Truncated)
+    );
+    assert_eq!(
+        BigNum::from_cbor(&hex("0101")),
+        Err(CborError::TrailingBytes)
+    );
+    assert_eq!(BigNum::from_cbor(&hex("1c")), Err(CborError::Malformed));
+    assert_eq!(BigNum::from_cbor(&hex("c201")), Err(CborError::Malformed));
+    assert_eq!(BigNum::from_cbor(&hex("c58120")), Err(CborError::Malformed));
+    assert_eq!(BigNum::from_cbor(&hex("6161")), Err(CborError::Unsupported));
+    assert_eq!(
+        BigNum::from_cbor(&hex("f93c00")),
+        Err(CborError::Unsupported)
+    );
+    assert_eq!(
+        BigNum::from_cbor(&hex("c5821b7fffffffffffffff01")),
+        Err(CborError::Exponent(crate::ExponentError::Overflow))
+    );
+    assert_eq!(
+        BigNum::from_cbor(&hex("c4823b7fffffffffffffff01")),
+        Err(CborError::NotDyadic)
+    );
+
+    //hostile exponents fail before building 10^e or 5^e
+    assert_eq!(
+        BigNum::from_cbor(&hex("c4821a000f424001")),
+        Err(CborError::TooLarge)
+    );
+    assert_eq!(
+        BigNum::from_cbor(&hex("c4823a000f423f01")),
+        Err(CborError::NotDyadic)
+    );
+    let tiny = hex("c5823a000f423f01");
+    assert_eq!(BigDecimal::from_cbor(&tiny), Err(CborError::TooLarge));
+    assert_eq!(
+        BigNum::from_cbor(&tiny).unwrap(),
+        BigNum::from(1).scale_by_bits(-1000000)
+    );
+    //a decimal fraction keeps its exponent as the scale and needs no power at all
+    assert_eq!(
+        BigDecimal::from_cbor(&hex("c4823a000f423f01"))
+            .unwrap()
+            .to_string()
+            .len(),
+        1000002
+    );
+    //1e1000 is well within the default limit, but not within 8 limbs
+    let thousand = hex("c4821903e801");
+    assert_eq!(
+        BigNum::from_cbor(&thousand).unwrap(),
+        decimal(&format!("1{}", "0".repeat(1000)))
+    );
+    assert_eq!(
+        BigNum::from_cbor_limited(&thousand, 8),
+        Err(CborError::TooLarge)
+    );
+    assert_eq!(
+        BigDecimal::from_cbor_limited(&hex("c58238ff01"), 8),
+        Err(CborError::TooLarge)
+    );
+    assert_eq!(
+        BigDecimal::from_cbor(&hex("c58238ff01")).unwrap(),
+        BigDecimal::from(BigNum::from(1).scale_by_bits(-256))
+    );
+}
@@ -0,0 +1,242 @@
+use std::error::Error;
+use std::fmt::{self, Display};
+use std::io::{self, Read, Write};
+
+use crate::big_num::BigNum;
+
+//a compact binary form for any BigNum, fractions included:
+//
+//  version   one byte, VERSION
+//  flags     bit 0 the sign, bits 1..=3 the length in bytes of the top limb minus one, the rest
+//            reserved and zero
+//  exponent  signed LEB128, the exponent of the lowest limb as in into_parts
+//  count     unsigned LEB128, the number of limbs
+//  limbs     little endian, least significant first, all 8 bytes except the top one which only
+//            has the bytes flags says it has
+//
+//every value has exactly one encoding, the normalized parts with the shortest varints and no
+//zero top byte, and zero is flags 0, exponent 0 and no limbs
+//read_from rejects anything else, so equal values always give equal bytes
+
+pub const VERSION: u8 = 1;
+
+const NEGATIVE: u8 = 1;
+const TOP_LEN_SHIFT: u32 = 1;
+const TOP_LEN_MASK: u8 = 0b111 << TOP_LEN_SHIFT;
+
+//limbs allocated ahead of reading them, so a bogus count can't reserve more than this
+const PREALLOCATE: usize = 1024;
+
+#[derive(Debug)]
+pub enum WireError {
+    Io(io::Error),
+    //the input ended partway through a value
+    Truncated,
+    UnsupportedVersion(u8),
+    //well formed but not the one encoding of its value
+    NonCanonical,
+    //reserved flag bits set or a varint longer than 64 bits
+    Malformed,
+    ExponentOutOfRange,
+    //the value claims `len` limbs but only `max` were allowed
+    TooLong { len: u64, max: usize },
+}
+
+impl Display for WireError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            WireError::Io(e) => write!(f, "{}", e),
+            WireError::Truncated => write!(f, "Input ended in the middle of a value"),
+            WireError::UnsupportedVersion(version) => {
+                write!(f, "Unsupported wire format version {}", version)
+            }
+            WireError::NonCanonical => write!(f, "Non-canonical encoding"),
+            WireError::Malformed => write!(f, "Malformed encoding"),
+            WireError::ExponentOutOfRange => write!(f, "Exponent out of range"),
+            WireError::TooLong { len, max } => {
+                write!(f, "Value claims {} limbs but only {} are allowed", len, max)
+            }
+        }
+    }
+}
+
+impl Error for WireError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            WireError::Io(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for WireError {
+    fn from(e: io::Error) -> Self {
+        if e.kind() == io::ErrorKind::UnexpectedEof {
+            WireError::Truncated
+        } else {
+            WireError::Io(e)
+        }
+    }
+}
+
+fn write_unsigned(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn write_signed(out: &mut Vec<u8>, mut value: i64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        //done once the rest is all sign and the sign bit of this byte agrees with it
+        if (value == 0 && byte & 0x40 == 0) || (value == -1 && byte & 0x40 != 0) {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+fn read_byte<R: Read>(reader: &mut R) -> Result<u8, WireError> {
+    let mut byte = [0];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+//the raw bytes of one varint, at most the 10 a 64 bit value can need
+fn read_varint<R: Read>(reader: &mut R) -> Result<Vec<u8>, WireError> {
+    let mut bytes = Vec::with_capacity(10);
+    loop {
+        let byte = read_byte(reader)?;
+        bytes.push(byte);
+        if byte & 0x80 == 0 {
+            return Ok(bytes);
+        }
+        if bytes.len() == 10 {
+            return Err(WireError::Malformed);
+        }
+    }
+}
+
+//the varints are decoded and then written again, any difference means padding or bits past
+//64 that got dropped
+fn read_unsigned<R: Read>(reader: &mut R) -> Result<u64, WireError> {
+    let bytes = read_varint(reader)?;
+    let value = bytes.iter().enumerate().fold(0, |value, (i, &byte)| {
+        value | (byte as u64 & 0x7f) << (7 * i)
+    });
+    let mut canonical = Vec::with_capacity(10);
+    write_unsigned(&mut canonical, value);
+    if canonical != bytes {
+        return Err(WireError::NonCanonical);
+    }
+    Ok(value)
+}
+
+fn read_signed<R: Read>(reader: &mut R) -> Result<i64, WireError> {
+    let bytes = read_varint(reader)?;
+    let mut value = bytes.iter().enumerate().fold(0, |value, (i, &byte)| {
+        value | (byte as i64 & 0x7f) << (7 * i)
+    });
+    let bits = 7 * bytes.len();
+    if bits < 64 && bytes[bytes.len() - 1] & 0x40 != 0 {
+        value |= -1 << bits;
+    }
+    let mut canonical = Vec::with_capacity(10);
+    write_signed(&mut canonical, value);
+    if canonical != bytes {
+        return Err(WireError::NonCanonical);
+    }
+    Ok(value)
+}
+
+impl BigNum {
+    //the whole value in one write_all
+    pub fn write_to<W: Write>(&self, writer: &mut W) -> io::Result<()> {
+        let limbs = self.as_limbs();
+        let top_len = limbs
+            .last()
+            .map_or(1, |top| 8 - top.leading_zeros() as usize / 8);
+        let mut flags = (top_len as u8 - 1) << TOP_LEN_SHIFT;
+        if self.is_negative() {
+            flags |= NEGATIVE;
+        }
+        let mut out = Vec::with_capacity(22 + 8 * limbs.len());
+        out.push(VERSION);
+        out.push(flags);
+        write_signed(&mut out, self.exponent());
+        write_unsigned(&mut out, limbs.len() as u64);
+        if let Some((top, rest)) = limbs.split_last() {
+            for limb in rest {
+                out.extend_from_slice(&limb.to_le_bytes());
+            }
+            out.extend_from_slice(&top.to_le_bytes()[..top_len]);
+        }
+        writer.write_all(&out)
+    }
+
+    //one value written by write_to, reading nothing past it
+    //reads a byte at a time for the header, so give it a BufReader rather than a raw file
+    pub fn read_from<R: Read>(reader: &mut R) -> Result<BigNum, WireError> {
+        BigNum::read_from_limited(reader, usize::MAX)
+    }
+
+    //read_from for untrusted input, refusing values of more than max_limbs limbs before
+    //reading them
+    pub fn read_from_limited<R: Read>(
+        reader: &mut R,
+        max_limbs: usize,
+    ) -> Result<BigNum, WireError> {
+        let version = read_byte(reader)?;
+        if version != VERSION {
+            return Err(WireError::UnsupportedVersion(version));
+        }
+        let flags = read_byte(reader)?;
+        if flags & !(NEGATIVE | TOP_LEN_MASK) != 0 {
+            return Err(WireError::Malformed);
+        }
+        let neg = flags & NEGATIVE != 0;
+        let top_len = ((flags & TOP_LEN_MASK) >> TOP_LEN_SHIFT) as usize + 1;
+        let exp = read_signed(reader)?;
+        let count = read_unsigned(reader)?;
+        if count == 0 {
+            if flags != 0 || exp != 0 {
+                return Err(WireError::NonCanonical);
+            }
+            return Ok(BigNum::new());
+        }
+        if !(BigNum::MIN_EXP..=BigNum::MAX_EXP).contains(&exp) {
+            return Err(WireError::ExponentOutOfRange);
+        }
+        let count = match usize::try_from(count) {
+            Ok(count) if count <= max_limbs => count,
+            _ => {
+                return Err(WireError::TooLong {
+                    len: count,
+                    max: max_limbs,
+                })
+            }
+        };
+        //grown as the limbs arrive, a short input fails long before a huge count allocates
+        let mut limbs = Vec::with_capacity(count.min(PREALLOCATE));
+        let mut limb = [0; 8];
+        for _ in 1..count {
+            reader.read_exact(&mut limb)?;
+            limbs.push(u64::from_le_bytes(limb));
+        }
+        limb = [0; 8];
+        reader.read_exact(&mut limb[..top_len])?;
+        limbs.push(u64::from_le_bytes(limb));
+        if limbs[0] == 0 || limb[top_len - 1] == 0 {
+            return Err(WireError::NonCanonical);
+        }
+        Ok(BigNum::from_parts(neg, limbs, exp))
+    }
+}